@@ -1,11 +1,22 @@
+use avian3d::prelude::*;
 use bevy::prelude::*;
 
+use crate::game::combat::Health;
+use crate::game::console::{ConsoleAppExt, ConsoleCommand, arg};
+use crate::game::core::GameState;
+use crate::game::physics::GameLayer;
+use crate::game::player::Player;
+use crate::game::prefab::{Overrides, Prefab, PrefabAppExt, PrefabShape, PrefabVisual, Prefabs};
+use crate::game::stats::{Stat, Stats};
+use crate::game::weather::Weather;
+use crate::game::world::PedestrianSpawn;
+
 #[derive(Component, Debug)]
 pub struct FactionBrain {
     pub alert: bool,
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone, Copy)]
 pub enum AiRole {
     Pedestrian,
     Cop,
@@ -13,16 +24,286 @@ pub enum AiRole {
     Boss,
 }
 
+impl AiRole {
+    /// Base stats for a fresh NPC in this role.
+    pub fn stats(self) -> Stats {
+        let (max_health, walk_speed) = match self {
+            AiRole::Pedestrian => (60.0, 1.4),
+            AiRole::Cop => (100.0, 4.5),
+            AiRole::GangSoldier => (90.0, 4.0),
+            AiRole::Boss => (250.0, 3.5),
+        };
+        Stats::default()
+            .with_base(Stat::MaxHealth, max_health)
+            .with_base(Stat::WalkSpeed, walk_speed)
+    }
+}
+
+/// Sent when an NPC notices the player: on coming into view, and again for
+/// everyone watching when the wanted level goes up.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct PlayerSpotted {
+    pub by: Entity,
+}
+
+/// A cop sent after a wanted player, as opposed to one placed by hand.
+/// Called off when the wanted level drops.
+#[derive(Component, Debug)]
+pub struct Responder;
+
+pub const MAX_WANTED_STARS: u8 = 5;
+
+/// How hard the police are looking for the player.
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct WantedLevel {
+    pub stars: u8,
+}
+
+impl WantedLevel {
+    pub fn set(&mut self, stars: u8) {
+        self.stars = stars.min(MAX_WANTED_STARS);
+    }
+
+    pub fn clear(&mut self) {
+        self.stars = 0;
+    }
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct AiConfig {
+    /// How far NPCs can spot the player in clear weather.
+    pub vision_range: f32,
+    /// Pedestrians kept around the player in clear weather.
+    pub pedestrian_count: usize,
+    pub pedestrian_spawn_radius: f32,
+    pub pedestrian_despawn_radius: f32,
+    /// Responders kept around the player per wanted star.
+    pub cops_per_star: usize,
+    /// Close enough that responders see the player on arrival.
+    pub cop_spawn_radius: f32,
+}
+
+impl Default for AiConfig {
+    fn default() -> Self {
+        Self {
+            vision_range: 30.0,
+            pedestrian_count: 12,
+            pedestrian_spawn_radius: 35.0,
+            pedestrian_despawn_radius: 60.0,
+            cops_per_star: 2,
+            cop_spawn_radius: 20.0,
+        }
+    }
+}
+
+impl AiConfig {
+    pub fn vision_range(&self, weather: &Weather) -> f32 {
+        self.vision_range * weather.effects.vision
+    }
+
+    pub fn pedestrian_target(&self, weather: &Weather) -> usize {
+        (self.pedestrian_count as f32 * weather.effects.pedestrian_density).round() as usize
+    }
+
+    pub fn cop_target(&self, wanted: &WantedLevel) -> usize {
+        wanted.stars as usize * self.cops_per_star
+    }
+}
+
+const NPC_BODY: PrefabShape = PrefabShape::Capsule {
+    radius: 0.3,
+    length: 1.0,
+};
+
+fn npc_prefab(name: &'static str, color: Color, role: AiRole) -> Prefab {
+    Prefab::new(name)
+        .body(RigidBody::Dynamic, NPC_BODY, GameLayer::Npc)
+        .visual(PrefabVisual::new(NPC_BODY, color))
+        .with(move |npc| {
+            let stats = role.stats();
+            npc.insert((
+                role,
+                FactionBrain { alert: false },
+                LockedAxes::ROTATION_LOCKED,
+                // Kept in step with the stats from here on.
+                Health::new(stats.get(Stat::MaxHealth)),
+                stats,
+            ));
+        })
+}
+
 pub struct AiPlugin;
 
 impl Plugin for AiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, tick_ai);
+        app.init_resource::<WantedLevel>()
+            .init_resource::<AiConfig>()
+            .add_message::<PlayerSpotted>()
+            .register_prefab(npc_prefab(
+                "pedestrian",
+                Color::srgb(0.7, 0.6, 0.5),
+                AiRole::Pedestrian,
+            ))
+            .register_prefab(npc_prefab("cop", Color::srgb(0.15, 0.2, 0.55), AiRole::Cop))
+            // Placed with a `FactionId` and recoloured to match it.
+            .register_prefab(npc_prefab(
+                "gang_soldier",
+                Color::srgb(0.3, 0.3, 0.3),
+                AiRole::GangSoldier,
+            ))
+            .register_prefab(npc_prefab(
+                "gang_boss",
+                Color::srgb(0.1, 0.1, 0.1),
+                AiRole::Boss,
+            ))
+            .add_console_command(ConsoleCommand::new(
+                "set wanted",
+                "<stars>",
+                "Set the wanted level.",
+                set_wanted,
+            ))
+            .add_systems(
+                Update,
+                (populate_pedestrians, populate_cops, tick_ai).run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+fn set_wanted(world: &mut World, args: &[&str]) -> anyhow::Result<String> {
+    let stars: u8 = arg(args, 0, "stars")?;
+    let mut wanted = world.resource_mut::<WantedLevel>();
+    wanted.set(stars);
+    Ok(format!("wanted level {}", wanted.stars))
+}
+
+/// Tops the crowd up to the weather-scaled target around the player and
+/// thins it out when there are too many or they've been left behind.
+fn populate_pedestrians(
+    mut prefabs: Prefabs,
+    config: Res<AiConfig>,
+    weather: Res<Weather>,
+    mut spawned: Local<u32>,
+    player: Query<&Transform, With<Player>>,
+    pedestrians: Query<(Entity, &AiRole, &Transform)>,
+    spawn_points: Query<&Transform, (With<PedestrianSpawn>, Without<AiRole>)>,
+) {
+    let Ok(player) = player.single() else {
+        return;
+    };
+    let mut count = 0;
+    for (entity, role, transform) in &pedestrians {
+        if !matches!(role, AiRole::Pedestrian) {
+            continue;
+        }
+        let far =
+            transform.translation.distance(player.translation) > config.pedestrian_despawn_radius;
+        if far || count >= config.pedestrian_target(&weather) {
+            prefabs.commands.entity(entity).despawn();
+        } else {
+            count += 1;
+        }
+    }
+    if count >= config.pedestrian_target(&weather) {
+        return;
+    }
+
+    // One per frame, from the streamed-in spawn points out of sight, or
+    // spread around the player by the golden angle where there are none.
+    *spawned += 1;
+    let candidates: Vec<Vec3> = spawn_points
+        .iter()
+        .map(|transform| transform.translation)
+        .filter(|point| {
+            let distance = point.distance(player.translation);
+            distance >= config.pedestrian_spawn_radius * 0.5
+                && distance < config.pedestrian_despawn_radius
+        })
+        .collect();
+    let offset = match candidates.len() {
+        0 => {
+            let angle = *spawned as f32 * 2.399_963;
+            Vec3::new(angle.cos(), 0.0, angle.sin()) * config.pedestrian_spawn_radius
+        }
+        len => candidates[*spawned as usize % len] - player.translation,
+    };
+    let position = Vec3::new(
+        player.translation.x + offset.x,
+        1.0,
+        player.translation.z + offset.z,
+    );
+    if let Err(err) = prefabs.spawn(
+        "pedestrian",
+        Overrides::at(Transform::from_translation(position)),
+    ) {
+        error!("{err}");
+    }
+}
+
+/// Sends responders after the player as the wanted level rises and calls
+/// them off as it falls, or when the player has left them behind.
+fn populate_cops(
+    mut prefabs: Prefabs,
+    config: Res<AiConfig>,
+    wanted: Res<WantedLevel>,
+    mut spawned: Local<u32>,
+    player: Query<&Transform, With<Player>>,
+    responders: Query<(Entity, &Transform), With<Responder>>,
+) {
+    let Ok(player) = player.single() else {
+        return;
+    };
+    let target = config.cop_target(&wanted);
+    let mut count = 0;
+    for (entity, transform) in &responders {
+        let far =
+            transform.translation.distance(player.translation) > config.pedestrian_despawn_radius;
+        if far || count >= target {
+            prefabs.commands.entity(entity).despawn();
+        } else {
+            count += 1;
+        }
+    }
+    if count >= target {
+        return;
+    }
+
+    *spawned += 1;
+    let angle = *spawned as f32 * 2.399_963;
+    let offset = Vec3::new(angle.cos(), 0.0, angle.sin()) * config.cop_spawn_radius;
+    let position = Vec3::new(
+        player.translation.x + offset.x,
+        1.0,
+        player.translation.z + offset.z,
+    );
+    match prefabs.spawn("cop", Overrides::at(Transform::from_translation(position))) {
+        Ok(mut cop) => {
+            cop.insert(Responder);
+        }
+        Err(err) => error!("{err}"),
     }
 }
 
-fn tick_ai(mut query: Query<(&AiRole, &mut FactionBrain)>) {
-    for (_role, mut brain) in &mut query {
-        brain.alert = brain.alert || false;
+fn tick_ai(
+    config: Res<AiConfig>,
+    weather: Res<Weather>,
+    wanted: Res<WantedLevel>,
+    mut last_stars: Local<u8>,
+    player: Query<&Transform, With<Player>>,
+    mut query: Query<(Entity, &Transform, &mut FactionBrain)>,
+    mut spotted: MessageWriter<PlayerSpotted>,
+) {
+    let Ok(player) = player.single() else {
+        return;
+    };
+    let range = config.vision_range(&weather);
+    let wanted_rose = wanted.stars > *last_stars;
+    *last_stars = wanted.stars;
+    for (entity, transform, mut brain) in &mut query {
+        let sees_player = transform.translation.distance(player.translation) <= range;
+        if sees_player && (!brain.alert || wanted_rose) {
+            spotted.write(PlayerSpotted { by: entity });
+        }
+        // Losing sight of the player lets them be spotted afresh.
+        brain.alert = sees_player;
     }
 }
@@ -1,5 +1,22 @@
+//! Sound playback and mixing. The sound files under `assets/` (`audio/`,
+//! `radio/` and `music/`) ship separately from the source; without them the
+//! game runs silent and says so once at startup.
+
+use std::collections::HashSet;
+
+use avian3d::prelude::LinearVelocity;
+use bevy::asset::io::file::FileAssetReader;
+use bevy::audio::{PlaybackMode, SpatialListener, Volume};
 use bevy::prelude::*;
 
+use crate::game::camera::TopDownCamera;
+use crate::game::combat::WeaponFired;
+use crate::game::core::GameState;
+use crate::game::dialogue::Subtitle;
+use crate::game::player::{GroundContact, Player};
+use crate::game::stats::{Stat, Stats};
+use crate::game::vehicle::Vehicle;
+
 #[derive(Resource)]
 pub struct AudioSettings {
     pub master_volume: f32,
@@ -17,10 +34,568 @@ impl Default for AudioSettings {
     }
 }
 
+impl AudioSettings {
+    pub fn bus_volume(&self, bus: AudioBus) -> f32 {
+        let bus_volume = match bus {
+            AudioBus::Music => self.music_volume,
+            AudioBus::Sfx => self.sfx_volume,
+        };
+        (self.master_volume * bus_volume).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AudioBus {
+    Music,
+    Sfx,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SoundCategory {
+    Music,
+    Dialogue,
+    Weapon,
+    Vehicle,
+    Footstep,
+    Ambience,
+    Ui,
+}
+
+impl SoundCategory {
+    pub fn bus(self) -> AudioBus {
+        match self {
+            SoundCategory::Music => AudioBus::Music,
+            _ => AudioBus::Sfx,
+        }
+    }
+
+    /// Higher wins when the voice pool is full.
+    pub fn default_priority(self) -> u8 {
+        match self {
+            SoundCategory::Music | SoundCategory::Dialogue | SoundCategory::Ui => 255,
+            SoundCategory::Weapon => 200,
+            SoundCategory::Vehicle => 150,
+            SoundCategory::Ambience => 80,
+            SoundCategory::Footstep => 40,
+        }
+    }
+
+    /// Music streams outside the pool so it can never be stolen.
+    fn pooled(self) -> bool {
+        self != SoundCategory::Music
+    }
+}
+
+/// Play a sound. With an `emitter` the sound is spatial and follows that entity.
+#[derive(Message, Debug, Clone)]
+pub struct PlaySound {
+    pub sound: Handle<AudioSource>,
+    pub category: SoundCategory,
+    pub volume: f32,
+    pub priority: u8,
+    pub emitter: Option<Entity>,
+    pub looped: bool,
+}
+
+impl PlaySound {
+    pub fn new(sound: Handle<AudioSource>, category: SoundCategory) -> Self {
+        Self {
+            sound,
+            category,
+            volume: 1.0,
+            priority: category.default_priority(),
+            emitter: None,
+            looped: false,
+        }
+    }
+
+    pub fn at(mut self, emitter: Entity) -> Self {
+        self.emitter = Some(emitter);
+        self
+    }
+
+    pub fn looped(mut self) -> Self {
+        self.looped = true;
+        self
+    }
+
+    pub fn with_volume(mut self, volume: f32) -> Self {
+        self.volume = volume;
+        self
+    }
+}
+
+/// A playing sound owned by the mixer.
+#[derive(Component, Debug, Clone)]
+pub struct SoundVoice {
+    pub category: SoundCategory,
+    pub priority: u8,
+    pub volume: f32,
+    /// Start order, used to steal the oldest of equal-priority voices.
+    pub serial: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoiceAllocation {
+    Free,
+    Steal(Entity),
+    Reject,
+}
+
+/// Decides whether a new voice fits, given what's already playing.
+#[derive(Debug, Clone)]
+pub struct VoicePool {
+    pub max_voices: usize,
+}
+
+impl VoicePool {
+    pub fn allocate<'a>(
+        &self,
+        active: impl IntoIterator<Item = (Entity, &'a SoundVoice)>,
+        priority: u8,
+    ) -> VoiceAllocation {
+        let mut count = 0;
+        let mut weakest: Option<(Entity, &SoundVoice)> = None;
+        for (entity, voice) in active {
+            if !voice.category.pooled() {
+                continue;
+            }
+            count += 1;
+            let weaker = weakest
+                .is_none_or(|(_, w)| (voice.priority, voice.serial) < (w.priority, w.serial));
+            if weaker {
+                weakest = Some((entity, voice));
+            }
+        }
+
+        if count < self.max_voices {
+            return VoiceAllocation::Free;
+        }
+        match weakest {
+            Some((entity, voice)) if voice.priority < priority => VoiceAllocation::Steal(entity),
+            _ => VoiceAllocation::Reject,
+        }
+    }
+}
+
+/// Runtime mixer state layered on top of [`AudioSettings`].
+#[derive(Resource, Debug, Clone)]
+pub struct AudioMixer {
+    pub pool: VoicePool,
+    /// Music level while dialogue is playing.
+    pub duck_level: f32,
+    /// How fast the music duck fades in and out, per second.
+    pub duck_speed: f32,
+    pub music_duck: f32,
+    next_serial: u64,
+}
+
+impl Default for AudioMixer {
+    fn default() -> Self {
+        Self {
+            pool: VoicePool { max_voices: 24 },
+            duck_level: 0.35,
+            duck_speed: 3.0,
+            music_duck: 1.0,
+            next_serial: 0,
+        }
+    }
+}
+
+impl AudioMixer {
+    /// A new voice record, stamped with the next start serial.
+    pub fn voice(&mut self, category: SoundCategory, priority: u8, volume: f32) -> SoundVoice {
+        self.next_serial += 1;
+        SoundVoice {
+            category,
+            priority,
+            volume,
+            serial: self.next_serial,
+        }
+    }
+
+    /// Final linear gain for a voice after bus routing and ducking.
+    pub fn gain(&self, settings: &AudioSettings, voice: &SoundVoice) -> f32 {
+        let bus = voice.category.bus();
+        let duck = if bus == AudioBus::Music {
+            self.music_duck
+        } else {
+            1.0
+        };
+        voice.volume * settings.bus_volume(bus) * duck
+    }
+}
+
+/// Handles for the built-in gameplay sounds.
+#[derive(Resource, Debug, Clone)]
+pub struct SoundLibrary {
+    pub footstep: Handle<AudioSource>,
+    pub gunshot: Handle<AudioSource>,
+    pub engine: Handle<AudioSource>,
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct FootstepConfig {
+    pub walk_interval: f32,
+    pub min_speed: f32,
+}
+
+impl Default for FootstepConfig {
+    fn default() -> Self {
+        Self {
+            walk_interval: 0.45,
+            min_speed: 1.0,
+        }
+    }
+}
+
+/// Looping engine voice spawned for a vehicle.
+#[derive(Component, Debug)]
+struct EngineVoice;
+
 pub struct AudioPlugin;
 
 impl Plugin for AudioPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<AudioSettings>();
+        app.init_resource::<AudioSettings>()
+            .init_resource::<AudioMixer>()
+            .init_resource::<FootstepConfig>()
+            .add_message::<PlaySound>()
+            .add_systems(Startup, (warn_missing_audio, load_sound_library))
+            .add_systems(
+                Update,
+                (
+                    attach_listener,
+                    (
+                        play_footsteps,
+                        play_gunfire,
+                        play_voice_lines,
+                        start_engine_loops,
+                    )
+                        .run_if(in_state(GameState::InGame)),
+                    start_sounds,
+                    drop_failed_voices,
+                    (update_ducking, update_voice_gain, update_engine_pitch),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Asset folders the sound library, radio and score load from.
+const AUDIO_DIRS: [&str; 3] = ["audio", "radio", "music"];
+
+fn warn_missing_audio() {
+    let root = FileAssetReader::get_base_path().join("assets");
+    let missing: Vec<&str> = AUDIO_DIRS
+        .into_iter()
+        .filter(|dir| !root.join(dir).is_dir())
+        .collect();
+    if !missing.is_empty() {
+        warn!(
+            "no {} under {}; the audio ships separately and the game will be silent without it",
+            missing.join(", "),
+            root.display()
+        );
+    }
+}
+
+fn load_sound_library(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(SoundLibrary {
+        footstep: asset_server.load("audio/footstep.ogg"),
+        gunshot: asset_server.load("audio/gunshot.ogg"),
+        engine: asset_server.load("audio/engine_loop.ogg"),
+    });
+}
+
+fn attach_listener(
+    mut commands: Commands,
+    cameras: Query<Entity, (With<TopDownCamera>, Without<SpatialListener>)>,
+) {
+    for camera in &cameras {
+        commands.entity(camera).insert(SpatialListener::new(2.0));
+    }
+}
+
+fn start_sounds(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut requests: MessageReader<PlaySound>,
+    mut mixer: ResMut<AudioMixer>,
+    settings: Res<AudioSettings>,
+    voices: Query<(Entity, &SoundVoice)>,
+) {
+    // Voices stolen this frame are still in the query until commands apply.
+    let mut stolen = Vec::new();
+    let mut started = Vec::new();
+    for request in requests.read() {
+        // A clip that's known to be missing would only take a voice slot.
+        if asset_server.load_state(&request.sound).is_failed() {
+            continue;
+        }
+        if request.category.pooled() {
+            let active = voices
+                .iter()
+                .filter(|(entity, _)| !stolen.contains(entity))
+                .chain(started.iter().map(|(e, v)| (*e, v)));
+            match mixer.pool.allocate(active, request.priority) {
+                VoiceAllocation::Free => {}
+                VoiceAllocation::Steal(entity) => {
+                    commands.entity(entity).despawn();
+                    stolen.push(entity);
+                }
+                VoiceAllocation::Reject => continue,
+            }
+        }
+        let voice = mixer.voice(request.category, request.priority, request.volume);
+
+        let playback = PlaybackSettings {
+            mode: if request.looped {
+                PlaybackMode::Loop
+            } else {
+                PlaybackMode::Despawn
+            },
+            volume: Volume::Linear(mixer.gain(&settings, &voice)),
+            spatial: request.emitter.is_some(),
+            ..default()
+        };
+        let mut entity = commands.spawn((
+            AudioPlayer(request.sound.clone()),
+            playback,
+            voice.clone(),
+            Transform::IDENTITY,
+        ));
+        if let Some(emitter) = request.emitter {
+            entity.insert(ChildOf(emitter));
+        }
+        started.push((entity.id(), voice));
+    }
+}
+
+/// One-shot voices despawn when their sink finishes, which never happens if
+/// the clip failed to load. Drop them so they don't hold a pool slot, or duck
+/// the music forever in the case of dialogue.
+fn drop_failed_voices(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut warned: Local<HashSet<AssetId<AudioSource>>>,
+    voices: Query<(Entity, &AudioPlayer, &PlaybackSettings), With<SoundVoice>>,
+) {
+    for (entity, player, playback) in &voices {
+        if matches!(playback.mode, PlaybackMode::Loop)
+            || !asset_server.load_state(&player.0).is_failed()
+        {
+            continue;
+        }
+        if warned.insert(player.0.id()) {
+            warn!("dropping sound {:?}: it failed to load", player.0.path());
+        }
+        commands.entity(entity).despawn();
+    }
+}
+
+fn update_ducking(
+    time: Res<Time<Real>>,
+    mut mixer: ResMut<AudioMixer>,
+    voices: Query<&SoundVoice>,
+) {
+    let dialogue = voices
+        .iter()
+        .any(|voice| voice.category == SoundCategory::Dialogue);
+    let target = if dialogue { mixer.duck_level } else { 1.0 };
+    let step = mixer.duck_speed * time.delta_secs();
+    mixer.music_duck = if mixer.music_duck < target {
+        (mixer.music_duck + step).min(target)
+    } else {
+        (mixer.music_duck - step).max(target)
+    };
+}
+
+fn update_voice_gain(
+    settings: Res<AudioSettings>,
+    mixer: Res<AudioMixer>,
+    mut sinks: Query<(&SoundVoice, &mut AudioSink)>,
+    mut spatial_sinks: Query<(&SoundVoice, &mut SpatialAudioSink)>,
+) {
+    for (voice, mut sink) in &mut sinks {
+        sink.set_volume(Volume::Linear(mixer.gain(&settings, voice)));
+    }
+    for (voice, mut sink) in &mut spatial_sinks {
+        sink.set_volume(Volume::Linear(mixer.gain(&settings, voice)));
+    }
+}
+
+fn play_footsteps(
+    time: Res<Time>,
+    config: Res<FootstepConfig>,
+    library: Option<Res<SoundLibrary>>,
+    mut timer: Local<f32>,
+    player: Query<(Entity, &LinearVelocity, &GroundContact), With<Player>>,
+    mut sounds: MessageWriter<PlaySound>,
+) {
+    let (Some(library), Ok((entity, velocity, ground))) = (library, player.single()) else {
+        return;
+    };
+    let speed = Vec3::new(velocity.x, 0.0, velocity.z).length();
+    if !ground.grounded || speed < config.min_speed {
+        *timer = 0.0;
+        return;
+    }
+
+    // Faster movement shortens the cadence, relative to a brisk walk.
+    *timer -= time.delta_secs() * (speed / 7.5).max(0.5);
+    if *timer <= 0.0 {
+        *timer = config.walk_interval;
+        sounds.write(
+            PlaySound::new(library.footstep.clone(), SoundCategory::Footstep)
+                .at(entity)
+                .with_volume(0.6),
+        );
+    }
+}
+
+/// Every round fired plays a gunshot from the shooter.
+fn play_gunfire(
+    library: Option<Res<SoundLibrary>>,
+    mut fired: MessageReader<WeaponFired>,
+    mut sounds: MessageWriter<PlaySound>,
+) {
+    let Some(library) = library else {
+        fired.clear();
+        return;
+    };
+    for shot in fired.read() {
+        sounds
+            .write(PlaySound::new(library.gunshot.clone(), SoundCategory::Weapon).at(shot.shooter));
+    }
+}
+
+/// Voiced subtitles play on the dialogue channel, which ducks the music
+/// while they last.
+fn play_voice_lines(
+    asset_server: Res<AssetServer>,
+    mut subtitles: MessageReader<Subtitle>,
+    mut sounds: MessageWriter<PlaySound>,
+) {
+    for subtitle in subtitles.read() {
+        let Some(voice) = &subtitle.voice else {
+            continue;
+        };
+        let mut sound = PlaySound::new(asset_server.load(voice.clone()), SoundCategory::Dialogue);
+        sound.emitter = subtitle.emitter;
+        sounds.write(sound);
+    }
+}
+
+fn start_engine_loops(
+    mut commands: Commands,
+    library: Option<Res<SoundLibrary>>,
+    vehicles: Query<Entity, Added<Vehicle>>,
+    mut sounds: MessageWriter<PlaySound>,
+) {
+    let Some(library) = library else {
+        return;
+    };
+    for vehicle in &vehicles {
+        commands.entity(vehicle).insert(EngineVoice);
+        sounds.write(
+            PlaySound::new(library.engine.clone(), SoundCategory::Vehicle)
+                .at(vehicle)
+                .looped(),
+        );
+    }
+}
+
+fn update_engine_pitch(
+    vehicles: Query<(&Stats, &LinearVelocity, &Children), With<EngineVoice>>,
+    sinks: Query<(&SoundVoice, &SpatialAudioSink)>,
+) {
+    for (stats, velocity, children) in &vehicles {
+        let top_speed = stats.get(Stat::TopSpeed).max(1.0);
+        let load = (velocity.length() / top_speed).clamp(0.0, 1.0);
+        for child in children {
+            if let Ok((voice, sink)) = sinks.get(*child)
+                && voice.category == SoundCategory::Vehicle
+            {
+                sink.set_speed(0.8 + load * 0.9);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voice(category: SoundCategory, priority: u8, serial: u64) -> SoundVoice {
+        SoundVoice {
+            category,
+            priority,
+            volume: 1.0,
+            serial,
+        }
+    }
+
+    #[test]
+    fn categories_route_through_their_bus() {
+        let settings = AudioSettings {
+            master_volume: 0.5,
+            music_volume: 0.4,
+            sfx_volume: 1.0,
+        };
+        let mixer = AudioMixer::default();
+        let music = mixer.gain(&settings, &voice(SoundCategory::Music, 255, 0));
+        let weapon = mixer.gain(&settings, &voice(SoundCategory::Weapon, 200, 1));
+        assert!((music - 0.2).abs() < 1e-6);
+        assert!((weapon - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn ducking_only_affects_music() {
+        let settings = AudioSettings::default();
+        let mut mixer = AudioMixer::default();
+        let music = voice(SoundCategory::Music, 255, 0);
+        let dialogue = voice(SoundCategory::Dialogue, 255, 1);
+        let before = (
+            mixer.gain(&settings, &music),
+            mixer.gain(&settings, &dialogue),
+        );
+        mixer.music_duck = mixer.duck_level;
+        assert!(mixer.gain(&settings, &music) < before.0);
+        assert_eq!(mixer.gain(&settings, &dialogue), before.1);
+    }
+
+    #[test]
+    fn full_pool_steals_lowest_priority_oldest_voice() {
+        let pool = VoicePool { max_voices: 3 };
+        let entities = [
+            Entity::from_raw_u32(1).unwrap(),
+            Entity::from_raw_u32(2).unwrap(),
+            Entity::from_raw_u32(3).unwrap(),
+        ];
+        let voices = [
+            voice(SoundCategory::Footstep, 40, 5),
+            voice(SoundCategory::Footstep, 40, 2),
+            voice(SoundCategory::Weapon, 200, 1),
+        ];
+        let active = || entities.iter().copied().zip(voices.iter());
+
+        assert_eq!(
+            pool.allocate(active(), 200),
+            VoiceAllocation::Steal(entities[1])
+        );
+        assert_eq!(pool.allocate(active(), 40), VoiceAllocation::Reject);
+        assert_eq!(
+            VoicePool { max_voices: 4 }.allocate(active(), 0),
+            VoiceAllocation::Free
+        );
+    }
+
+    #[test]
+    fn music_does_not_count_against_the_pool() {
+        let pool = VoicePool { max_voices: 1 };
+        let music = voice(SoundCategory::Music, 255, 0);
+        let active = [(Entity::from_raw_u32(1).unwrap(), &music)];
+        assert_eq!(pool.allocate(active, 10), VoiceAllocation::Free);
     }
 }
@@ -1,8 +1,10 @@
+use avian3d::prelude::LinearVelocity;
 use bevy::prelude::*;
 
-use crate::game::core::GameState;
+use crate::game::core::{GameState, InSession};
 use crate::game::input::PlayerInput;
 use crate::game::player::Player;
+use crate::game::vehicle::Driving;
 
 #[derive(Resource)]
 pub struct TopDownCameraConfig {
@@ -12,6 +14,13 @@ pub struct TopDownCameraConfig {
     pub zoom_step: f32,
     pub min_height: f32,
     pub max_height: f32,
+    /// Seconds of velocity the follow camera leads by.
+    pub look_ahead_secs: f32,
+    pub max_look_ahead: f32,
+    /// Extra height per m/s of vehicle speed in chase mode.
+    pub vehicle_zoom_per_speed: f32,
+    pub max_vehicle_height: f32,
+    pub blend_secs: f32,
 }
 
 impl Default for TopDownCameraConfig {
@@ -23,26 +32,196 @@ impl Default for TopDownCameraConfig {
             zoom_step: 2.0,
             min_height: 8.0,
             max_height: 40.0,
+            look_ahead_secs: 0.35,
+            max_look_ahead: 6.0,
+            vehicle_zoom_per_speed: 0.6,
+            max_vehicle_height: 70.0,
+            blend_secs: 0.6,
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraPose {
+    pub translation: Vec3,
+    pub rotation: Quat,
+}
+
+impl CameraPose {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        Self {
+            translation: self.translation.lerp(other.translation, t),
+            rotation: self.rotation.slerp(other.rotation, t),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CameraKey {
+    pub time: f32,
+    pub position: Vec3,
+    pub look_at: Vec3,
+}
+
+/// Scripted camera path for cutscenes. Keys must be sorted by time.
+#[derive(Debug, Clone)]
+pub struct CinematicRail {
+    pub keys: Vec<CameraKey>,
+    pub elapsed: f32,
+}
+
+impl CinematicRail {
+    pub fn new(keys: Vec<CameraKey>) -> Self {
+        Self { keys, elapsed: 0.0 }
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.keys.last().map_or(0.0, |k| k.time)
+    }
+
+    pub fn finished(&self) -> bool {
+        self.elapsed >= self.duration()
+    }
+
+    pub fn sample(&self) -> Option<CameraPose> {
+        let first = self.keys.first()?;
+        let next_index = self
+            .keys
+            .iter()
+            .position(|k| k.time > self.elapsed)
+            .unwrap_or(self.keys.len() - 1);
+        let from = if next_index == 0 {
+            first
+        } else {
+            &self.keys[next_index - 1]
+        };
+        let to = &self.keys[next_index];
+
+        let span = to.time - from.time;
+        let t = if span > f32::EPSILON {
+            smoothstep(((self.elapsed - from.time) / span).clamp(0.0, 1.0))
+        } else {
+            1.0
+        };
+        let position = from.position.lerp(to.position, t);
+        let look_at = from.look_at.lerp(to.look_at, t);
+        Some(CameraPose {
+            translation: position,
+            rotation: Transform::from_translation(position)
+                .looking_at(look_at, Vec3::Y)
+                .rotation,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum CameraMode {
+    /// Top-down follow on the player, leading in the direction of travel.
+    Follow,
+    /// Chase a vehicle, pulling back as it speeds up.
+    VehicleChase(Entity),
+    Cinematic(CinematicRail),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CameraBlend {
+    from: CameraPose,
+    duration: f32,
+    elapsed: f32,
+}
+
+/// Active camera modes; the top of the stack drives the camera. `Follow` is
+/// always at the bottom.
+#[derive(Resource, Debug)]
+pub struct CameraModeStack {
+    modes: Vec<CameraMode>,
+    blend: Option<CameraBlend>,
+    pending_blend: Option<f32>,
+}
+
+impl Default for CameraModeStack {
+    fn default() -> Self {
+        Self {
+            modes: vec![CameraMode::Follow],
+            blend: None,
+            pending_blend: None,
+        }
+    }
+}
+
+impl CameraModeStack {
+    pub fn current(&self) -> &CameraMode {
+        self.modes.last().unwrap_or(&CameraMode::Follow)
+    }
+
+    pub fn push(&mut self, mode: CameraMode, blend_secs: f32) {
+        self.modes.push(mode);
+        self.pending_blend = Some(blend_secs);
+    }
+
+    pub fn pop(&mut self, blend_secs: f32) -> Option<CameraMode> {
+        if self.modes.len() <= 1 {
+            return None;
+        }
+        self.pending_blend = Some(blend_secs);
+        self.modes.pop()
+    }
+
+    /// Swaps the top mode without growing the stack.
+    pub fn replace(&mut self, mode: CameraMode, blend_secs: f32) {
+        if self.modes.len() > 1 {
+            self.modes.pop();
+        }
+        self.push(mode, blend_secs);
+    }
+}
+
 #[derive(Component)]
 pub struct TopDownCamera;
 
+/// Runs the system that places the camera for the active mode; effects order around it.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CameraPoseSet;
+
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<TopDownCameraConfig>().add_systems(
-            PostUpdate,
-            (ensure_camera, update_camera_pose, handle_zoom).run_if(in_state(GameState::InGame)),
-        );
+        app.init_resource::<TopDownCameraConfig>()
+            .init_resource::<CameraModeStack>()
+            .add_systems(
+                PostUpdate,
+                (
+                    ensure_camera,
+                    chase_driven_vehicle,
+                    update_camera_pose.in_set(CameraPoseSet),
+                    handle_zoom,
+                )
+                    .run_if(in_state(GameState::InGame)),
+            );
     }
 }
 
-fn camera_offset(config: &TopDownCameraConfig) -> Vec3 {
-    Vec3::Y * config.height
+fn smoothstep(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Offset from the focus point for a camera `height` above it, tilted back by the configured pitch.
+fn camera_offset(config: &TopDownCameraConfig, height: f32) -> Vec3 {
+    let pitch = config.pitch_radians.clamp(0.1, std::f32::consts::FRAC_PI_2);
+    Vec3::new(0.0, height, height * pitch.cos() / pitch.sin())
+}
+
+fn look_ahead(config: &TopDownCameraConfig, velocity: Option<&LinearVelocity>) -> Vec3 {
+    let velocity = velocity.map_or(Vec3::ZERO, |v| Vec3::new(v.x, 0.0, v.z));
+    (velocity * config.look_ahead_secs).clamp_length_max(config.max_look_ahead)
+}
+
+fn follow_pose(config: &TopDownCameraConfig, focus: Vec3, height: f32) -> CameraPose {
+    CameraPose {
+        translation: focus + camera_offset(config, height),
+        rotation: Quat::from_rotation_x(-config.pitch_radians),
+    }
 }
 
 fn ensure_camera(
@@ -59,36 +238,108 @@ fn ensure_camera(
         return;
     }
 
-    let start = player_transform.translation() + camera_offset(&config);
+    let start = follow_pose(&config, player_transform.translation(), config.height);
     commands.spawn((
         TopDownCamera,
         Camera3d::default(),
-        Transform::from_translation(start)
-            .with_rotation(Quat::from_rotation_x(-config.pitch_radians)),
+        Transform::from_translation(start.translation).with_rotation(start.rotation),
+        DespawnOnExit(InSession),
     ));
 }
 
+/// Chases the player's vehicle while they drive it.
+fn chase_driven_vehicle(
+    config: Res<TopDownCameraConfig>,
+    mut stack: ResMut<CameraModeStack>,
+    mut left: RemovedComponents<Driving>,
+    drivers: Query<&Driving, (With<Player>, Changed<Driving>)>,
+) {
+    let chasing = |stack: &CameraModeStack| matches!(stack.current(), CameraMode::VehicleChase(_));
+    if left.read().count() > 0 && chasing(&stack) {
+        stack.pop(config.blend_secs);
+    }
+    for driving in &drivers {
+        if chasing(&stack) {
+            stack.replace(CameraMode::VehicleChase(driving.0), config.blend_secs);
+        } else {
+            stack.push(CameraMode::VehicleChase(driving.0), config.blend_secs);
+        }
+    }
+}
+
 fn update_camera_pose(
     time: Res<Time>,
     config: Res<TopDownCameraConfig>,
-    player_query: Query<&GlobalTransform, With<Player>>,
+    mut stack: ResMut<CameraModeStack>,
+    player_query: Query<(&GlobalTransform, Option<&LinearVelocity>), With<Player>>,
+    targets: Query<(&GlobalTransform, Option<&LinearVelocity>)>,
     mut camera_query: Query<&mut Transform, With<TopDownCamera>>,
 ) {
-    let Ok(player_transform) = player_query.single() else {
+    let Ok(mut cam_transform) = camera_query.single_mut() else {
         return;
     };
-    let Ok(mut cam_transform) = camera_query.single_mut() else {
+    let delta = time.delta_secs();
+    let current = CameraPose {
+        translation: cam_transform.translation,
+        rotation: cam_transform.rotation,
+    };
+
+    // A mode change starts blending from wherever the camera is right now.
+    if let Some(duration) = stack.pending_blend.take() {
+        stack.blend = (duration > f32::EPSILON).then_some(CameraBlend {
+            from: current,
+            duration,
+            elapsed: 0.0,
+        });
+    }
+
+    let mut cinematic_done = false;
+    let desired = match stack.modes.last_mut() {
+        Some(CameraMode::Cinematic(rail)) => {
+            rail.elapsed += delta;
+            cinematic_done = rail.finished();
+            rail.sample()
+        }
+        Some(CameraMode::VehicleChase(vehicle)) => targets.get(*vehicle).ok().map(|(tx, vel)| {
+            let speed = vel.map_or(0.0, |v| v.length());
+            let height = (config.height + speed * config.vehicle_zoom_per_speed)
+                .min(config.max_vehicle_height);
+            follow_pose(&config, tx.translation() + look_ahead(&config, vel), height)
+        }),
+        Some(CameraMode::Follow) | None => player_query.single().ok().map(|(tx, vel)| {
+            follow_pose(
+                &config,
+                tx.translation() + look_ahead(&config, vel),
+                config.height,
+            )
+        }),
+    };
+    let is_cinematic = matches!(stack.current(), CameraMode::Cinematic(_));
+    if cinematic_done {
+        let blend_secs = config.blend_secs;
+        stack.pop(blend_secs);
+    }
+    let Some(desired) = desired else {
         return;
     };
 
-    let player_pos = player_transform.translation();
-    let target_pos = player_pos + camera_offset(&config);
-    let lerp_alpha = 1.0 - (-config.damping * time.delta_secs()).exp();
+    let pose = if let Some(blend) = stack.blend.as_mut() {
+        blend.elapsed += delta;
+        let t = (blend.elapsed / blend.duration).clamp(0.0, 1.0);
+        let pose = blend.from.lerp(desired, smoothstep(t));
+        if t >= 1.0 {
+            stack.blend = None;
+        }
+        pose
+    } else if is_cinematic {
+        desired
+    } else {
+        let lerp_alpha = (1.0 - (-config.damping * delta).exp()).clamp(0.0, 1.0);
+        current.lerp(desired, lerp_alpha)
+    };
 
-    cam_transform.translation = cam_transform
-        .translation
-        .lerp(target_pos, lerp_alpha.clamp(0.0, 1.0));
-    cam_transform.rotation = Quat::from_rotation_x(-config.pitch_radians);
+    cam_transform.translation = pose.translation;
+    cam_transform.rotation = pose.rotation;
 }
 
 fn handle_zoom(player_input: Res<PlayerInput>, mut config: ResMut<TopDownCameraConfig>) {
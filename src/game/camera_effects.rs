@@ -0,0 +1,453 @@
+use avian3d::prelude::*;
+use bevy::ecs::system::SystemParam;
+use bevy::pbr::{MeshMaterial3d, StandardMaterial};
+use bevy::prelude::*;
+
+use crate::game::camera::{CameraPoseSet, TopDownCamera};
+use crate::game::combat::{DamageEvent, WeaponFired};
+use crate::game::core::GameState;
+use crate::game::destructible::Destroyed;
+use crate::game::physics::GameLayer;
+use crate::game::player::Player;
+use crate::game::vehicle::Driving;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OcclusionMode {
+    /// Blend occluders down to `fade_alpha`.
+    Fade,
+    /// Hide occluders entirely.
+    Cutaway,
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct OcclusionConfig {
+    pub mode: OcclusionMode,
+    pub fade_alpha: f32,
+    pub fade_speed: f32,
+    pub max_occluders: u32,
+}
+
+impl Default for OcclusionConfig {
+    fn default() -> Self {
+        Self {
+            mode: OcclusionMode::Fade,
+            fade_alpha: 0.2,
+            fade_speed: 8.0,
+            max_occluders: 16,
+        }
+    }
+}
+
+/// Layers that can block the view of the player.
+const OCCLUDER_LAYERS: [GameLayer; 3] = [GameLayer::Default, GameLayer::Static, GameLayer::Prop];
+
+/// Tracks a mesh that has been faded or hidden because it blocks the camera.
+/// Holds a private copy of the material so shared materials aren't touched,
+/// and the visibility to put back once it stops blocking.
+#[derive(Component, Debug)]
+pub struct Occluding {
+    original: Handle<StandardMaterial>,
+    faded: Handle<StandardMaterial>,
+    visibility: Option<Visibility>,
+    alpha: f32,
+    blocking: bool,
+}
+
+/// How the camera wobbles for a given amount of trauma.
+#[derive(Debug, Clone, Copy)]
+pub struct ShakeProfile {
+    pub max_offset: Vec3,
+    /// Max pitch/yaw/roll in radians.
+    pub max_rotation: Vec3,
+    /// Noise samples per second; higher is more jittery.
+    pub frequency: f32,
+    /// Trauma lost per second.
+    pub decay: f32,
+    /// Shake scales with trauma^exponent so small hits stay subtle.
+    pub trauma_exponent: f32,
+    /// Player-facing multiplier from the settings menu.
+    pub intensity: f32,
+}
+
+impl Default for ShakeProfile {
+    fn default() -> Self {
+        Self {
+            max_offset: Vec3::new(0.8, 0.4, 0.8),
+            max_rotation: Vec3::new(0.03, 0.03, 0.06),
+            frequency: 18.0,
+            decay: 1.4,
+            trauma_exponent: 2.0,
+            intensity: 1.0,
+        }
+    }
+}
+
+/// Trauma-based screen shake. Noise is seeded and driven by accumulated
+/// time, so identical seeds and deltas produce identical shakes.
+#[derive(Resource, Debug, Clone)]
+pub struct CameraShake {
+    pub profile: ShakeProfile,
+    pub trauma: f32,
+    pub seed: u32,
+    pub time: f32,
+}
+
+impl Default for CameraShake {
+    fn default() -> Self {
+        Self::with_seed(0x5A1_7E5)
+    }
+}
+
+impl CameraShake {
+    pub fn with_seed(seed: u32) -> Self {
+        Self {
+            profile: ShakeProfile::default(),
+            trauma: 0.0,
+            seed,
+            time: 0.0,
+        }
+    }
+
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+
+    pub fn tick(&mut self, delta: f32) {
+        self.time += delta;
+        self.trauma = (self.trauma - self.profile.decay * delta).max(0.0);
+    }
+
+    /// Current shake as a translation offset and rotation.
+    pub fn sample(&self) -> (Vec3, Quat) {
+        let shake = self.trauma.powf(self.profile.trauma_exponent) * self.profile.intensity;
+        if shake <= f32::EPSILON {
+            return (Vec3::ZERO, Quat::IDENTITY);
+        }
+
+        let t = self.time * self.profile.frequency;
+        let channel = |c: u32| value_noise(self.seed, c, t) * shake;
+        let offset = Vec3::new(channel(0), channel(1), channel(2)) * self.profile.max_offset;
+        let angles = Vec3::new(channel(3), channel(4), channel(5)) * self.profile.max_rotation;
+        (
+            offset,
+            Quat::from_euler(EulerRot::YXZ, angles.y, angles.x, angles.z),
+        )
+    }
+}
+
+/// Request a camera shake from gameplay code without touching the resource.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct ScreenShake {
+    pub trauma: f32,
+}
+
+impl ScreenShake {
+    pub fn weapon_fire() -> Self {
+        Self { trauma: 0.12 }
+    }
+
+    /// Falls off linearly to nothing at `radius`.
+    pub fn explosion(distance: f32, radius: f32) -> Self {
+        let falloff = 1.0 - (distance / radius.max(f32::EPSILON)).clamp(0.0, 1.0);
+        Self {
+            trauma: 0.9 * falloff,
+        }
+    }
+
+    /// Scales with how much of a full health bar the hit took.
+    pub fn hit(damage: f32) -> Self {
+        Self {
+            trauma: (damage / 100.0).clamp(0.05, 0.6),
+        }
+    }
+
+    pub fn vehicle_crash(impact_speed: f32) -> Self {
+        Self {
+            trauma: (impact_speed / 30.0).clamp(0.0, 0.8),
+        }
+    }
+}
+
+/// Shake applied to the camera last frame, removed again before the pose update.
+#[derive(Component, Debug, Default)]
+struct AppliedShake {
+    offset: Vec3,
+    rotation: Quat,
+}
+
+pub struct CameraEffectsPlugin;
+
+impl Plugin for CameraEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OcclusionConfig>()
+            .init_resource::<CameraShake>()
+            .add_message::<ScreenShake>()
+            .add_systems(
+                PostUpdate,
+                (
+                    clear_camera_shake.before(CameraPoseSet),
+                    (
+                        (detect_occluders, fade_occluders).chain(),
+                        (
+                            (shake_on_combat, shake_on_crash),
+                            collect_screen_shake,
+                            apply_camera_shake,
+                        )
+                            .chain(),
+                    )
+                        .after(CameraPoseSet),
+                )
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+fn lattice(seed: u32, channel: u32, index: i32) -> f32 {
+    let mut h = seed ^ channel.wrapping_mul(0x9E37_79B9) ^ (index as u32).wrapping_mul(0x85EB_CA6B);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7FEB_352D);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846C_A68B);
+    h ^= h >> 16;
+    (h as f32 / u32::MAX as f32) * 2.0 - 1.0
+}
+
+/// Smooth 1D value noise in [-1, 1].
+fn value_noise(seed: u32, channel: u32, t: f32) -> f32 {
+    let index = t.floor();
+    let frac = t - index;
+    let a = lattice(seed, channel, index as i32);
+    let b = lattice(seed, channel, index as i32 + 1);
+    a.lerp(b, frac * frac * (3.0 - 2.0 * frac))
+}
+
+/// The line from the camera to the player that occluders are searched along.
+#[derive(SystemParam)]
+struct Sightline<'w, 's> {
+    camera: Query<'w, 's, &'static GlobalTransform, With<TopDownCamera>>,
+    player: Query<'w, 's, &'static GlobalTransform, With<Player>>,
+}
+
+impl Sightline<'_, '_> {
+    /// Camera position, direction to the player and distance to them.
+    fn ray(&self) -> Option<(Vec3, Dir3, f32)> {
+        let origin = self.camera.single().ok()?.translation();
+        let to_player = self.player.single().ok()?.translation() - origin;
+        let direction = Dir3::new(to_player).ok()?;
+        Some((origin, direction, to_player.length()))
+    }
+}
+
+fn detect_occluders(
+    mut commands: Commands,
+    config: Res<OcclusionConfig>,
+    spatial_query: SpatialQuery,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    sightline: Sightline,
+    children: Query<&Children>,
+    mut meshes: Query<(
+        &mut MeshMaterial3d<StandardMaterial>,
+        Option<&mut Occluding>,
+        Option<&Visibility>,
+    )>,
+) {
+    for (_, occluding, _) in &mut meshes {
+        if let Some(mut occluding) = occluding {
+            occluding.blocking = false;
+        }
+    }
+
+    let Some((origin, direction, distance)) = sightline.ray() else {
+        return;
+    };
+    let hits = spatial_query.ray_hits(
+        origin,
+        direction,
+        distance,
+        config.max_occluders,
+        true,
+        &SpatialQueryFilter::from_mask(OCCLUDER_LAYERS),
+    );
+
+    let mut seen = Vec::new();
+    for hit in hits {
+        let targets = std::iter::once(hit.entity).chain(children.iter_descendants(hit.entity));
+        for target in targets {
+            if seen.contains(&target) {
+                continue;
+            }
+            seen.push(target);
+            let Ok((mut material, occluding, visibility)) = meshes.get_mut(target) else {
+                continue;
+            };
+            if let Some(mut occluding) = occluding {
+                occluding.blocking = true;
+                continue;
+            }
+
+            let original = material.0.clone();
+            let Some(mut faded) = materials.get(&original).cloned() else {
+                continue;
+            };
+            faded.alpha_mode = AlphaMode::Blend;
+            let faded = materials.add(faded);
+            material.0 = faded.clone();
+            commands.entity(target).insert(Occluding {
+                original,
+                faded,
+                visibility: visibility.copied(),
+                alpha: 1.0,
+                blocking: true,
+            });
+        }
+    }
+}
+
+fn fade_occluders(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<OcclusionConfig>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut query: Query<(
+        Entity,
+        &mut Occluding,
+        &mut MeshMaterial3d<StandardMaterial>,
+        Option<&mut Visibility>,
+    )>,
+) {
+    let step = config.fade_speed * time.delta_secs();
+    for (entity, mut occluding, mut material, visibility) in &mut query {
+        let target = if occluding.blocking {
+            config.fade_alpha
+        } else {
+            1.0
+        };
+        occluding.alpha = if occluding.alpha < target {
+            (occluding.alpha + step).min(target)
+        } else {
+            (occluding.alpha - step).max(target)
+        };
+
+        if let (Some(mut visibility), Some(previous)) = (visibility, occluding.visibility) {
+            let hidden = config.mode == OcclusionMode::Cutaway && occluding.blocking;
+            visibility.set_if_neq(if hidden { Visibility::Hidden } else { previous });
+        }
+
+        if !occluding.blocking && occluding.alpha >= 1.0 {
+            // Fully restored: go back to the shared material and drop our copy.
+            material.0 = occluding.original.clone();
+            materials.remove(&occluding.faded);
+            commands.entity(entity).remove::<Occluding>();
+            continue;
+        }
+
+        let alpha = match config.mode {
+            OcclusionMode::Fade => occluding.alpha,
+            OcclusionMode::Cutaway => 1.0,
+        };
+        if let Some(faded) = materials.get_mut(&occluding.faded) {
+            faded.base_color.set_alpha(alpha);
+        }
+    }
+}
+
+/// Props breaking further away than this don't reach the camera.
+const DESTRUCTION_SHAKE_RADIUS: f32 = 10.0;
+
+/// Below this, vehicle contacts are just the tyres on the road.
+const CRASH_MIN_SPEED: f32 = 3.0;
+
+/// The player's own shots, hits they take and nearby wreckage.
+fn shake_on_combat(
+    mut fired: MessageReader<WeaponFired>,
+    mut damage: MessageReader<DamageEvent>,
+    mut destroyed: MessageReader<Destroyed>,
+    player: Query<(Entity, &GlobalTransform), With<Player>>,
+    mut shakes: MessageWriter<ScreenShake>,
+) {
+    let Ok((player, transform)) = player.single() else {
+        fired.clear();
+        damage.clear();
+        destroyed.clear();
+        return;
+    };
+    for _ in fired.read().filter(|shot| shot.shooter == player) {
+        shakes.write(ScreenShake::weapon_fire());
+    }
+    for hit in damage.read().filter(|hit| hit.entity == player) {
+        shakes.write(ScreenShake::hit(hit.amount));
+    }
+    for wreck in destroyed.read() {
+        let distance = wreck.position.distance(transform.translation());
+        if distance < DESTRUCTION_SHAKE_RADIUS {
+            shakes.write(ScreenShake::explosion(distance, DESTRUCTION_SHAKE_RADIUS));
+        }
+    }
+}
+
+/// Hard knocks to the vehicle the player is driving, measured as the speed
+/// change the hardest contact impulse caused this frame.
+fn shake_on_crash(
+    collisions: Collisions,
+    driver: Query<&Driving, With<Player>>,
+    vehicles: Query<&ComputedMass>,
+    mut shakes: MessageWriter<ScreenShake>,
+) {
+    let Ok(Driving(vehicle)) = driver.single() else {
+        return;
+    };
+    let Ok(mass) = vehicles.get(*vehicle) else {
+        return;
+    };
+    let impulse = collisions
+        .collisions_with(*vehicle)
+        .map(|contacts| contacts.max_normal_impulse_magnitude())
+        .fold(0.0, f32::max);
+    let impact_speed = impulse * mass.inverse();
+    if impact_speed >= CRASH_MIN_SPEED {
+        shakes.write(ScreenShake::vehicle_crash(impact_speed));
+    }
+}
+
+fn collect_screen_shake(
+    time: Res<Time>,
+    mut requests: MessageReader<ScreenShake>,
+    mut shake: ResMut<CameraShake>,
+) {
+    for request in requests.read() {
+        shake.add_trauma(request.trauma);
+    }
+    shake.tick(time.delta_secs());
+}
+
+fn clear_camera_shake(
+    mut camera_query: Query<(&mut Transform, &AppliedShake), With<TopDownCamera>>,
+) {
+    for (mut transform, applied) in &mut camera_query {
+        transform.translation -= applied.offset;
+        transform.rotation *= applied.rotation.inverse();
+    }
+}
+
+fn apply_camera_shake(
+    mut commands: Commands,
+    shake: Res<CameraShake>,
+    mut camera_query: Query<
+        (Entity, &mut Transform, Option<&mut AppliedShake>),
+        With<TopDownCamera>,
+    >,
+) {
+    let (offset, rotation) = shake.sample();
+    for (entity, mut transform, applied) in &mut camera_query {
+        transform.translation += offset;
+        transform.rotation *= rotation;
+        match applied {
+            Some(mut applied) => *applied = AppliedShake { offset, rotation },
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(AppliedShake { offset, rotation });
+            }
+        }
+    }
+}
@@ -1,5 +1,14 @@
+use avian3d::prelude::*;
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 
+use crate::game::core::GameState;
+use crate::game::input::PlayerInput;
+use crate::game::physics::GameLayer;
+use crate::game::player::{Player, PlayerFacing};
+use crate::game::stats::{Stat, Stats};
+use crate::game::vehicle::Driving;
+
 #[derive(Message, Debug, Clone)]
 pub struct DamageEvent {
     pub entity: Entity,
@@ -7,6 +16,43 @@ pub struct DamageEvent {
     pub source: Option<Entity>,
 }
 
+/// Sent once when damage takes an entity's health to zero.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct Killed {
+    pub entity: Entity,
+    pub by: Option<Entity>,
+}
+
+/// Sent for every round fired, hit or miss.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct WeaponFired {
+    pub shooter: Entity,
+    pub weapon: &'static str,
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct GunplayConfig {
+    /// Seconds between rounds while the trigger is held.
+    pub fire_interval: f32,
+    pub range: f32,
+    pub damage: f32,
+    /// Height above the shooter's origin that rounds leave from.
+    pub muzzle_height: f32,
+}
+
+impl Default for GunplayConfig {
+    fn default() -> Self {
+        Self {
+            fire_interval: 0.2,
+            range: 60.0,
+            damage: 20.0,
+            muzzle_height: 0.4,
+        }
+    }
+}
+
 #[derive(Component, Debug, Clone)]
 pub struct Health {
     pub current: f32,
@@ -31,19 +77,189 @@ impl Health {
     }
 }
 
+/// Absorbs damage before [`Health`] takes any.
+#[derive(Component, Debug, Clone)]
+pub struct Armor {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Armor {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+
+    /// Soaks up as much of `amount` as possible and returns what's left over.
+    pub fn absorb(&mut self, amount: f32) -> f32 {
+        let absorbed = amount.min(self.current);
+        self.current -= absorbed;
+        amount - absorbed
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Weapon {
+    pub name: &'static str,
+    pub clip: u32,
+    pub clip_size: u32,
+    pub reserve: u32,
+    /// Half-angle of the firing cone before any [`Stat::WeaponSpread`] modifiers.
+    pub spread_degrees: f32,
+}
+
+impl Weapon {
+    /// Effective cone half-angle in radians for whoever is holding it.
+    pub fn spread_radians(&self, holder: Option<&Stats>) -> f32 {
+        let multiplier = holder.map_or(1.0, |stats| stats.get(Stat::WeaponSpread));
+        (self.spread_degrees * multiplier).max(0.0).to_radians()
+    }
+}
+
+#[derive(Component, Debug, Clone, Default)]
+pub struct WeaponInventory {
+    pub weapons: Vec<Weapon>,
+    pub equipped: usize,
+}
+
+impl WeaponInventory {
+    pub fn equipped(&self) -> Option<&Weapon> {
+        self.weapons.get(self.equipped)
+    }
+}
+
 pub struct CombatPlugin;
 
 impl Plugin for CombatPlugin {
     fn build(&self, app: &mut App) {
-        app.add_message::<DamageEvent>()
-            .add_systems(Update, (apply_damage, prune_dead));
+        app.init_resource::<GunplayConfig>()
+            .add_message::<DamageEvent>()
+            .add_message::<Killed>()
+            .add_message::<WeaponFired>()
+            .add_systems(
+                Update,
+                (
+                    fire_player_weapon.run_if(in_state(GameState::InGame)),
+                    apply_damage,
+                    prune_dead,
+                )
+                    .chain(),
+            );
+    }
+}
+
+type Gunner<'a> = (
+    Entity,
+    &'a Transform,
+    &'a PlayerFacing,
+    &'a mut WeaponInventory,
+    Option<&'a Stats>,
+);
+
+/// Where rounds go once they leave the barrel.
+#[derive(SystemParam)]
+struct Ballistics<'w, 's> {
+    config: Res<'w, GunplayConfig>,
+    spatial_query: SpatialQuery<'w, 's>,
+    targets: Query<'w, 's, (), With<Health>>,
+    fired: MessageWriter<'w, WeaponFired>,
+    damage: MessageWriter<'w, DamageEvent>,
+}
+
+impl Ballistics<'_, '_> {
+    /// Announces the shot and damages the first thing with health along it.
+    fn shoot(&mut self, shooter: Entity, weapon: &'static str, yaw: f32, from: Vec3) {
+        let direction = Quat::from_rotation_y(yaw) * Dir3::NEG_Z;
+        let origin = from + Vec3::Y * self.config.muzzle_height;
+        self.fired.write(WeaponFired {
+            shooter,
+            weapon,
+            origin,
+            direction: *direction,
+        });
+
+        let filter = GameLayer::Projectile
+            .query_filter()
+            .with_excluded_entities([shooter]);
+        let Some(hit) =
+            self.spatial_query
+                .cast_ray(origin, direction, self.config.range, true, &filter)
+        else {
+            return;
+        };
+        if self.targets.contains(hit.entity) {
+            self.damage.write(DamageEvent {
+                entity: hit.entity,
+                amount: self.config.damage,
+                source: Some(shooter),
+            });
+        }
+    }
+}
+
+/// Fires the equipped weapon along the player's facing while the trigger is
+/// held, reloading from reserve once the clip runs dry.
+fn fire_player_weapon(
+    time: Res<Time>,
+    input: Res<PlayerInput>,
+    mut cooldown: Local<f32>,
+    mut shots: Local<u32>,
+    mut player: Query<Gunner, (With<Player>, Without<Driving>)>,
+    mut ballistics: Ballistics,
+) {
+    *cooldown = (*cooldown - time.delta_secs()).max(0.0);
+    let Ok((shooter, transform, facing, mut inventory, stats)) = player.single_mut() else {
+        return;
+    };
+    if !input.fire_primary || *cooldown > 0.0 {
+        return;
+    }
+    let equipped = inventory.equipped;
+    let Some(weapon) = inventory.weapons.get_mut(equipped) else {
+        return;
+    };
+    *cooldown = ballistics.config.fire_interval;
+    if weapon.clip == 0 {
+        let reload = weapon.reserve.min(weapon.clip_size);
+        weapon.reserve -= reload;
+        weapon.clip = reload;
+        return;
     }
+    weapon.clip -= 1;
+
+    // Walk the spread around the cone rather than rolling dice, so the same
+    // burst always lands the same way.
+    *shots = shots.wrapping_add(1);
+    let spread = weapon.spread_radians(stats) * (*shots as f32 * 2.399).sin();
+    ballistics.shoot(
+        shooter,
+        weapon.name,
+        facing.yaw + spread,
+        transform.translation,
+    );
 }
 
-fn apply_damage(mut events: MessageReader<DamageEvent>, mut query: Query<&mut Health>) {
+fn apply_damage(
+    mut events: MessageReader<DamageEvent>,
+    mut kills: MessageWriter<Killed>,
+    mut query: Query<(&mut Health, Option<&mut Armor>)>,
+) {
     for event in events.read() {
-        if let Ok(mut health) = query.get_mut(event.entity) {
-            health.apply(event.amount);
+        if let Ok((mut health, armor)) = query.get_mut(event.entity) {
+            if health.invulnerable {
+                continue;
+            }
+            let amount = match armor {
+                Some(mut armor) => armor.absorb(event.amount),
+                None => event.amount,
+            };
+            let was_alive = health.current > 0.0;
+            health.apply(amount);
+            if was_alive && health.current <= 0.0 {
+                kills.write(Killed {
+                    entity: event.entity,
+                    by: event.source,
+                });
+            }
         }
     }
 }
@@ -0,0 +1,419 @@
+use std::collections::VecDeque;
+use std::str::FromStr;
+
+use anyhow::{Context, Result, bail};
+use bevy::input::ButtonState;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+use bevy::ui::{FlexDirection, PositionType, UiRect, Val};
+
+use crate::game::input::KeyboardCaptured;
+
+/// Runs a command with the words after its name. The returned text is
+/// echoed to the console.
+pub type ConsoleHandler = fn(&mut World, &[&str]) -> Result<String>;
+
+/// Suggestions for a command's first argument.
+pub type ConsoleCompletions = fn(&World) -> Vec<String>;
+
+#[derive(Clone)]
+pub struct ConsoleCommand {
+    /// One or more words, e.g. `"give cash"`.
+    pub name: &'static str,
+    /// Argument synopsis shown by `help`, e.g. `"<amount>"`.
+    pub args: &'static str,
+    pub help: &'static str,
+    pub run: ConsoleHandler,
+    pub completions: Option<ConsoleCompletions>,
+}
+
+impl ConsoleCommand {
+    pub fn new(
+        name: &'static str,
+        args: &'static str,
+        help: &'static str,
+        run: ConsoleHandler,
+    ) -> Self {
+        Self {
+            name,
+            args,
+            help,
+            run,
+            completions: None,
+        }
+    }
+
+    pub fn with_completions(mut self, completions: ConsoleCompletions) -> Self {
+        self.completions = Some(completions);
+        self
+    }
+
+    pub fn usage(&self) -> String {
+        if self.args.is_empty() {
+            self.name.to_string()
+        } else {
+            format!("{} {}", self.name, self.args)
+        }
+    }
+}
+
+/// Every registered command. Plugins add theirs with
+/// [`ConsoleAppExt::add_console_command`].
+#[derive(Resource, Default, Clone)]
+pub struct ConsoleCommands {
+    pub commands: Vec<ConsoleCommand>,
+}
+
+impl ConsoleCommands {
+    /// The command with the longest name matching the start of `words`.
+    pub fn resolve<'a>(&self, words: &'a [&'a str]) -> Option<(&ConsoleCommand, &'a [&'a str])> {
+        self.commands
+            .iter()
+            .filter_map(|command| {
+                let name: Vec<_> = command.name.split(' ').collect();
+                let matches = words.len() >= name.len()
+                    && name
+                        .iter()
+                        .zip(words)
+                        .all(|(a, b)| a.eq_ignore_ascii_case(b));
+                matches.then_some((command, name.len()))
+            })
+            .max_by_key(|(_, len)| *len)
+            .map(|(command, len)| (command, &words[len..]))
+    }
+}
+
+pub trait ConsoleAppExt {
+    fn add_console_command(&mut self, command: ConsoleCommand) -> &mut Self;
+}
+
+impl ConsoleAppExt for App {
+    fn add_console_command(&mut self, command: ConsoleCommand) -> &mut Self {
+        self.init_resource::<ConsoleCommands>()
+            .world_mut()
+            .resource_mut::<ConsoleCommands>()
+            .commands
+            .push(command);
+        self
+    }
+}
+
+/// Parses and runs one console line. Also the entry point for tests.
+pub fn run_console_command(world: &mut World, line: &str) -> Result<String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    if words.is_empty() {
+        return Ok(String::new());
+    }
+    let commands = world.resource::<ConsoleCommands>();
+    let Some((command, args)) = commands.resolve(&words) else {
+        bail!("unknown command `{}`; try `help`", words[0]);
+    };
+    let (run, usage) = (command.run, command.usage());
+    run(world, args).with_context(|| format!("usage: {usage}"))
+}
+
+/// Full-line completions for `input`: command names first, then values for
+/// the first argument of a fully typed command.
+pub fn complete(world: &World, input: &str) -> Vec<String> {
+    let lowered = input.to_ascii_lowercase();
+    let mut candidates = Vec::new();
+    for command in &world.resource::<ConsoleCommands>().commands {
+        if command.name.starts_with(&lowered) {
+            candidates.push(command.name.to_string());
+            continue;
+        }
+        let Some(rest) = lowered.strip_prefix(command.name) else {
+            continue;
+        };
+        let Some(partial) = rest.strip_prefix(' ') else {
+            continue;
+        };
+        if partial.contains(' ') {
+            continue;
+        }
+        if let Some(completions) = command.completions {
+            candidates.extend(
+                completions(world)
+                    .into_iter()
+                    .filter(|option| option.to_ascii_lowercase().starts_with(partial))
+                    .map(|option| format!("{} {option}", command.name)),
+            );
+        }
+    }
+    candidates.sort();
+    candidates.dedup();
+    candidates
+}
+
+/// Parses `args[index]`, naming it in the error.
+pub fn arg<T: FromStr>(args: &[&str], index: usize, name: &str) -> Result<T> {
+    let value = args
+        .get(index)
+        .with_context(|| format!("missing <{name}>"))?;
+    value
+        .parse()
+        .map_err(|_| anyhow::anyhow!("bad <{name}> `{value}`"))
+}
+
+/// Registry and the built-in `help`. The drop-down itself is
+/// [`ConsoleUiPlugin`].
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConsoleCommands>()
+            .add_console_command(ConsoleCommand::new("help", "", "List every command.", help));
+    }
+}
+
+fn help(world: &mut World, _args: &[&str]) -> Result<String> {
+    let mut lines: Vec<_> = world
+        .resource::<ConsoleCommands>()
+        .commands
+        .iter()
+        .map(|command| format!("{:<28} {}", command.usage(), command.help))
+        .collect();
+    lines.sort();
+    Ok(lines.join("\n"))
+}
+
+#[derive(Resource, Debug)]
+pub struct ConsoleState {
+    pub open: bool,
+    pub input: String,
+    pub log: VecDeque<String>,
+    pub log_capacity: usize,
+    pub history: Vec<String>,
+    history_cursor: Option<usize>,
+    suggestions: Vec<String>,
+    pending: Vec<String>,
+    tab_pressed: bool,
+    input_edited: bool,
+}
+
+impl Default for ConsoleState {
+    fn default() -> Self {
+        Self {
+            open: false,
+            input: String::new(),
+            log: VecDeque::new(),
+            log_capacity: 64,
+            history: Vec::new(),
+            history_cursor: None,
+            suggestions: Vec::new(),
+            pending: Vec::new(),
+            tab_pressed: false,
+            input_edited: false,
+        }
+    }
+}
+
+impl ConsoleState {
+    fn print(&mut self, text: &str) {
+        for line in text.lines() {
+            self.log.push_back(line.to_string());
+        }
+        while self.log.len() > self.log_capacity {
+            self.log.pop_front();
+        }
+    }
+
+    fn complete_input(&mut self, candidates: &[String]) {
+        match candidates {
+            [] => {}
+            [only] => self.input = format!("{only} "),
+            [first, rest @ ..] => {
+                let common = rest.iter().fold(first.len(), |len, other| {
+                    first
+                        .bytes()
+                        .zip(other.bytes())
+                        .take(len)
+                        .take_while(|(a, b)| a == b)
+                        .count()
+                });
+                if common > self.input.len() {
+                    self.input = first[..common].to_string();
+                } else {
+                    self.print(&candidates.join("  "));
+                }
+            }
+        }
+    }
+
+    fn browse_history(&mut self, older: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+        let last = self.history.len() - 1;
+        self.history_cursor = match (self.history_cursor, older) {
+            (None, true) => Some(last),
+            (None, false) => None,
+            (Some(index), true) => Some(index.saturating_sub(1)),
+            (Some(index), false) if index < last => Some(index + 1),
+            (Some(_), false) => None,
+        };
+        self.input = self
+            .history_cursor
+            .map_or_else(String::new, |index| self.history[index].clone());
+    }
+}
+
+#[derive(Component)]
+struct ConsoleRoot;
+
+const TOGGLE_KEY: KeyCode = KeyCode::Backquote;
+
+/// Backtick drop-down with history (Up/Down) and Tab completion.
+pub struct ConsoleUiPlugin;
+
+impl Plugin for ConsoleUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConsoleState>().add_systems(
+            Update,
+            (
+                toggle_console,
+                edit_console_input,
+                execute_console_input,
+                render_console,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn toggle_console(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut console: ResMut<ConsoleState>,
+    mut captured: ResMut<KeyboardCaptured>,
+) {
+    if keyboard.just_pressed(TOGGLE_KEY) {
+        console.open = !console.open;
+        captured.0 = console.open;
+    }
+}
+
+fn edit_console_input(mut keys: MessageReader<KeyboardInput>, mut console: ResMut<ConsoleState>) {
+    if !console.open {
+        keys.clear();
+        return;
+    }
+    for key in keys.read() {
+        if key.state != ButtonState::Pressed || key.key_code == TOGGLE_KEY {
+            continue;
+        }
+        match &key.logical_key {
+            Key::Enter => {
+                let line = std::mem::take(&mut console.input);
+                console.history_cursor = None;
+                if !line.trim().is_empty() {
+                    console.history.push(line.clone());
+                    console.pending.push(line);
+                }
+            }
+            Key::Backspace => {
+                console.input.pop();
+            }
+            Key::ArrowUp => console.browse_history(true),
+            Key::ArrowDown => console.browse_history(false),
+            // Completion needs the whole world; `execute_console_input` does it.
+            Key::Tab => console.tab_pressed = true,
+            Key::Space => console.input.push(' '),
+            Key::Character(text) => console.input.push_str(text),
+            _ => continue,
+        }
+        console.input_edited = true;
+    }
+}
+
+/// Runs submitted lines and anything else that needs `&mut World`.
+fn execute_console_input(world: &mut World) {
+    let (pending, tab_pressed, input_edited) = {
+        let mut console = world.resource_mut::<ConsoleState>();
+        let console = console.bypass_change_detection();
+        (
+            std::mem::take(&mut console.pending),
+            std::mem::take(&mut console.tab_pressed),
+            std::mem::take(&mut console.input_edited),
+        )
+    };
+    for line in pending {
+        let result = run_console_command(world, &line);
+        let mut console = world.resource_mut::<ConsoleState>();
+        console.print(&format!("> {line}"));
+        match result {
+            Ok(output) => console.print(&output),
+            Err(err) => console.print(&format!("error: {err:#}")),
+        }
+    }
+    if tab_pressed {
+        let candidates = complete(world, &world.resource::<ConsoleState>().input);
+        world
+            .resource_mut::<ConsoleState>()
+            .complete_input(&candidates);
+    }
+    if tab_pressed || input_edited {
+        let suggestions = complete(world, &world.resource::<ConsoleState>().input);
+        world.resource_mut::<ConsoleState>().suggestions = suggestions;
+    }
+}
+
+fn render_console(
+    mut commands: Commands,
+    console: Res<ConsoleState>,
+    roots: Query<Entity, With<ConsoleRoot>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+    for root in &roots {
+        commands.entity(root).despawn();
+    }
+    if !console.open {
+        return;
+    }
+
+    let log = console.log.iter().cloned().collect::<Vec<_>>().join("\n");
+    let hint = if console.input.is_empty() {
+        "Tab completes, Up/Down for history, ` closes".to_string()
+    } else {
+        console
+            .suggestions
+            .iter()
+            .take(6)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("   ")
+    };
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(0.0),
+            width: Val::Percent(100.0),
+            height: Val::Percent(40.0),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::FlexEnd,
+            padding: UiRect::all(Val::Px(10.0)),
+            overflow: Overflow::clip(),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.02, 0.02, 0.03, 0.92)),
+        GlobalZIndex(200),
+        ConsoleRoot,
+        children![
+            console_text(log, Color::srgb(0.8, 0.82, 0.85)),
+            console_text(format!("> {}_", console.input), Color::WHITE),
+            console_text(hint, Color::srgb(0.5, 0.55, 0.6)),
+        ],
+    ));
+}
+
+fn console_text(text: impl Into<String>, color: Color) -> impl Bundle {
+    (
+        Text::new(text),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(color),
+    )
+}
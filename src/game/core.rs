@@ -1,5 +1,7 @@
 use bevy::prelude::*;
 
+use crate::game::console::{ConsoleAppExt, ConsoleCommand, arg};
+
 #[derive(States, Default, Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum GameState {
     #[default]
@@ -9,6 +11,20 @@ pub enum GameState {
     Paused,
 }
 
+/// Active while a game is running, whether or not it's paused. Spawn gameplay
+/// content on entering this rather than `GameState::InGame` so resuming from
+/// pause doesn't spawn it twice.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InSession;
+
+impl ComputedStates for InSession {
+    type SourceStates = GameState;
+
+    fn compute(state: GameState) -> Option<Self> {
+        matches!(state, GameState::InGame | GameState::Paused).then_some(InSession)
+    }
+}
+
 #[derive(Resource, Default, Debug)]
 pub struct TimeScale(pub f32);
 
@@ -17,7 +33,14 @@ pub struct CorePlugin;
 impl Plugin for CorePlugin {
     fn build(&self, app: &mut App) {
         app.init_state::<GameState>()
+            .add_computed_state::<InSession>()
             .init_resource::<TimeScale>()
+            .add_console_command(ConsoleCommand::new(
+                "timescale",
+                "<factor>",
+                "Speed up or slow down game time.",
+                set_time_scale,
+            ))
             .add_systems(Startup, bootstrap)
             .add_systems(OnEnter(GameState::Loading), finish_loading)
             .add_systems(OnEnter(GameState::Paused), pause_time)
@@ -25,12 +48,28 @@ impl Plugin for CorePlugin {
     }
 }
 
+fn set_time_scale(world: &mut World, args: &[&str]) -> anyhow::Result<String> {
+    let factor: f32 = arg(args, 0, "factor")?;
+    if !(0.01..=10.0).contains(&factor) {
+        anyhow::bail!("factor must be between 0.01 and 10");
+    }
+    // While paused the clock is stopped; the new speed applies on resume.
+    if *world.resource::<State<GameState>>().get() == GameState::Paused {
+        world.resource_mut::<TimeScale>().0 = factor;
+    } else {
+        world
+            .resource_mut::<Time<Virtual>>()
+            .set_relative_speed(factor);
+    }
+    Ok(format!("time scale {factor}"))
+}
+
 fn bootstrap(mut commands: Commands) {
     commands.insert_resource(ClearColor(Color::srgb(0.01, 0.01, 0.015)));
 }
 
 fn finish_loading(mut next_state: ResMut<NextState<GameState>>) {
-    next_state.set(GameState::InGame);
+    next_state.set(GameState::MainMenu);
 }
 
 fn pause_time(mut time: ResMut<Time<Virtual>>, mut time_scale: ResMut<TimeScale>) {
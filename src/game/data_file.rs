@@ -0,0 +1,70 @@
+//! Helpers shared by the hand-written data files under the game directory,
+//! such as levels and dialogue.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ron::error::SpannedError;
+
+/// A problem in a data file, pointing at the offending line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataError {
+    pub path: PathBuf,
+    /// 1-based; 0 when the whole file is at fault.
+    pub line: usize,
+    /// 1-based; 0 when only the line is known.
+    pub column: usize,
+    pub message: String,
+}
+
+impl DataError {
+    /// Where RON reading stopped and why.
+    pub fn from_ron(path: &Path, err: SpannedError) -> DataError {
+        DataError {
+            path: path.to_path_buf(),
+            line: err.position.line,
+            column: err.position.col,
+            message: err.code.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (0, _) => write!(f, "{}: {}", self.path.display(), self.message),
+            (line, 0) => write!(f, "{}:{line}: {}", self.path.display(), self.message),
+            (line, column) => write!(
+                f,
+                "{}:{line}:{column}: {}",
+                self.path.display(),
+                self.message
+            ),
+        }
+    }
+}
+
+/// Reads a whole data file, blaming the file itself if that fails.
+pub fn read_data_file(path: &Path) -> Result<String, DataError> {
+    fs::read_to_string(path).map_err(|err| DataError {
+        path: path.to_path_buf(),
+        line: 0,
+        column: 0,
+        message: err.to_string(),
+    })
+}
+
+/// Files in `directory` ending in `.{extension}`, sorted. Empty if it
+/// doesn't exist.
+pub fn data_paths(directory: &Path, extension: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == extension))
+        .collect();
+    paths.sort();
+    paths
+}
@@ -1,10 +1,144 @@
+use avian3d::prelude::{PhysicsDebugPlugin, PhysicsGizmos};
 use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 
+use crate::game::input::keyboard_free;
+use crate::game::physics::PhysicsConfig;
+
+/// Which debug overlays are showing. Only exists in `dev` builds.
+#[derive(Resource, Debug, Clone)]
+pub struct DebugOverlays {
+    pub menu: bool,
+    pub inspector: bool,
+    pub world_grid: bool,
+    pub player: bool,
+    pub triggers: bool,
+    pub colliders: bool,
+}
+
+impl Default for DebugOverlays {
+    fn default() -> Self {
+        Self {
+            menu: false,
+            inspector: true,
+            world_grid: true,
+            player: true,
+            triggers: true,
+            colliders: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Overlay {
+    Menu,
+    Inspector,
+    WorldGrid,
+    Player,
+    Triggers,
+    Colliders,
+}
+
+impl Overlay {
+    const ALL: [Overlay; 6] = [
+        Overlay::Menu,
+        Overlay::Inspector,
+        Overlay::WorldGrid,
+        Overlay::Player,
+        Overlay::Triggers,
+        Overlay::Colliders,
+    ];
+
+    fn hotkey(self) -> KeyCode {
+        match self {
+            Overlay::Menu => KeyCode::F1,
+            Overlay::Inspector => KeyCode::F2,
+            Overlay::WorldGrid => KeyCode::F3,
+            Overlay::Player => KeyCode::F4,
+            Overlay::Triggers => KeyCode::F5,
+            Overlay::Colliders => KeyCode::F6,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Overlay::Menu => "Debug menu",
+            Overlay::Inspector => "World inspector",
+            Overlay::WorldGrid => "Grid and axes",
+            Overlay::Player => "Player vision and velocity",
+            Overlay::Triggers => "Trigger volumes",
+            Overlay::Colliders => "Physics colliders",
+        }
+    }
+
+    fn flag(self, overlays: &mut DebugOverlays) -> &mut bool {
+        match self {
+            Overlay::Menu => &mut overlays.menu,
+            Overlay::Inspector => &mut overlays.inspector,
+            Overlay::WorldGrid => &mut overlays.world_grid,
+            Overlay::Player => &mut overlays.player,
+            Overlay::Triggers => &mut overlays.triggers,
+            Overlay::Colliders => &mut overlays.colliders,
+        }
+    }
+}
+
 pub struct DebugPlugin;
 
 impl Plugin for DebugPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(WorldInspectorPlugin::new());
+        if !app.is_plugin_added::<PhysicsDebugPlugin>() {
+            app.add_plugins(PhysicsDebugPlugin);
+        }
+        let colliders = app
+            .world()
+            .get_resource::<PhysicsConfig>()
+            .is_some_and(|config| config.enable_debug_render);
+        app.insert_resource(DebugOverlays {
+            colliders,
+            ..default()
+        })
+        .add_plugins(
+            WorldInspectorPlugin::new().run_if(|overlays: Res<DebugOverlays>| overlays.inspector),
+        )
+        .add_systems(
+            Update,
+            (toggle_overlays.run_if(keyboard_free), sync_collider_gizmos).chain(),
+        )
+        .add_systems(EguiPrimaryContextPass, debug_menu);
+    }
+}
+
+fn toggle_overlays(keyboard: Res<ButtonInput<KeyCode>>, mut overlays: ResMut<DebugOverlays>) {
+    for overlay in Overlay::ALL {
+        if keyboard.just_pressed(overlay.hotkey()) {
+            let flag = overlay.flag(&mut overlays);
+            *flag = !*flag;
+        }
+    }
+}
+
+fn sync_collider_gizmos(overlays: Res<DebugOverlays>, mut store: ResMut<GizmoConfigStore>) {
+    if overlays.is_changed() {
+        store.config_mut::<PhysicsGizmos>().0.enabled = overlays.colliders;
+    }
+}
+
+fn debug_menu(mut contexts: EguiContexts, mut overlays: ResMut<DebugOverlays>) -> Result {
+    if !overlays.menu {
+        return Ok(());
     }
+    let mut open = true;
+    egui::Window::new("Debug")
+        .open(&mut open)
+        .resizable(false)
+        .show(contexts.ctx_mut()?, |ui| {
+            for overlay in Overlay::ALL.into_iter().skip(1) {
+                let label = format!("{} ({:?})", overlay.label(), overlay.hotkey());
+                ui.checkbox(overlay.flag(&mut overlays), label);
+            }
+        });
+    overlays.menu = open;
+    Ok(())
 }
@@ -0,0 +1,210 @@
+use avian3d::prelude::*;
+use bevy::pbr::{MeshMaterial3d, StandardMaterial};
+use bevy::prelude::*;
+
+use crate::game::combat::DamageEvent;
+use crate::game::core::{GameState, InSession};
+use crate::game::physics::GameLayer;
+use crate::game::pickup::{DropPickup, PickupEffect};
+
+/// A prop that breaks into debris once its health runs out or something
+/// hits it hard enough.
+#[derive(Component, Debug, Clone)]
+pub struct Destructible {
+    pub health: f32,
+    /// Contact impulse (N·s) that breaks it outright.
+    pub impulse_threshold: f32,
+    /// Full extents, split into debris pieces.
+    pub size: Vec3,
+    /// Debris pieces along each axis.
+    pub fracture: UVec3,
+    pub loot: Option<PickupEffect>,
+}
+
+impl Destructible {
+    pub fn new(health: f32, impulse_threshold: f32, size: Vec3) -> Self {
+        Self {
+            health,
+            impulse_threshold,
+            size,
+            fracture: UVec3::splat(2),
+            loot: None,
+        }
+    }
+
+    pub fn with_fracture(mut self, fracture: UVec3) -> Self {
+        self.fracture = fracture.max(UVec3::ONE);
+        self
+    }
+
+    pub fn with_loot(mut self, loot: PickupEffect) -> Self {
+        self.loot = Some(loot);
+        self
+    }
+
+    pub fn is_broken(&self) -> bool {
+        self.health <= 0.0
+    }
+}
+
+/// A fragment of a broken [`Destructible`].
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Debris {
+    pub age: f32,
+}
+
+/// Sent when a [`Destructible`] breaks.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct Destroyed {
+    pub entity: Entity,
+    pub position: Vec3,
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct DestructionConfig {
+    /// Oldest debris is removed first once there's more than this.
+    pub max_debris: usize,
+    pub debris_lifetime: f32,
+    /// Outward speed added to debris when something breaks.
+    pub burst_speed: f32,
+}
+
+impl Default for DestructionConfig {
+    fn default() -> Self {
+        Self {
+            max_debris: 96,
+            debris_lifetime: 12.0,
+            burst_speed: 2.5,
+        }
+    }
+}
+
+pub struct DestructiblePlugin;
+
+impl Plugin for DestructiblePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DestructionConfig>()
+            .add_message::<Destroyed>()
+            .add_systems(
+                Update,
+                (
+                    damage_destructibles,
+                    break_on_impact,
+                    shatter_destructibles,
+                    clean_up_debris,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+fn damage_destructibles(
+    mut events: MessageReader<DamageEvent>,
+    mut destructibles: Query<&mut Destructible>,
+) {
+    for event in events.read() {
+        if let Ok(mut destructible) = destructibles.get_mut(event.entity) {
+            destructible.health -= event.amount;
+        }
+    }
+}
+
+fn break_on_impact(collisions: Collisions, mut destructibles: Query<(Entity, &mut Destructible)>) {
+    for (entity, mut destructible) in &mut destructibles {
+        let hardest = collisions
+            .collisions_with(entity)
+            .map(|contacts| contacts.max_normal_impulse_magnitude())
+            .fold(0.0, f32::max);
+        if hardest >= destructible.impulse_threshold {
+            destructible.health = 0.0;
+        }
+    }
+}
+
+type Shattering<'a> = (
+    Entity,
+    &'a Destructible,
+    &'a Transform,
+    Option<&'a LinearVelocity>,
+    Option<&'a MeshMaterial3d<StandardMaterial>>,
+);
+
+fn shatter_destructibles(
+    mut commands: Commands,
+    config: Res<DestructionConfig>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    destructibles: Query<Shattering>,
+    mut destroyed: MessageWriter<Destroyed>,
+    mut drops: MessageWriter<DropPickup>,
+) {
+    for (entity, destructible, transform, velocity, material) in &destructibles {
+        if !destructible.is_broken() {
+            continue;
+        }
+        commands.entity(entity).despawn();
+        destroyed.write(Destroyed {
+            entity,
+            position: transform.translation,
+        });
+        if let Some(loot) = &destructible.loot {
+            drops.write(DropPickup {
+                effect: loot.clone(),
+                position: transform.translation.with_y(0.6),
+            });
+        }
+
+        let fracture = destructible.fracture.max(UVec3::ONE);
+        let piece = destructible.size / fracture.as_vec3();
+        let mesh = meshes.add(Cuboid::from_size(piece));
+        let inherited = velocity.map_or(Vec3::ZERO, |velocity| velocity.0);
+        for x in 0..fracture.x {
+            for y in 0..fracture.y {
+                for z in 0..fracture.z {
+                    // Centre of this piece relative to the prop's centre.
+                    let local =
+                        (UVec3::new(x, y, z).as_vec3() + 0.5) * piece - destructible.size * 0.5;
+                    let outward = (transform.rotation * local).normalize_or_zero();
+                    let mut fragment = commands.spawn((
+                        Debris::default(),
+                        RigidBody::Dynamic,
+                        Collider::cuboid(piece.x, piece.y, piece.z),
+                        GameLayer::Debris.layers(),
+                        LinearVelocity(inherited + outward * config.burst_speed),
+                        Mesh3d(mesh.clone()),
+                        transform.mul_transform(Transform::from_translation(local)),
+                        DespawnOnExit(InSession),
+                        Name::new("Debris"),
+                    ));
+                    if let Some(material) = material {
+                        fragment.insert(material.clone());
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn clean_up_debris(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<DestructionConfig>,
+    mut debris: Query<(Entity, &mut Debris)>,
+) {
+    let mut live = Vec::new();
+    for (entity, mut piece) in &mut debris {
+        piece.age += time.delta_secs();
+        if piece.age > config.debris_lifetime {
+            commands.entity(entity).despawn();
+        } else {
+            live.push((entity, piece.age));
+        }
+    }
+    if live.len() > config.max_debris {
+        live.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let excess = live.len() - config.max_debris;
+        for (entity, _) in live.into_iter().take(excess) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,874 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use avian3d::prelude::*;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use ron::extensions::Extensions;
+use serde::Deserialize;
+use serde::de::{Deserializer, Error as _, SeqAccess, Visitor};
+
+use crate::game::ai::{AiRole, PlayerSpotted, WantedLevel};
+use crate::game::combat::DamageEvent;
+use crate::game::core::{GameState, InSession};
+use crate::game::data_file::{DataError, data_paths, read_data_file};
+use crate::game::faction::{FactionId, FactionRespect};
+use crate::game::input::{NUMBER_KEYS, PlayerInput, keyboard_free};
+use crate::game::mission::{CompleteMission, MissionCatalog, MissionLog};
+use crate::game::physics::GameLayer;
+use crate::game::player::Player;
+use crate::game::prefab::{Prefab, PrefabAppExt, PrefabShape, PrefabVisual};
+use crate::game::ui::Toast;
+
+/// Who a bark is written for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum BarkSpeaker {
+    Pedestrian,
+    Cop,
+    Gang,
+}
+
+impl BarkSpeaker {
+    pub fn of(role: &AiRole) -> Self {
+        match role {
+            AiRole::Pedestrian => BarkSpeaker::Pedestrian,
+            AiRole::Cop => BarkSpeaker::Cop,
+            AiRole::GangSoldier | AiRole::Boss => BarkSpeaker::Gang,
+        }
+    }
+}
+
+/// What makes an NPC bark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum BarkTrigger {
+    /// They were hit, or something near them was.
+    ShotAt,
+    /// They just noticed the player.
+    Spotted,
+    /// A gang member from another faction is close.
+    Rival,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Compare {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Compare {
+    pub fn test(self, left: i32, right: i32) -> bool {
+        match self {
+            Compare::Less => left < right,
+            Compare::LessOrEqual => left <= right,
+            Compare::Equal => left == right,
+            Compare::GreaterOrEqual => left >= right,
+            Compare::Greater => left > right,
+        }
+    }
+}
+
+/// Gate on a bark, choice or redirect.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum Condition {
+    /// `Respect(StreetOracles, GreaterOrEqual, 10)`
+    Respect(FactionId, Compare, i32),
+    /// `Wanted(GreaterOrEqual, 1)`, in stars.
+    Wanted(Compare, i32),
+    /// `MissionActive("oracle_errand")`
+    MissionActive(String),
+    /// `MissionDone("oracle_errand")`
+    MissionDone(String),
+    /// No mission is active.
+    Idle,
+    /// `Not(Idle)`
+    Not(Box<Condition>),
+}
+
+impl Condition {
+    pub fn holds(&self, context: &DialogueContext) -> bool {
+        match self {
+            Condition::Respect(faction, compare, value) => {
+                compare.test(context.respect.get(*faction), *value)
+            }
+            Condition::Wanted(compare, value) => {
+                compare.test(i32::from(context.wanted.stars), *value)
+            }
+            Condition::MissionActive(id) => context
+                .missions
+                .active
+                .as_ref()
+                .is_some_and(|mission| mission.id == id),
+            Condition::MissionDone(id) => context.missions.completed.contains(&id.as_str()),
+            Condition::Idle => context.missions.active.is_none(),
+            Condition::Not(condition) => !condition.holds(context),
+        }
+    }
+}
+
+/// Game state conditions are checked against.
+pub struct DialogueContext<'a> {
+    pub respect: &'a FactionRespect,
+    pub missions: &'a MissionLog,
+    pub wanted: &'a WantedLevel,
+}
+
+fn allowed(condition: &Option<Condition>, context: &DialogueContext) -> bool {
+    condition
+        .as_ref()
+        .is_none_or(|condition| condition.holds(context))
+}
+
+/// Lines an NPC shouts in reaction to something. One is picked each time,
+/// cycling through `lines`.
+#[derive(Debug, Clone)]
+pub struct Bark {
+    pub speaker: BarkSpeaker,
+    pub trigger: BarkTrigger,
+    /// `{faction}` and `{rival}` are replaced with faction names.
+    pub lines: Vec<String>,
+    pub voice: Option<String>,
+    pub when: Option<Condition>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum DialogueAction {
+    StartMission(String),
+    CompleteMission,
+    Respect(FactionId, i32),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Choice {
+    pub text: String,
+    /// Node to go to; `None` ends the conversation.
+    #[serde(rename = "to", default)]
+    pub target: Option<String>,
+    #[serde(default)]
+    pub when: Option<Condition>,
+}
+
+/// One line of a conversation and the player's possible replies.
+#[derive(Debug, Clone)]
+pub struct DialogueNode {
+    pub id: String,
+    /// Defaults to the mission giver's name.
+    pub speaker: Option<String>,
+    pub text: String,
+    pub voice: Option<String>,
+    /// Checked in order on entry; the first that holds replaces this node.
+    pub redirects: Vec<(Option<String>, Option<Condition>)>,
+    pub choices: Vec<Choice>,
+    pub actions: Vec<DialogueAction>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DialogueFile {
+    pub barks: Vec<Bark>,
+    pub nodes: Vec<DialogueNode>,
+}
+
+/// Parses a dialogue file: a RON list of barks and conversation nodes.
+/// Fields with a default can be left out, and `//` starts a comment:
+///
+/// ```text
+/// [
+///     Bark(
+///         speaker: Cop,
+///         on: Spotted,
+///         when: Wanted(GreaterOrEqual, 1),
+///         lines: ["Freeze!", "Hands where I can see them!"],
+///     ),
+///     Node(
+///         id: "oracle_hello",
+///         text: "The cards said you'd come.",
+///         next: [(to: "oracle_busy", when: Not(Idle))],
+///         choices: [
+///             (text: "What do they say?", to: "oracle_offer"),
+///             (text: "Not today."),
+///         ],
+///     ),
+/// ]
+/// ```
+///
+/// A choice or redirect without `to` closes the conversation. Errors point
+/// at the line and column where reading stopped.
+pub fn parse_dialogue(path: &Path, text: &str) -> Result<DialogueFile, DataError> {
+    ron::Options::default()
+        .with_default_extension(Extensions::IMPLICIT_SOME)
+        .from_str(text)
+        .map_err(|err| DataError::from_ron(path, err))
+}
+
+/// A `next` entry on a node.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Redirect {
+    #[serde(default)]
+    to: Option<String>,
+    #[serde(default)]
+    when: Option<Condition>,
+}
+
+/// One entry as written in a dialogue file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+enum EntryDef {
+    Bark {
+        speaker: BarkSpeaker,
+        on: BarkTrigger,
+        lines: Vec<String>,
+        #[serde(default)]
+        voice: Option<String>,
+        #[serde(default)]
+        when: Option<Condition>,
+    },
+    Node {
+        id: String,
+        #[serde(default)]
+        speaker: Option<String>,
+        text: String,
+        #[serde(default)]
+        voice: Option<String>,
+        #[serde(default)]
+        next: Vec<Redirect>,
+        #[serde(default)]
+        choices: Vec<Choice>,
+        #[serde(default)]
+        actions: Vec<DialogueAction>,
+    },
+}
+
+impl<'de> Deserialize<'de> for DialogueFile {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(DialogueVisitor)
+    }
+}
+
+/// Reads the entry list, checking what serde can't as it goes so problems
+/// are reported where they occur. Links are checked once every node is in,
+/// since conversations are self-contained and resolve within the file.
+struct DialogueVisitor;
+
+impl<'de> Visitor<'de> for DialogueVisitor {
+    type Value = DialogueFile;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a list of barks and nodes")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<DialogueFile, A::Error> {
+        let mut file = DialogueFile::default();
+        while let Some(entry) = seq.next_element::<EntryDef>()? {
+            match entry {
+                EntryDef::Bark {
+                    speaker,
+                    on,
+                    lines,
+                    voice,
+                    when,
+                } => {
+                    if lines.is_empty() {
+                        return Err(A::Error::custom("bark needs at least one line"));
+                    }
+                    file.barks.push(Bark {
+                        speaker,
+                        trigger: on,
+                        lines,
+                        voice,
+                        when,
+                    });
+                }
+                EntryDef::Node {
+                    id,
+                    speaker,
+                    text,
+                    voice,
+                    next,
+                    choices,
+                    actions,
+                } => {
+                    if file.nodes.iter().any(|node| node.id == id) {
+                        return Err(A::Error::custom(format!("duplicate node `{id}`")));
+                    }
+                    file.nodes.push(DialogueNode {
+                        id,
+                        speaker,
+                        text,
+                        voice,
+                        redirects: next
+                            .into_iter()
+                            .map(|redirect| (redirect.to, redirect.when))
+                            .collect(),
+                        choices,
+                        actions,
+                    });
+                }
+            }
+        }
+
+        for node in &file.nodes {
+            let targets = node
+                .redirects
+                .iter()
+                .map(|(target, _)| target)
+                .chain(node.choices.iter().map(|choice| &choice.target));
+            for target in targets.flatten() {
+                if !file.nodes.iter().any(|other| other.id == *target) {
+                    return Err(A::Error::custom(format!(
+                        "`{}` links to unknown node `{target}`",
+                        node.id
+                    )));
+                }
+            }
+        }
+        Ok(file)
+    }
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct DialogueConfig {
+    /// Every `*.dialogue` file here is loaded at the start of a session.
+    pub directory: PathBuf,
+    /// How long an NPC waits before barking again.
+    pub bark_cooldown: f32,
+    /// Gap between any two barks, so a crowd doesn't talk over itself.
+    pub global_bark_cooldown: f32,
+    /// NPCs this close to something that gets shot react to it, and gang
+    /// taunts further than this from the player go unsaid.
+    pub hearing_radius: f32,
+    /// Gang members this close to a rival taunt them.
+    pub rival_range: f32,
+    /// How close the player has to be to talk to a mission giver.
+    pub talk_radius: f32,
+    /// Subtitle time per character, on top of [`Self::min_subtitle_secs`].
+    pub secs_per_char: f32,
+    pub min_subtitle_secs: f32,
+}
+
+impl Default for DialogueConfig {
+    fn default() -> Self {
+        Self {
+            directory: PathBuf::from("dialogue"),
+            bark_cooldown: 6.0,
+            global_bark_cooldown: 1.0,
+            hearing_radius: 15.0,
+            rival_range: 12.0,
+            talk_radius: 3.5,
+            secs_per_char: 0.05,
+            min_subtitle_secs: 1.5,
+        }
+    }
+}
+
+impl DialogueConfig {
+    pub fn subtitle_secs(&self, text: &str) -> f32 {
+        self.min_subtitle_secs + text.chars().count() as f32 * self.secs_per_char
+    }
+}
+
+/// Everything loaded from the dialogue files.
+#[derive(Resource, Debug, Default)]
+pub struct DialogueLibrary {
+    pub barks: Vec<Bark>,
+    pub nodes: HashMap<String, DialogueNode>,
+    pub errors: Vec<DataError>,
+}
+
+impl DialogueLibrary {
+    pub fn add(&mut self, file: DialogueFile) {
+        self.barks.extend(file.barks);
+        self.nodes
+            .extend(file.nodes.into_iter().map(|node| (node.id.clone(), node)));
+    }
+
+    pub fn barks(
+        &self,
+        speaker: BarkSpeaker,
+        trigger: BarkTrigger,
+    ) -> impl Iterator<Item = &Bark> + '_ {
+        self.barks
+            .iter()
+            .filter(move |bark| bark.speaker == speaker && bark.trigger == trigger)
+    }
+}
+
+/// A line of speech for the HUD. Voiced lines are also played through the
+/// dialogue channel, which ducks the music.
+#[derive(Message, Debug, Clone)]
+pub struct Subtitle {
+    pub speaker: String,
+    pub text: String,
+    pub seconds: f32,
+    pub voice: Option<String>,
+    /// Where the voice plays from.
+    pub emitter: Option<Entity>,
+}
+
+/// Asks an NPC to bark. Dropped if nothing fits or it barked recently.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct BarkRequest {
+    pub speaker: Entity,
+    pub trigger: BarkTrigger,
+    pub rival: Option<FactionId>,
+}
+
+/// When this NPC may bark again, in elapsed seconds.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct BarkCooldown(pub f32);
+
+/// Someone the player can talk to. `dialogue` is the first node.
+#[derive(Component, Debug, Clone)]
+pub struct MissionGiver {
+    pub name: String,
+    pub dialogue: String,
+}
+
+#[derive(Message, Debug, Clone, Copy)]
+pub struct StartConversation {
+    pub giver: Entity,
+}
+
+/// Picks one of [`Conversation::choices`].
+#[derive(Message, Debug, Clone, Copy)]
+pub struct ChooseResponse(pub usize);
+
+/// The conversation the player is in, if any.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct Conversation {
+    pub giver: Option<Entity>,
+    pub node: Option<String>,
+    /// The current node's replies whose conditions hold: text and target.
+    pub choices: Vec<(String, Option<String>)>,
+}
+
+impl Conversation {
+    pub fn is_active(&self) -> bool {
+        self.giver.is_some()
+    }
+}
+
+pub struct DialoguePlugin;
+
+impl Plugin for DialoguePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DialogueConfig>()
+            .init_resource::<DialogueLibrary>()
+            .init_resource::<Conversation>()
+            .register_prefab(giver_prefab())
+            .add_message::<Subtitle>()
+            .add_message::<BarkRequest>()
+            .add_message::<StartConversation>()
+            .add_message::<ChooseResponse>()
+            .add_systems(OnEnter(InSession), load_dialogue)
+            .add_systems(OnExit(InSession), end_conversation)
+            .add_systems(
+                Update,
+                (
+                    (
+                        bark_when_shot_at,
+                        bark_when_spotting,
+                        taunt_rivals,
+                        play_barks,
+                    )
+                        .chain(),
+                    (
+                        talk_to_givers,
+                        choose_with_number_keys.run_if(keyboard_free),
+                        run_conversations,
+                        walk_away,
+                    )
+                        .chain(),
+                )
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+fn giver_prefab() -> Prefab {
+    let body = PrefabShape::Capsule {
+        radius: 0.3,
+        length: 1.0,
+    };
+    Prefab::new("mission_giver")
+        .body(RigidBody::Dynamic, body, GameLayer::Npc)
+        .visual(PrefabVisual::new(body, Color::srgb(0.85, 0.65, 0.15)))
+        .with(|giver| {
+            giver.insert(LockedAxes::ROTATION_LOCKED);
+        })
+}
+
+fn load_dialogue(
+    config: Res<DialogueConfig>,
+    mut library: ResMut<DialogueLibrary>,
+    mut toasts: MessageWriter<Toast>,
+) {
+    *library = DialogueLibrary::default();
+    for path in data_paths(&config.directory, "dialogue") {
+        match read_data_file(&path).and_then(|text| parse_dialogue(&path, &text)) {
+            Ok(file) => library.add(file),
+            Err(error) => {
+                error!("{error}");
+                toasts.write(Toast::new(format!(
+                    "{}: dialogue error, see log",
+                    path.display()
+                )));
+                library.errors.push(error);
+            }
+        }
+    }
+}
+
+fn bark_when_shot_at(
+    config: Res<DialogueConfig>,
+    mut damage: MessageReader<DamageEvent>,
+    transforms: Query<&Transform>,
+    npcs: Query<(Entity, &Transform), With<AiRole>>,
+    mut barks: MessageWriter<BarkRequest>,
+) {
+    for event in damage.read() {
+        let Ok(hit) = transforms.get(event.entity) else {
+            continue;
+        };
+        for (npc, transform) in &npcs {
+            let close = transform.translation.distance(hit.translation) <= config.hearing_radius;
+            if (npc == event.entity || close) && Some(npc) != event.source {
+                barks.write(BarkRequest {
+                    speaker: npc,
+                    trigger: BarkTrigger::ShotAt,
+                    rival: None,
+                });
+            }
+        }
+    }
+}
+
+fn bark_when_spotting(
+    mut spotted: MessageReader<PlayerSpotted>,
+    mut barks: MessageWriter<BarkRequest>,
+) {
+    for event in spotted.read() {
+        barks.write(BarkRequest {
+            speaker: event.by,
+            trigger: BarkTrigger::Spotted,
+            rival: None,
+        });
+    }
+}
+
+fn taunt_rivals(
+    config: Res<DialogueConfig>,
+    player: Query<&Transform, With<Player>>,
+    gangs: Query<(Entity, &AiRole, &FactionId, &Transform)>,
+    everyone: Query<(&AiRole, &FactionId, &Transform)>,
+    mut barks: MessageWriter<BarkRequest>,
+) {
+    let Ok(player) = player.single() else {
+        return;
+    };
+    for (entity, role, faction, transform) in &gangs {
+        if BarkSpeaker::of(role) != BarkSpeaker::Gang
+            || transform.translation.distance(player.translation) > config.hearing_radius
+        {
+            continue;
+        }
+        let rival = everyone.iter().find(|(other_role, other_faction, other)| {
+            BarkSpeaker::of(other_role) == BarkSpeaker::Gang
+                && *other_faction != faction
+                && other.translation.distance(transform.translation) <= config.rival_range
+        });
+        if let Some((_, rival, _)) = rival {
+            barks.write(BarkRequest {
+                speaker: entity,
+                trigger: BarkTrigger::Rival,
+                rival: Some(*rival),
+            });
+        }
+    }
+}
+
+/// Read-only game state for conditions.
+#[derive(SystemParam)]
+struct Context<'w> {
+    respect: Res<'w, FactionRespect>,
+    missions: Res<'w, MissionLog>,
+    wanted: Res<'w, WantedLevel>,
+}
+
+impl Context<'_> {
+    fn get(&self) -> DialogueContext<'_> {
+        DialogueContext {
+            respect: &self.respect,
+            missions: &self.missions,
+            wanted: &self.wanted,
+        }
+    }
+}
+
+/// The loaded lines and where spoken ones go.
+#[derive(SystemParam)]
+struct Script<'w> {
+    config: Res<'w, DialogueConfig>,
+    library: Res<'w, DialogueLibrary>,
+    subtitles: MessageWriter<'w, Subtitle>,
+}
+
+impl Script<'_> {
+    fn say(&mut self, speaker: String, text: String, voice: Option<String>, emitter: Entity) {
+        self.subtitles.write(Subtitle {
+            speaker,
+            seconds: self.config.subtitle_secs(&text),
+            text,
+            voice,
+            emitter: Some(emitter),
+        });
+    }
+}
+
+fn play_barks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut script: Script,
+    context: Context,
+    mut requests: MessageReader<BarkRequest>,
+    speakers: Query<(&AiRole, Option<&FactionId>, Option<&BarkCooldown>)>,
+    mut state: Local<(f32, usize)>,
+) {
+    let (next_bark, said) = &mut *state;
+    let now = time.elapsed_secs();
+    let context = context.get();
+    for request in requests.read() {
+        if now < *next_bark {
+            continue;
+        }
+        let Ok((role, faction, cooldown)) = speakers.get(request.speaker) else {
+            continue;
+        };
+        if cooldown.is_some_and(|cooldown| now < cooldown.0) {
+            continue;
+        }
+        let speaker = BarkSpeaker::of(role);
+        let options: Vec<&Bark> = script
+            .library
+            .barks(speaker, request.trigger)
+            .filter(|bark| allowed(&bark.when, &context))
+            .collect();
+        if options.is_empty() {
+            continue;
+        }
+        // Cycle through everything that fits rather than repeating a line.
+        let bark = options[*said % options.len()];
+        let line = &bark.lines[(*said / options.len()) % bark.lines.len()];
+        *said += 1;
+
+        let text = line
+            .replace("{faction}", faction.map_or("", |faction| faction.name()))
+            .replace("{rival}", request.rival.map_or("", |rival| rival.name()));
+        let name = match (speaker, faction) {
+            (BarkSpeaker::Gang, Some(faction)) => faction.name(),
+            (BarkSpeaker::Gang, None) => "Gangster",
+            (BarkSpeaker::Cop, _) => "Cop",
+            (BarkSpeaker::Pedestrian, _) => "Pedestrian",
+        };
+        let voice = bark.voice.clone();
+        let (bark_cooldown, global_cooldown) = (
+            script.config.bark_cooldown,
+            script.config.global_bark_cooldown,
+        );
+        script.say(name.to_string(), text, voice, request.speaker);
+        commands
+            .entity(request.speaker)
+            .insert(BarkCooldown(now + bark_cooldown));
+        *next_bark = now + global_cooldown;
+    }
+}
+
+fn talk_to_givers(
+    input: Res<PlayerInput>,
+    config: Res<DialogueConfig>,
+    conversation: Res<Conversation>,
+    player: Query<&Transform, With<Player>>,
+    givers: Query<(Entity, &Transform), With<MissionGiver>>,
+    mut start: MessageWriter<StartConversation>,
+) {
+    if !input.interact || conversation.is_active() {
+        return;
+    }
+    let Ok(player) = player.single() else {
+        return;
+    };
+    let nearest = givers
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation.distance(player.translation)))
+        .filter(|(_, distance)| *distance <= config.talk_radius)
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+    if let Some((giver, _)) = nearest {
+        start.write(StartConversation { giver });
+    }
+}
+
+fn choose_with_number_keys(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    conversation: Res<Conversation>,
+    mut choose: MessageWriter<ChooseResponse>,
+) {
+    if !conversation.is_active() {
+        return;
+    }
+    if let Some(index) = NUMBER_KEYS
+        .iter()
+        .position(|key| keyboard.just_pressed(*key))
+    {
+        choose.write(ChooseResponse(index));
+    }
+}
+
+/// Game state dialogue actions can change.
+#[derive(SystemParam)]
+struct Outcomes<'w> {
+    respect: ResMut<'w, FactionRespect>,
+    missions: ResMut<'w, MissionLog>,
+    catalog: Res<'w, MissionCatalog>,
+    wanted: Res<'w, WantedLevel>,
+    complete: MessageWriter<'w, CompleteMission>,
+}
+
+impl Outcomes<'_> {
+    fn context(&self) -> DialogueContext<'_> {
+        DialogueContext {
+            respect: &self.respect,
+            missions: &self.missions,
+            wanted: &self.wanted,
+        }
+    }
+
+    fn apply(&mut self, action: &DialogueAction) {
+        match action {
+            DialogueAction::StartMission(id) => match self.catalog.get(id) {
+                Some(mission) => self.missions.active = Some(mission.clone()),
+                None => error!("dialogue starts unknown mission `{id}`"),
+            },
+            DialogueAction::CompleteMission => {
+                self.complete.write(CompleteMission);
+            }
+            DialogueAction::Respect(faction, amount) => {
+                self.respect.respect[faction.index()] += amount;
+            }
+        }
+    }
+}
+
+fn run_conversations(
+    mut script: Script,
+    mut conversation: ResMut<Conversation>,
+    mut outcomes: Outcomes,
+    mut starts: MessageReader<StartConversation>,
+    mut choices: MessageReader<ChooseResponse>,
+    givers: Query<&MissionGiver>,
+) {
+    let mut next = None;
+    for start in starts.read() {
+        if let Ok(giver) = givers.get(start.giver) {
+            conversation.giver = Some(start.giver);
+            next = Some(Some(giver.dialogue.clone()));
+        }
+    }
+    for choice in choices.read() {
+        if let Some((_, target)) = conversation.choices.get(choice.0) {
+            next = Some(target.clone());
+        }
+    }
+    let (Some(next), Some(giver)) = (next, conversation.giver) else {
+        return;
+    };
+
+    let mut id = next;
+    // Redirects can chain; the limit only guards against loops in the data.
+    for _ in 0..8 {
+        let redirect = id
+            .as_ref()
+            .and_then(|id| script.library.nodes.get(id))
+            .and_then(|node| {
+                let context = outcomes.context();
+                node.redirects
+                    .iter()
+                    .find(|(_, when)| allowed(when, &context))
+                    .map(|(target, _)| target.clone())
+            });
+        match redirect {
+            Some(target) => id = target,
+            None => break,
+        }
+    }
+    let Some(node) = id
+        .as_ref()
+        .and_then(|id| script.library.nodes.get(id))
+        .cloned()
+    else {
+        if let Some(id) = id {
+            error!("unknown dialogue node `{id}`");
+        }
+        *conversation = Conversation::default();
+        return;
+    };
+
+    for action in &node.actions {
+        outcomes.apply(action);
+    }
+    let speaker = node
+        .speaker
+        .clone()
+        .or_else(|| givers.get(giver).ok().map(|giver| giver.name.clone()));
+    script.say(
+        speaker.unwrap_or_default(),
+        node.text.clone(),
+        node.voice.clone(),
+        giver,
+    );
+
+    let context = outcomes.context();
+    let choices: Vec<(String, Option<String>)> = node
+        .choices
+        .iter()
+        .filter(|choice| allowed(&choice.when, &context))
+        .map(|choice| (choice.text.clone(), choice.target.clone()))
+        .collect();
+    *conversation = if choices.is_empty() {
+        Conversation::default()
+    } else {
+        Conversation {
+            giver: Some(giver),
+            node: Some(node.id.clone()),
+            choices,
+        }
+    };
+}
+
+fn walk_away(
+    config: Res<DialogueConfig>,
+    mut conversation: ResMut<Conversation>,
+    player: Query<&Transform, With<Player>>,
+    givers: Query<&Transform, With<MissionGiver>>,
+) {
+    let Some(giver) = conversation.giver else {
+        return;
+    };
+    let near = player
+        .single()
+        .ok()
+        .zip(givers.get(giver).ok())
+        .is_some_and(|(player, giver)| {
+            player.translation.distance(giver.translation) <= config.talk_radius * 2.0
+        });
+    if !near {
+        *conversation = Conversation::default();
+    }
+}
+
+fn end_conversation(mut conversation: ResMut<Conversation>) {
+    *conversation = Conversation::default();
+}
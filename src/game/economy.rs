@@ -0,0 +1,272 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use bevy::ecs::system::{RunSystemOnce, SystemParam};
+use bevy::prelude::*;
+
+use crate::game::console::{ConsoleAppExt, ConsoleCommand, arg};
+use crate::game::faction::{FactionId, FactionRespect};
+use crate::game::progression::Progression;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransactionKind {
+    Mission,
+    Robbery,
+    Pickup,
+    Purchase,
+    Service,
+    Adjustment,
+}
+
+impl TransactionKind {
+    pub const ALL: [TransactionKind; 6] = [
+        TransactionKind::Mission,
+        TransactionKind::Robbery,
+        TransactionKind::Pickup,
+        TransactionKind::Purchase,
+        TransactionKind::Service,
+        TransactionKind::Adjustment,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TransactionKind::Mission => "mission",
+            TransactionKind::Robbery => "robbery",
+            TransactionKind::Pickup => "pickup",
+            TransactionKind::Purchase => "purchase",
+            TransactionKind::Service => "service",
+            TransactionKind::Adjustment => "adjustment",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.label() == label)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transaction {
+    pub kind: TransactionKind,
+    /// Signed change in cash; negative for spending.
+    pub amount: i64,
+    /// Balance right after this transaction.
+    pub balance: u32,
+    pub memo: String,
+}
+
+/// A debit larger than the current balance. Nothing is deducted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overdraft {
+    pub balance: u32,
+    pub requested: u32,
+}
+
+impl fmt::Display for Overdraft {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cannot spend ${} with a balance of ${}",
+            self.requested, self.balance
+        )
+    }
+}
+
+impl std::error::Error for Overdraft {}
+
+/// Recent transactions, oldest first. Trimmed to `capacity` entries.
+#[derive(Resource, Debug, Clone)]
+pub struct Ledger {
+    pub entries: VecDeque<Transaction>,
+    pub capacity: usize,
+}
+
+impl Default for Ledger {
+    fn default() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity: 200,
+        }
+    }
+}
+
+impl Ledger {
+    pub fn credit(
+        &mut self,
+        cash: &mut u32,
+        kind: TransactionKind,
+        amount: u32,
+        memo: impl Into<String>,
+    ) -> Transaction {
+        let previous = *cash;
+        *cash = cash.saturating_add(amount);
+        self.record(kind, *cash as i64 - previous as i64, *cash, memo.into())
+    }
+
+    pub fn debit(
+        &mut self,
+        cash: &mut u32,
+        kind: TransactionKind,
+        amount: u32,
+        memo: impl Into<String>,
+    ) -> Result<Transaction, Overdraft> {
+        let Some(balance) = cash.checked_sub(amount) else {
+            return Err(Overdraft {
+                balance: *cash,
+                requested: amount,
+            });
+        };
+        *cash = balance;
+        Ok(self.record(kind, -(amount as i64), balance, memo.into()))
+    }
+
+    fn record(
+        &mut self,
+        kind: TransactionKind,
+        amount: i64,
+        balance: u32,
+        memo: String,
+    ) -> Transaction {
+        let transaction = Transaction {
+            kind,
+            amount,
+            balance,
+            memo,
+        };
+        debug!(
+            "ledger: {} {:+} -> ${} ({})",
+            kind.label(),
+            amount,
+            balance,
+            transaction.memo
+        );
+        self.entries.push_back(transaction.clone());
+        while self.entries.len() > self.capacity {
+            self.entries.pop_front();
+        }
+        transaction
+    }
+}
+
+/// Sent after every successful transaction.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct CashChanged {
+    pub previous: u32,
+    pub current: u32,
+    pub kind: TransactionKind,
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct EconomyConfig {
+    /// Reward bonus per point of respect with the paying faction.
+    pub respect_bonus_per_point: f32,
+    /// Cap on the bonus, and on the penalty for negative respect.
+    pub max_respect_bonus: f32,
+}
+
+impl Default for EconomyConfig {
+    fn default() -> Self {
+        Self {
+            respect_bonus_per_point: 0.005,
+            max_respect_bonus: 0.5,
+        }
+    }
+}
+
+impl EconomyConfig {
+    pub fn reward_multiplier(&self, respect: i32) -> f32 {
+        1.0 + (respect as f32 * self.respect_bonus_per_point)
+            .clamp(-self.max_respect_bonus, self.max_respect_bonus)
+    }
+}
+
+/// The only way gameplay code should touch the player's cash.
+#[derive(SystemParam)]
+pub struct Wallet<'w> {
+    progression: ResMut<'w, Progression>,
+    ledger: ResMut<'w, Ledger>,
+    config: Res<'w, EconomyConfig>,
+    respect: Res<'w, FactionRespect>,
+    changes: MessageWriter<'w, CashChanged>,
+}
+
+impl Wallet<'_> {
+    pub fn balance(&self) -> u32 {
+        self.progression.cash
+    }
+
+    pub fn can_afford(&self, amount: u32) -> bool {
+        self.progression.cash >= amount
+    }
+
+    /// Pays out `base`, scaled by respect with `faction` if given. Returns the
+    /// amount actually granted.
+    pub fn reward(
+        &mut self,
+        kind: TransactionKind,
+        base: u32,
+        faction: Option<FactionId>,
+        memo: impl Into<String>,
+    ) -> u32 {
+        let multiplier = faction.map_or(1.0, |faction| {
+            self.config.reward_multiplier(self.respect.get(faction))
+        });
+        let amount = (base as f32 * multiplier).round() as u32;
+        let previous = self.progression.cash;
+        self.ledger
+            .credit(&mut self.progression.cash, kind, amount, memo);
+        self.notify(previous, kind);
+        amount
+    }
+
+    /// Deducts `amount`, or leaves the balance untouched if it can't cover it.
+    pub fn spend(
+        &mut self,
+        kind: TransactionKind,
+        amount: u32,
+        memo: impl Into<String>,
+    ) -> Result<u32, Overdraft> {
+        let previous = self.progression.cash;
+        let transaction = self
+            .ledger
+            .debit(&mut self.progression.cash, kind, amount, memo)?;
+        self.notify(previous, kind);
+        Ok(transaction.balance)
+    }
+
+    fn notify(&mut self, previous: u32, kind: TransactionKind) {
+        self.changes.write(CashChanged {
+            previous,
+            current: self.progression.cash,
+            kind,
+        });
+    }
+}
+
+pub struct EconomyPlugin;
+
+impl Plugin for EconomyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Ledger>()
+            .init_resource::<EconomyConfig>()
+            .add_message::<CashChanged>()
+            .add_console_command(ConsoleCommand::new(
+                "give cash",
+                "<amount>",
+                "Credit cash as an adjustment.",
+                give_cash,
+            ));
+    }
+}
+
+fn give_cash(world: &mut World, args: &[&str]) -> anyhow::Result<String> {
+    let amount: u32 = arg(args, 0, "amount")?;
+    let balance = world
+        .run_system_once_with(credit_adjustment, amount)
+        .map_err(|err| anyhow::anyhow!("{err}"))?;
+    Ok(format!("balance ${balance}"))
+}
+
+fn credit_adjustment(In(amount): In<u32>, mut wallet: Wallet) -> u32 {
+    wallet.reward(TransactionKind::Adjustment, amount, None, "console");
+    wallet.balance()
+}
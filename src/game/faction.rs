@@ -1,6 +1,7 @@
 use bevy::prelude::*;
+use serde::Deserialize;
 
-#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
 pub enum FactionId {
     RustbornChoir,
     VelvetAlgorithm,
@@ -9,11 +10,45 @@ pub enum FactionId {
     TideSyndicate,
 }
 
+impl FactionId {
+    pub fn name(self) -> &'static str {
+        match self {
+            FactionId::RustbornChoir => "Rustborn Choir",
+            FactionId::VelvetAlgorithm => "Velvet Algorithm",
+            FactionId::StreetOracles => "Street Oracles",
+            FactionId::NeonJackals => "Neon Jackals",
+            FactionId::TideSyndicate => "Tide Syndicate",
+        }
+    }
+
+    /// Colours worn by the faction's members.
+    pub fn color(self) -> Color {
+        match self {
+            FactionId::RustbornChoir => Color::srgb(0.55, 0.3, 0.15),
+            FactionId::VelvetAlgorithm => Color::srgb(0.45, 0.15, 0.5),
+            FactionId::StreetOracles => Color::srgb(0.85, 0.65, 0.15),
+            FactionId::NeonJackals => Color::srgb(0.2, 0.85, 0.4),
+            FactionId::TideSyndicate => Color::srgb(0.1, 0.45, 0.6),
+        }
+    }
+
+    /// Slot in [`FactionRespect::respect`].
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
 #[derive(Resource, Default)]
 pub struct FactionRespect {
     pub respect: [i32; 5],
 }
 
+impl FactionRespect {
+    pub fn get(&self, faction: FactionId) -> i32 {
+        self.respect[faction.index()]
+    }
+}
+
 pub struct FactionPlugin;
 
 impl Plugin for FactionPlugin {
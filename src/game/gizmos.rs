@@ -1,14 +1,24 @@
-use avian3d::prelude::LinearVelocity;
+use avian3d::prelude::{ColliderAabb, LinearVelocity};
 use bevy::prelude::*;
 
+use crate::game::debug::DebugOverlays;
 use crate::game::player::{Player, PlayerController, PlayerFacing};
+use crate::game::trigger::TriggerVolume;
 
 pub struct GizmoHelpersPlugin;
 
 impl Plugin for GizmoHelpersPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, configure_gizmos)
-            .add_systems(Update, (draw_world_gizmos, draw_player_gizmos));
+        app.init_resource::<DebugOverlays>()
+            .add_systems(Startup, configure_gizmos)
+            .add_systems(
+                Update,
+                (
+                    draw_world_gizmos.run_if(|overlays: Res<DebugOverlays>| overlays.world_grid),
+                    draw_player_gizmos.run_if(|overlays: Res<DebugOverlays>| overlays.player),
+                    draw_trigger_gizmos.run_if(|overlays: Res<DebugOverlays>| overlays.triggers),
+                ),
+            );
     }
 }
 
@@ -139,3 +149,21 @@ fn draw_player_gizmos(
         Color::srgb(0.3, 0.8, 0.7).with_alpha(0.4),
     );
 }
+
+fn draw_trigger_gizmos(mut gizmos: Gizmos, triggers: Query<(&ColliderAabb, &TriggerVolume)>) {
+    for (aabb, volume) in &triggers {
+        let color = if volume.is_occupied() {
+            Color::srgb(0.2, 1.0, 0.4)
+        } else if volume.fired {
+            Color::srgb(0.45, 0.45, 0.45)
+        } else {
+            Color::srgb(1.0, 0.85, 0.1)
+        };
+        let center = (aabb.min + aabb.max) * 0.5;
+        let size = aabb.max - aabb.min;
+        gizmos.cuboid(
+            Transform::from_translation(center).with_scale(size),
+            color.with_alpha(0.6),
+        );
+    }
+}
@@ -0,0 +1,30 @@
+use bevy::asset::AssetPlugin;
+use bevy::input::InputPlugin as BevyInputPlugin;
+use bevy::mesh::MeshPlugin;
+use bevy::pbr::StandardMaterial;
+use bevy::prelude::*;
+use bevy::scene::ScenePlugin;
+use bevy::state::app::StatesPlugin;
+use bevy::transform::TransformPlugin;
+
+use crate::game::SimulationPlugin;
+
+/// Runs the simulation without a window or GPU. Add after `MinimalPlugins`;
+/// this supplies the pieces of `DefaultPlugins` gameplay still relies on.
+/// Meshes and materials are still created as assets, they're just never drawn.
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            TransformPlugin,
+            StatesPlugin,
+            AssetPlugin::default(),
+            MeshPlugin,
+            ScenePlugin,
+            BevyInputPlugin,
+        ))
+        .init_asset::<StandardMaterial>()
+        .add_plugins(SimulationPlugin);
+    }
+}
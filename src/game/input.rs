@@ -10,8 +10,177 @@ pub struct PlayerInput {
     pub fire_secondary: bool,
     pub sprint: bool,
     pub interact: bool,
+    pub enter_vehicle: bool,
     pub jump: bool,
     pub camera_zoom: f32,
+    pub toggle_map: bool,
+    pub radio_next: bool,
+}
+
+/// Picks numbered entries in menus such as shops and dialogue choices.
+pub const NUMBER_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+/// Set while a text field such as the console owns the keyboard. Gameplay
+/// input reads as idle until it's released.
+#[derive(Resource, Default, Debug, Clone, Copy)]
+pub struct KeyboardCaptured(pub bool);
+
+/// Run condition for systems that read raw keys, which must stay out of the
+/// way while something is typing.
+pub fn keyboard_free(captured: Res<KeyboardCaptured>) -> bool {
+    !captured.0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputAction {
+    Forward,
+    Back,
+    SteerLeft,
+    SteerRight,
+    TurnLeft,
+    TurnRight,
+    Sprint,
+    Jump,
+    Interact,
+    EnterVehicle,
+    ZoomIn,
+    ZoomOut,
+    Map,
+    Radio,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 14] = [
+        InputAction::Forward,
+        InputAction::Back,
+        InputAction::SteerLeft,
+        InputAction::SteerRight,
+        InputAction::TurnLeft,
+        InputAction::TurnRight,
+        InputAction::Sprint,
+        InputAction::Jump,
+        InputAction::Interact,
+        InputAction::EnterVehicle,
+        InputAction::ZoomIn,
+        InputAction::ZoomOut,
+        InputAction::Map,
+        InputAction::Radio,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            InputAction::Forward => "Forward",
+            InputAction::Back => "Back",
+            InputAction::SteerLeft => "Steer left",
+            InputAction::SteerRight => "Steer right",
+            InputAction::TurnLeft => "Turn left",
+            InputAction::TurnRight => "Turn right",
+            InputAction::Sprint => "Sprint",
+            InputAction::Jump => "Jump",
+            InputAction::Interact => "Interact",
+            InputAction::EnterVehicle => "Enter/exit vehicle",
+            InputAction::ZoomIn => "Zoom in",
+            InputAction::ZoomOut => "Zoom out",
+            InputAction::Map => "Map",
+            InputAction::Radio => "Radio station",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct KeyBinding {
+    pub action: InputAction,
+    pub primary: KeyCode,
+    pub secondary: Option<KeyCode>,
+}
+
+/// Keyboard layout for gameplay actions. Only `primary` keys are rebindable
+/// from the settings menu.
+#[derive(Resource, Debug, Clone)]
+pub struct KeyBindings {
+    pub bindings: Vec<KeyBinding>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let bind = |action, primary, secondary| KeyBinding {
+            action,
+            primary,
+            secondary,
+        };
+        Self {
+            bindings: vec![
+                bind(InputAction::Forward, KeyCode::KeyW, Some(KeyCode::Space)),
+                bind(InputAction::Back, KeyCode::KeyS, Some(KeyCode::Backspace)),
+                bind(InputAction::SteerLeft, KeyCode::KeyA, None),
+                bind(InputAction::SteerRight, KeyCode::KeyD, None),
+                bind(InputAction::TurnLeft, KeyCode::ArrowLeft, None),
+                bind(InputAction::TurnRight, KeyCode::ArrowRight, None),
+                bind(
+                    InputAction::Sprint,
+                    KeyCode::ShiftLeft,
+                    Some(KeyCode::ShiftRight),
+                ),
+                bind(
+                    InputAction::Jump,
+                    KeyCode::Enter,
+                    Some(KeyCode::NumpadEnter),
+                ),
+                bind(InputAction::Interact, KeyCode::KeyE, None),
+                bind(InputAction::EnterVehicle, KeyCode::KeyF, None),
+                bind(InputAction::ZoomIn, KeyCode::Equal, None),
+                bind(InputAction::ZoomOut, KeyCode::Minus, None),
+                bind(InputAction::Map, KeyCode::KeyM, None),
+                bind(InputAction::Radio, KeyCode::KeyR, None),
+            ],
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn get(&self, action: InputAction) -> Option<&KeyBinding> {
+        self.bindings.iter().find(|b| b.action == action)
+    }
+
+    pub fn rebind(&mut self, action: InputAction, key: KeyCode) {
+        if let Some(binding) = self.bindings.iter_mut().find(|b| b.action == action) {
+            binding.primary = key;
+        }
+    }
+
+    fn keys(&self, action: InputAction) -> impl Iterator<Item = KeyCode> + '_ {
+        self.get(action)
+            .into_iter()
+            .flat_map(|b| std::iter::once(b.primary).chain(b.secondary))
+    }
+
+    pub fn pressed(&self, keyboard: &ButtonInput<KeyCode>, action: InputAction) -> bool {
+        keyboard.any_pressed(self.keys(action))
+    }
+
+    pub fn just_pressed(&self, keyboard: &ButtonInput<KeyCode>, action: InputAction) -> bool {
+        keyboard.any_just_pressed(self.keys(action))
+    }
+
+    fn axis(
+        &self,
+        keyboard: &ButtonInput<KeyCode>,
+        positive: InputAction,
+        negative: InputAction,
+    ) -> f32 {
+        self.pressed(keyboard, positive) as i8 as f32
+            - self.pressed(keyboard, negative) as i8 as f32
+    }
 }
 
 pub struct InputPlugin;
@@ -19,6 +188,8 @@ pub struct InputPlugin;
 impl Plugin for InputPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<PlayerInput>()
+            .init_resource::<KeyBindings>()
+            .init_resource::<KeyboardCaptured>()
             .add_systems(Update, gather_player_input);
     }
 }
@@ -26,25 +197,29 @@ impl Plugin for InputPlugin {
 fn gather_player_input(
     keyboard: Res<ButtonInput<KeyCode>>,
     mouse_buttons: Res<ButtonInput<MouseButton>>,
+    bindings: Res<KeyBindings>,
+    captured: Res<KeyboardCaptured>,
     mut mouse_motion_events: MessageReader<MouseMotion>,
     mut player_input: ResMut<PlayerInput>,
 ) {
-    let forward =
-        keyboard.pressed(KeyCode::KeyW) as i8 as f32 - keyboard.pressed(KeyCode::KeyS) as i8 as f32;
-    let forward_alt = keyboard.pressed(KeyCode::Space) as i8 as f32
-        - keyboard.pressed(KeyCode::Backspace) as i8 as f32;
-    let strafe =
-        keyboard.pressed(KeyCode::KeyD) as i8 as f32 - keyboard.pressed(KeyCode::KeyA) as i8 as f32;
-    let movement = Vec2::new(strafe, forward + forward_alt);
+    if captured.0 {
+        mouse_motion_events.clear();
+        *player_input = PlayerInput::default();
+        return;
+    }
+
+    let forward = bindings.axis(&keyboard, InputAction::Forward, InputAction::Back);
+    let strafe = bindings.axis(&keyboard, InputAction::SteerRight, InputAction::SteerLeft);
+    let movement = Vec2::new(strafe, forward);
 
     let mut look_delta = Vec2::ZERO;
     for motion in mouse_motion_events.read() {
         look_delta += motion.delta;
     }
 
-    let camera_zoom = if keyboard.just_pressed(KeyCode::Equal) {
+    let camera_zoom = if bindings.just_pressed(&keyboard, InputAction::ZoomIn) {
         -1.0
-    } else if keyboard.just_pressed(KeyCode::Minus) {
+    } else if bindings.just_pressed(&keyboard, InputAction::ZoomOut) {
         1.0
     } else {
         0.0
@@ -57,13 +232,15 @@ fn gather_player_input(
             movement
         },
         look_delta,
-        yaw_input: keyboard.pressed(KeyCode::ArrowLeft) as i8 as f32
-            - keyboard.pressed(KeyCode::ArrowRight) as i8 as f32,
+        yaw_input: bindings.axis(&keyboard, InputAction::TurnLeft, InputAction::TurnRight),
         fire_primary: mouse_buttons.pressed(MouseButton::Left),
         fire_secondary: mouse_buttons.pressed(MouseButton::Right),
-        sprint: keyboard.pressed(KeyCode::ShiftLeft) || keyboard.pressed(KeyCode::ShiftRight),
-        interact: keyboard.just_pressed(KeyCode::KeyE),
-        jump: keyboard.just_pressed(KeyCode::Enter) || keyboard.just_pressed(KeyCode::NumpadEnter),
+        sprint: bindings.pressed(&keyboard, InputAction::Sprint),
+        interact: bindings.just_pressed(&keyboard, InputAction::Interact),
+        enter_vehicle: bindings.just_pressed(&keyboard, InputAction::EnterVehicle),
+        jump: bindings.just_pressed(&keyboard, InputAction::Jump),
         camera_zoom,
+        toggle_map: bindings.just_pressed(&keyboard, InputAction::Map),
+        radio_next: bindings.just_pressed(&keyboard, InputAction::Radio),
     };
 }
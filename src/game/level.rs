@@ -0,0 +1,530 @@
+use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use avian3d::prelude::*;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use ron::extensions::Extensions;
+use serde::Deserialize;
+use serde::de::{DeserializeSeed, Deserializer, Error as _, SeqAccess, Visitor};
+
+use crate::game::core::InSession;
+use crate::game::data_file::{DataError, data_paths, read_data_file};
+use crate::game::dialogue::MissionGiver;
+use crate::game::faction::FactionId;
+use crate::game::pickup::{PickupEffect, PickupSpawner};
+use crate::game::prefab::{Overrides, PrefabRegistry, Prefabs};
+use crate::game::shop::{ServiceKind, spawn_service_location};
+use crate::game::trigger::{TriggerFilter, TriggerVolume};
+use crate::game::ui::Toast;
+use crate::game::world::{ChunkProp, PropKind, spawn_chunk_prop};
+
+#[derive(Debug, Clone)]
+pub enum Placement {
+    Prop(PropKind),
+    /// Any other registered prefab, spawned as declared.
+    Prefab(&'static str),
+    Shop {
+        kind: ServiceKind,
+        name: &'static str,
+    },
+    Trigger {
+        volume: TriggerVolume,
+        radius: f32,
+    },
+    Pickup {
+        effect: PickupEffect,
+        respawn_secs: Option<f32>,
+    },
+    Giver {
+        name: &'static str,
+        dialogue: &'static str,
+    },
+    Gang {
+        faction: FactionId,
+        boss: bool,
+    },
+}
+
+/// A validated entity from a level file.
+#[derive(Debug, Clone)]
+pub struct PlacedEntity {
+    /// Position in the file's list, starting at 0.
+    pub index: usize,
+    pub position: Vec3,
+    pub placement: Placement,
+}
+
+/// Parses a level file: a RON list of entities, each named after what it
+/// places. Fields with a default can be left out, and `//` starts a comment:
+///
+/// ```text
+/// [
+///     Crate(position: (-48, 2, 12), loot: Cash(50)),
+///     Trigger(id: "oracle_dropoff", position: (-52, 0, 30), radius: 4),
+///     Prefab(name: "traffic_cone", position: (3, 0, 4)),
+/// ]
+/// ```
+///
+/// `Prefab` places anything in `prefabs` by name. Errors point at the line
+/// and column where reading stopped.
+pub fn parse_level(
+    path: &Path,
+    text: &str,
+    prefabs: &PrefabRegistry,
+) -> Result<Vec<PlacedEntity>, DataError> {
+    ron::Options::default()
+        .with_default_extension(Extensions::IMPLICIT_SOME)
+        .from_str_seed(text, LevelSeed(prefabs))
+        .map_err(|err| DataError::from_ron(path, err))
+}
+
+type Xyz = (f32, f32, f32);
+
+fn vec3((x, y, z): Xyz) -> Vec3 {
+    Vec3::new(x, y, z)
+}
+
+fn default_radius() -> f32 {
+    3.0
+}
+
+/// One entity as written in a level file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+enum EntityDef {
+    Building {
+        position: Xyz,
+        size: Xyz,
+    },
+    StreetLamp {
+        position: Xyz,
+    },
+    Hydrant {
+        position: Xyz,
+    },
+    Fence {
+        position: Xyz,
+    },
+    Crate {
+        position: Xyz,
+        #[serde(default)]
+        loot: Option<Loot>,
+    },
+    PedestrianSpawn {
+        position: Xyz,
+    },
+    Shop {
+        position: Xyz,
+        kind: ServiceKind,
+        name: String,
+    },
+    Trigger {
+        position: Xyz,
+        id: String,
+        #[serde(default = "default_radius")]
+        radius: f32,
+        #[serde(default)]
+        filter: TriggerFilter,
+        #[serde(default)]
+        once: bool,
+    },
+    Pickup {
+        position: Xyz,
+        effect: Loot,
+        /// Seconds before it comes back; never if left out.
+        #[serde(default)]
+        respawn: Option<f32>,
+    },
+    Giver {
+        position: Xyz,
+        name: String,
+        dialogue: String,
+    },
+    Gang {
+        position: Xyz,
+        faction: FactionId,
+        #[serde(default)]
+        boss: bool,
+    },
+    Prefab {
+        position: Xyz,
+        name: String,
+    },
+}
+
+/// The pickup effects a level can hand out.
+#[derive(Deserialize)]
+enum Loot {
+    Health(f32),
+    Armor(f32),
+    Cash(u32),
+    Ammo(u32),
+}
+
+impl From<Loot> for PickupEffect {
+    fn from(loot: Loot) -> Self {
+        match loot {
+            Loot::Health(amount) => PickupEffect::Health(amount),
+            Loot::Armor(amount) => PickupEffect::Armor(amount),
+            Loot::Cash(amount) => PickupEffect::Cash(amount),
+            Loot::Ammo(rounds) => PickupEffect::Ammo(rounds),
+        }
+    }
+}
+
+impl EntityDef {
+    /// Checks what serde can't: prefab names and sizes.
+    fn place(self, index: usize, prefabs: &PrefabRegistry) -> Result<PlacedEntity, String> {
+        let (position, placement) = match self {
+            EntityDef::Building { position, size } => (
+                position,
+                Placement::Prop(PropKind::Building { size: vec3(size) }),
+            ),
+            EntityDef::StreetLamp { position } => (position, Placement::Prop(PropKind::StreetLamp)),
+            EntityDef::Hydrant { position } => (position, Placement::Prop(PropKind::Hydrant)),
+            EntityDef::Fence { position } => (position, Placement::Prop(PropKind::Fence)),
+            EntityDef::Crate { position, loot } => (
+                position,
+                Placement::Prop(PropKind::Crate {
+                    loot: loot.map(PickupEffect::from),
+                }),
+            ),
+            EntityDef::PedestrianSpawn { position } => {
+                (position, Placement::Prop(PropKind::PedestrianSpawn))
+            }
+            EntityDef::Shop {
+                position,
+                kind,
+                name,
+            } => (
+                position,
+                Placement::Shop {
+                    kind,
+                    name: intern(&name),
+                },
+            ),
+            EntityDef::Trigger {
+                position,
+                id,
+                radius,
+                filter,
+                once,
+            } => {
+                if radius <= 0.0 {
+                    return Err(format!("trigger `{id}` needs a radius above zero"));
+                }
+                let volume = TriggerVolume::new(intern(&id)).with_filter(filter);
+                (
+                    position,
+                    Placement::Trigger {
+                        volume: if once { volume.once() } else { volume },
+                        radius,
+                    },
+                )
+            }
+            EntityDef::Pickup {
+                position,
+                effect,
+                respawn,
+            } => (
+                position,
+                Placement::Pickup {
+                    effect: effect.into(),
+                    respawn_secs: respawn,
+                },
+            ),
+            EntityDef::Giver {
+                position,
+                name,
+                dialogue,
+            } => (
+                position,
+                Placement::Giver {
+                    name: intern(&name),
+                    dialogue: intern(&dialogue),
+                },
+            ),
+            EntityDef::Gang {
+                position,
+                faction,
+                boss,
+            } => (position, Placement::Gang { faction, boss }),
+            EntityDef::Prefab { position, name } => {
+                let Some(prefab) = prefabs.get(&name) else {
+                    return Err(format!("unknown prefab `{name}`"));
+                };
+                (position, Placement::Prefab(prefab.name))
+            }
+        };
+        Ok(PlacedEntity {
+            index,
+            position: vec3(position),
+            placement,
+        })
+    }
+}
+
+/// Reads the entity list, validating each entry against the prefab registry
+/// as it goes so problems are reported where they occur.
+#[derive(Clone, Copy)]
+struct LevelSeed<'a>(&'a PrefabRegistry);
+
+impl<'de> DeserializeSeed<'de> for LevelSeed<'_> {
+    type Value = Vec<PlacedEntity>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for LevelSeed<'_> {
+    type Value = Vec<PlacedEntity>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a list of entities")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut placed = Vec::new();
+        while let Some(entity) = seq.next_element::<EntityDef>()? {
+            let entity = entity
+                .place(placed.len(), self.0)
+                .map_err(A::Error::custom)?;
+            placed.push(entity);
+        }
+        Ok(placed)
+    }
+}
+
+/// Names from level files live as long as the built-in ones. Interned so
+/// reloading a file doesn't leak another copy each time.
+fn intern(text: &str) -> &'static str {
+    static NAMES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+    let mut names = NAMES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(name) = names.iter().find(|name| **name == text) {
+        return name;
+    }
+    let name: &'static str = Box::leak(text.to_string().into_boxed_str());
+    names.push(name);
+    name
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct LevelConfig {
+    /// Every `*.level` file here is loaded, in name order.
+    pub directory: PathBuf,
+    /// How often to check the files for edits, in real seconds.
+    pub poll_secs: f32,
+}
+
+impl Default for LevelConfig {
+    fn default() -> Self {
+        Self {
+            directory: PathBuf::from("levels"),
+            poll_secs: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LoadedLevel {
+    pub path: PathBuf,
+    /// Hash of the text last loaded. Compared rather than modification times,
+    /// which are too coarse on some filesystems to catch quick edits.
+    pub content_hash: Option<u64>,
+    pub entities: Vec<Entity>,
+    /// The problem from the last load, if it failed. A broken edit keeps the
+    /// previous entities around.
+    pub error: Option<DataError>,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct Levels {
+    pub files: Vec<LoadedLevel>,
+    since_poll: f32,
+}
+
+/// Loads the level files at the start of a session and hot reloads them as
+/// they change. Added by [`WorldPlugin`](crate::game::world::WorldPlugin).
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelConfig>()
+            .init_resource::<Levels>()
+            .add_systems(OnEnter(InSession), load_levels)
+            .add_systems(OnExit(InSession), forget_levels)
+            .add_systems(Update, reload_levels.run_if(in_state(InSession)));
+    }
+}
+
+#[derive(SystemParam)]
+struct LevelSpawner<'w, 's> {
+    prefabs: Prefabs<'w, 's>,
+    toasts: MessageWriter<'w, Toast>,
+}
+
+impl LevelSpawner<'_, '_> {
+    fn spawn(&mut self, placed: &PlacedEntity) -> anyhow::Result<Entity> {
+        let transform = Transform::from_translation(placed.position);
+        match &placed.placement {
+            Placement::Prop(kind) => spawn_chunk_prop(
+                &mut self.prefabs,
+                &ChunkProp {
+                    id: placed.index as u32,
+                    kind: kind.clone(),
+                    position: placed.position,
+                },
+            ),
+            Placement::Prefab(name) => self
+                .prefabs
+                .spawn(name, Overrides::at(transform))
+                .map(|entity| entity.id()),
+            Placement::Shop { kind, name } => {
+                spawn_service_location(&mut self.prefabs, *kind, name, placed.position)
+            }
+            Placement::Trigger { volume, radius } => Ok(self
+                .prefabs
+                .commands
+                .spawn((
+                    volume.clone(),
+                    Collider::cylinder(*radius, 2.0),
+                    transform,
+                    DespawnOnExit(InSession),
+                    Name::new(volume.id),
+                ))
+                .id()),
+            Placement::Pickup {
+                effect,
+                respawn_secs,
+            } => Ok(self
+                .prefabs
+                .commands
+                .spawn((
+                    PickupSpawner::new(effect.clone(), *respawn_secs),
+                    transform,
+                    DespawnOnExit(InSession),
+                    Name::new("Pickup spawner"),
+                ))
+                .id()),
+            Placement::Giver { name, dialogue } => {
+                let mut giver = self
+                    .prefabs
+                    .spawn("mission_giver", Overrides::at(transform))?;
+                giver.insert((
+                    MissionGiver {
+                        name: name.to_string(),
+                        dialogue: dialogue.to_string(),
+                    },
+                    Name::new(*name),
+                ));
+                Ok(giver.id())
+            }
+            Placement::Gang { faction, boss } => {
+                let prefab = if *boss { "gang_boss" } else { "gang_soldier" };
+                let mut member = self
+                    .prefabs
+                    .spawn(prefab, Overrides::at(transform).color(faction.color()))?;
+                member.insert((*faction, Name::new(faction.name())));
+                Ok(member.id())
+            }
+        }
+    }
+
+    /// Re-reads changed files and respawns their entities. With `force`,
+    /// every file is treated as changed.
+    fn sync(&mut self, config: &LevelConfig, levels: &mut Levels, force: bool) {
+        let paths = data_paths(&config.directory, "level");
+        levels.files.retain(|file| {
+            let keep = paths.contains(&file.path);
+            if !keep {
+                for entity in &file.entities {
+                    self.prefabs.commands.entity(*entity).try_despawn();
+                }
+            }
+            keep
+        });
+
+        for path in paths {
+            let text = read_data_file(&path);
+            let content_hash = text.as_ref().ok().map(|text| {
+                let mut hasher = DefaultHasher::new();
+                text.hash(&mut hasher);
+                hasher.finish()
+            });
+            let index = match levels.files.iter().position(|file| file.path == path) {
+                Some(index) if !force && levels.files[index].content_hash == content_hash => {
+                    continue;
+                }
+                Some(index) => index,
+                None => {
+                    levels.files.push(LoadedLevel {
+                        path: path.clone(),
+                        content_hash: None,
+                        entities: Vec::new(),
+                        error: None,
+                    });
+                    levels.files.len() - 1
+                }
+            };
+            let file = &mut levels.files[index];
+            file.content_hash = content_hash;
+
+            match text.and_then(|text| parse_level(&path, &text, self.prefabs.registry())) {
+                Ok(placed) => {
+                    for entity in file.entities.drain(..) {
+                        self.prefabs.commands.entity(entity).try_despawn();
+                    }
+                    file.entities = placed
+                        .iter()
+                        .filter_map(|entity| {
+                            self.spawn(entity)
+                                .inspect_err(|err| {
+                                    error!("{}: entity {}: {err}", path.display(), entity.index)
+                                })
+                                .ok()
+                        })
+                        .collect();
+                    file.error = None;
+                }
+                Err(error) => {
+                    error!("{error}");
+                    self.toasts.write(Toast::new(format!(
+                        "{}: level error, see log",
+                        path.display()
+                    )));
+                    file.error = Some(error);
+                }
+            }
+        }
+    }
+}
+
+fn load_levels(config: Res<LevelConfig>, mut levels: ResMut<Levels>, mut spawner: LevelSpawner) {
+    levels.since_poll = 0.0;
+    spawner.sync(&config, &mut levels, true);
+}
+
+fn reload_levels(
+    time: Res<Time<Real>>,
+    config: Res<LevelConfig>,
+    mut levels: ResMut<Levels>,
+    mut spawner: LevelSpawner,
+) {
+    levels.since_poll += time.delta_secs();
+    if levels.since_poll < config.poll_secs {
+        return;
+    }
+    levels.since_poll = 0.0;
+    spawner.sync(&config, &mut levels, false);
+}
+
+/// The session's entities are gone; the next session loads from scratch.
+fn forget_levels(mut levels: ResMut<Levels>) {
+    levels.files.clear();
+}
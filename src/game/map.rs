@@ -0,0 +1,466 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy::ui::{Display, Overflow, PositionType, RelativeCursorPosition, UiTransform, Val};
+
+use crate::game::ai::AiRole;
+use crate::game::core::{GameState, InSession};
+use crate::game::input::PlayerInput;
+use crate::game::player::{Player, PlayerFacing};
+use crate::game::vehicle::Vehicle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlipKind {
+    Player,
+    Objective,
+    Shop,
+    Cop,
+    Vehicle,
+    Waypoint,
+}
+
+impl BlipKind {
+    pub fn color(self) -> Color {
+        match self {
+            BlipKind::Player => Color::WHITE,
+            BlipKind::Objective => Color::srgb(1.0, 0.85, 0.1),
+            BlipKind::Shop => Color::srgb(0.2, 0.9, 0.35),
+            BlipKind::Cop => Color::srgb(0.25, 0.45, 1.0),
+            BlipKind::Vehicle => Color::srgb(0.7, 0.7, 0.75),
+            BlipKind::Waypoint => Color::srgb(0.9, 0.2, 0.8),
+        }
+    }
+
+    fn size_px(self) -> f32 {
+        match self {
+            BlipKind::Player | BlipKind::Objective | BlipKind::Waypoint => 10.0,
+            _ => 7.0,
+        }
+    }
+}
+
+/// Shows an entity on the minimap and full-screen map. Any module can insert this.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Blip {
+    pub kind: BlipKind,
+    /// Keep the blip pinned to the minimap edge when it's out of range.
+    pub clamp_to_edge: bool,
+}
+
+impl Blip {
+    pub fn new(kind: BlipKind) -> Self {
+        Self {
+            kind,
+            clamp_to_edge: matches!(
+                kind,
+                BlipKind::Objective | BlipKind::Waypoint | BlipKind::Player
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapFeatureKind {
+    District,
+    Road,
+    Building,
+}
+
+impl MapFeatureKind {
+    fn color(self) -> Color {
+        match self {
+            MapFeatureKind::District => Color::srgba(0.12, 0.14, 0.18, 0.9),
+            MapFeatureKind::Road => Color::srgb(0.32, 0.32, 0.36),
+            MapFeatureKind::Building => Color::srgb(0.2, 0.22, 0.3),
+        }
+    }
+
+    fn z_index(self) -> i32 {
+        match self {
+            MapFeatureKind::District => 0,
+            MapFeatureKind::Road => 1,
+            MapFeatureKind::Building => 2,
+        }
+    }
+}
+
+/// Static world geometry drawn on the map as an axis-aligned rectangle
+/// centred on the entity.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct MapFeature {
+    pub kind: MapFeatureKind,
+    pub size: Vec2,
+}
+
+/// The player-placed navigation target.
+#[derive(Component, Debug)]
+pub struct Waypoint;
+
+#[derive(Resource, Debug, Clone)]
+pub struct MapConfig {
+    pub minimap_size_px: f32,
+    /// World metres shown across the minimap.
+    pub minimap_span: f32,
+    /// World metres shown across the full-screen map, centred on the origin.
+    pub full_map_span: f32,
+    pub waypoint_clear_radius: f32,
+}
+
+impl Default for MapConfig {
+    fn default() -> Self {
+        Self {
+            minimap_size_px: 200.0,
+            minimap_span: 120.0,
+            full_map_span: 512.0,
+            waypoint_clear_radius: 4.0,
+        }
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct MapView {
+    pub fullscreen: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MapLayerKind {
+    Mini,
+    Full,
+}
+
+#[derive(Component)]
+struct MapLayer(MapLayerKind);
+
+#[derive(Component)]
+struct FullMapRoot;
+
+#[derive(Component)]
+struct MapMarker {
+    target: Entity,
+}
+
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MapConfig>()
+            .init_resource::<MapView>()
+            .add_systems(OnEnter(InSession), spawn_map_ui)
+            .add_systems(OnExit(GameState::InGame), close_full_map)
+            .add_systems(
+                Update,
+                (
+                    register_default_blips,
+                    toggle_full_map,
+                    place_waypoint,
+                    clear_reached_waypoint,
+                    spawn_map_markers,
+                    update_map_markers,
+                    rotate_heading_markers,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+fn spawn_map_ui(mut commands: Commands, config: Res<MapConfig>) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(16.0),
+            bottom: Val::Px(16.0),
+            width: Val::Px(config.minimap_size_px),
+            height: Val::Px(config.minimap_size_px),
+            overflow: Overflow::clip(),
+            border: UiRect::all(Val::Px(2.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.02, 0.02, 0.03, 0.85)),
+        BorderColor::all(Color::srgb(0.3, 0.3, 0.35)),
+        MapLayer(MapLayerKind::Mini),
+        DespawnOnExit(InSession),
+        Name::new("Minimap"),
+    ));
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                display: Display::None,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+            FullMapRoot,
+            DespawnOnExit(InSession),
+            Name::new("FullMap"),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Node {
+                    width: Val::VMin(85.0),
+                    height: Val::VMin(85.0),
+                    overflow: Overflow::clip(),
+                    ..default()
+                },
+                BackgroundColor(Color::srgb(0.02, 0.02, 0.03)),
+                RelativeCursorPosition::default(),
+                MapLayer(MapLayerKind::Full),
+            ));
+        });
+}
+
+fn register_default_blips(
+    mut commands: Commands,
+    players: Query<Entity, (With<Player>, Without<Blip>)>,
+    vehicles: Query<Entity, (With<Vehicle>, Without<Blip>)>,
+    npcs: Query<(Entity, &AiRole), Without<Blip>>,
+) {
+    for entity in &players {
+        commands.entity(entity).insert(Blip::new(BlipKind::Player));
+    }
+    for entity in &vehicles {
+        commands.entity(entity).insert(Blip::new(BlipKind::Vehicle));
+    }
+    for (entity, role) in &npcs {
+        if matches!(role, AiRole::Cop) {
+            commands.entity(entity).insert(Blip::new(BlipKind::Cop));
+        }
+    }
+}
+
+fn toggle_full_map(
+    input: Res<PlayerInput>,
+    mut view: ResMut<MapView>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut physics_time: ResMut<Time<Physics>>,
+    mut root: Query<&mut Node, With<FullMapRoot>>,
+) {
+    if !input.toggle_map {
+        return;
+    }
+
+    view.fullscreen = !view.fullscreen;
+    if view.fullscreen {
+        virtual_time.pause();
+        physics_time.pause();
+    } else {
+        virtual_time.unpause();
+        physics_time.unpause();
+    }
+    for mut node in &mut root {
+        node.display = if view.fullscreen {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+}
+
+/// The full map stops both clocks behind the state machine's back, so hand
+/// them back before pausing or leaving the session. Otherwise the pause menu
+/// would resume into a frozen world, or the next session would start frozen.
+fn close_full_map(
+    mut view: ResMut<MapView>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut physics_time: ResMut<Time<Physics>>,
+    mut root: Query<&mut Node, With<FullMapRoot>>,
+) {
+    if !view.fullscreen {
+        return;
+    }
+    view.fullscreen = false;
+    virtual_time.unpause();
+    physics_time.unpause();
+    for mut node in &mut root {
+        node.display = Display::None;
+    }
+}
+
+fn place_waypoint(
+    mut commands: Commands,
+    view: Res<MapView>,
+    config: Res<MapConfig>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    layers: Query<(&MapLayer, &RelativeCursorPosition)>,
+    waypoints: Query<Entity, With<Waypoint>>,
+) {
+    if !view.fullscreen {
+        return;
+    }
+    let place = mouse_buttons.just_pressed(MouseButton::Left);
+    if !place && !mouse_buttons.just_pressed(MouseButton::Right) {
+        return;
+    }
+    let Some(cursor) = layers
+        .iter()
+        .find(|(layer, _)| layer.0 == MapLayerKind::Full)
+        .and_then(|(_, cursor)| cursor.cursor_over().then_some(cursor.normalized?))
+    else {
+        return;
+    };
+
+    for entity in &waypoints {
+        commands.entity(entity).despawn();
+    }
+    if place {
+        // Normalized cursor runs -0.5..0.5 with +y down, which maps onto +z.
+        let world = cursor * config.full_map_span;
+        commands.spawn((
+            Waypoint,
+            Blip::new(BlipKind::Waypoint),
+            Transform::from_xyz(world.x, 0.0, world.y),
+            DespawnOnExit(InSession),
+            Name::new("Waypoint"),
+        ));
+    }
+}
+
+fn clear_reached_waypoint(
+    mut commands: Commands,
+    config: Res<MapConfig>,
+    player: Query<&GlobalTransform, With<Player>>,
+    waypoints: Query<(Entity, &Transform), With<Waypoint>>,
+) {
+    let Ok(player) = player.single() else {
+        return;
+    };
+    for (entity, transform) in &waypoints {
+        let offset = (transform.translation - player.translation()).xz();
+        if offset.length() <= config.waypoint_clear_radius {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn spawn_map_markers(
+    mut commands: Commands,
+    layers: Query<(Entity, &MapLayer, Option<&Children>)>,
+    markers: Query<&MapMarker>,
+    blips: Query<(Entity, &Blip)>,
+    features: Query<(Entity, &MapFeature)>,
+) {
+    for (layer_entity, _, children) in &layers {
+        let existing: Vec<Entity> = children
+            .into_iter()
+            .flatten()
+            .filter_map(|child| markers.get(*child).ok().map(|m| m.target))
+            .collect();
+
+        for (target, feature) in &features {
+            if existing.contains(&target) {
+                continue;
+            }
+            commands.entity(layer_entity).with_child((
+                Node {
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                BackgroundColor(feature.kind.color()),
+                ZIndex(feature.kind.z_index()),
+                MapMarker { target },
+            ));
+        }
+
+        for (target, blip) in &blips {
+            if existing.contains(&target) {
+                continue;
+            }
+            let size = Val::Px(blip.kind.size_px());
+            let mut marker = commands.spawn((
+                Node {
+                    position_type: PositionType::Absolute,
+                    width: size,
+                    height: size,
+                    margin: UiRect::all(Val::Px(-blip.kind.size_px() * 0.5)),
+                    ..default()
+                },
+                BackgroundColor(blip.kind.color()),
+                ZIndex(10),
+                UiTransform::default(),
+                MapMarker { target },
+            ));
+            if blip.kind == BlipKind::Player {
+                // Nose so the heading reads at a glance.
+                marker.with_child((
+                    Node {
+                        position_type: PositionType::Absolute,
+                        width: Val::Px(4.0),
+                        height: Val::Px(6.0),
+                        left: Val::Px(3.0),
+                        top: Val::Px(-6.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(1.0, 0.3, 0.3)),
+                ));
+            }
+            let marker = marker.id();
+            commands.entity(layer_entity).add_child(marker);
+        }
+    }
+}
+
+fn update_map_markers(
+    mut commands: Commands,
+    config: Res<MapConfig>,
+    player: Query<&GlobalTransform, With<Player>>,
+    layers: Query<&MapLayer>,
+    targets: Query<(&GlobalTransform, Option<&Blip>, Option<&MapFeature>)>,
+    mut markers: Query<(Entity, &MapMarker, &ChildOf, &mut Node)>,
+) {
+    let player_pos = player
+        .single()
+        .map_or(Vec2::ZERO, |tx| tx.translation().xz());
+
+    for (marker_entity, marker, child_of, mut node) in &mut markers {
+        // Gone, or no longer on the map.
+        let Ok((target_tx, blip, feature)) = targets.get(marker.target) else {
+            commands.entity(marker_entity).despawn();
+            continue;
+        };
+        if blip.is_none() && feature.is_none() {
+            commands.entity(marker_entity).despawn();
+            continue;
+        }
+        let Ok(layer) = layers.get(child_of.parent()) else {
+            continue;
+        };
+        let (center, span) = match layer.0 {
+            MapLayerKind::Mini => (player_pos, config.minimap_span),
+            MapLayerKind::Full => (Vec2::ZERO, config.full_map_span),
+        };
+
+        // Map space: 0..1 across the layer, north (-z) up.
+        let mut uv = (target_tx.translation().xz() - center) / span + Vec2::splat(0.5);
+
+        if let Some(feature) = feature {
+            let size = feature.size / span;
+            node.left = Val::Percent((uv.x - size.x * 0.5) * 100.0);
+            node.top = Val::Percent((uv.y - size.y * 0.5) * 100.0);
+            node.width = Val::Percent(size.x * 100.0);
+            node.height = Val::Percent(size.y * 100.0);
+            continue;
+        }
+
+        if blip.is_some_and(|b| b.clamp_to_edge) && layer.0 == MapLayerKind::Mini {
+            uv = uv.clamp(Vec2::splat(0.03), Vec2::splat(0.97));
+        }
+        node.left = Val::Percent(uv.x * 100.0);
+        node.top = Val::Percent(uv.y * 100.0);
+    }
+}
+
+fn rotate_heading_markers(
+    facings: Query<&PlayerFacing>,
+    mut markers: Query<(&MapMarker, &mut UiTransform)>,
+) {
+    for (marker, mut ui_transform) in &mut markers {
+        if let Ok(facing) = facings.get(marker.target) {
+            // Yaw turns counter-clockwise seen from above; UI rotation is clockwise.
+            ui_transform.rotation = Rot2::radians(-facing.yaw);
+        }
+    }
+}
@@ -0,0 +1,656 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::ui::{FlexDirection, PositionType, UiRect, Val};
+use bevy::window::{PresentMode, PrimaryWindow};
+
+use crate::game::audio::AudioSettings;
+use crate::game::camera::TopDownCameraConfig;
+use crate::game::camera_effects::CameraShake;
+use crate::game::core::GameState;
+use crate::game::input::{InputAction, KeyBindings, keyboard_free};
+use crate::game::progression::{Progression, SkillTree};
+use crate::game::save::{LoadGame, SaveConfig, SaveGame};
+
+#[derive(Resource, Debug, Clone)]
+pub struct GraphicsSettings {
+    pub vsync: bool,
+    pub shadows: bool,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self {
+            vsync: true,
+            shadows: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuScreen {
+    Main,
+    Pause,
+    Load,
+    Save,
+    Settings,
+    Audio,
+    Controls,
+    Camera,
+    Graphics,
+    Skills,
+}
+
+impl MenuScreen {
+    fn title(self) -> &'static str {
+        match self {
+            MenuScreen::Main => "ASPHALT SAINTS",
+            MenuScreen::Pause => "PAUSED",
+            MenuScreen::Load => "LOAD GAME",
+            MenuScreen::Save => "SAVE GAME",
+            MenuScreen::Settings => "SETTINGS",
+            MenuScreen::Audio => "AUDIO",
+            MenuScreen::Controls => "CONTROLS",
+            MenuScreen::Camera => "CAMERA",
+            MenuScreen::Graphics => "GRAPHICS",
+            MenuScreen::Skills => "SKILLS",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Setting {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    CameraHeight,
+    CameraPitch,
+    ShakeIntensity,
+    VSync,
+    Shadows,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MenuAction {
+    NewGame,
+    Continue,
+    Resume,
+    Open(MenuScreen),
+    LoadSlot(usize),
+    SaveSlot(usize),
+    Adjust(Setting),
+    Rebind(InputAction),
+    UnlockSkill(&'static str),
+    QuitToMenu,
+    Quit,
+    Back,
+}
+
+struct MenuItem {
+    label: String,
+    action: MenuAction,
+    enabled: bool,
+}
+
+impl MenuItem {
+    fn new(label: impl Into<String>, action: MenuAction) -> Self {
+        Self {
+            label: label.into(),
+            action,
+            enabled: true,
+        }
+    }
+
+    fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+}
+
+/// Navigation state for whichever menu is open. Screens form a stack so Back
+/// returns to the previous one.
+#[derive(Resource, Debug, Default)]
+pub struct MenuState {
+    stack: Vec<MenuScreen>,
+    focus: usize,
+    awaiting_rebind: Option<InputAction>,
+    /// Which save slots hold a save, read when a screen opens rather than
+    /// every frame.
+    used_slots: Vec<bool>,
+    latest_slot: Option<usize>,
+}
+
+impl MenuState {
+    pub fn current(&self) -> Option<MenuScreen> {
+        self.stack.last().copied()
+    }
+
+    fn open(&mut self, screen: MenuScreen, saves: &SaveConfig) {
+        self.stack.push(screen);
+        self.focus = 0;
+        self.scan_slots(saves);
+    }
+
+    fn reset(&mut self, screen: MenuScreen, saves: &SaveConfig) {
+        self.stack = vec![screen];
+        self.focus = 0;
+        self.awaiting_rebind = None;
+        self.scan_slots(saves);
+    }
+
+    fn scan_slots(&mut self, saves: &SaveConfig) {
+        self.used_slots = (0..saves.slots)
+            .map(|slot| saves.slot_path(slot).exists())
+            .collect();
+        self.latest_slot = saves.latest_slot();
+    }
+
+    fn slot_used(&self, slot: usize) -> bool {
+        self.used_slots.get(slot).copied().unwrap_or(false)
+    }
+}
+
+#[derive(SystemParam)]
+struct MenuSettings<'w> {
+    audio: ResMut<'w, AudioSettings>,
+    bindings: ResMut<'w, KeyBindings>,
+    camera: ResMut<'w, TopDownCameraConfig>,
+    shake: ResMut<'w, CameraShake>,
+    graphics: ResMut<'w, GraphicsSettings>,
+    saves: Res<'w, SaveConfig>,
+    progression: ResMut<'w, Progression>,
+    skills: Res<'w, SkillTree>,
+}
+
+#[derive(Component)]
+struct MenuRoot;
+
+#[derive(Component)]
+struct MenuButton(usize);
+
+/// Keyboard, gamepads and mouse clicks on menu buttons.
+#[derive(SystemParam)]
+struct MenuInput<'w, 's> {
+    keyboard: Res<'w, ButtonInput<KeyCode>>,
+    gamepads: Query<'w, 's, &'static Gamepad>,
+    buttons: Query<'w, 's, (&'static Interaction, &'static MenuButton), Changed<Interaction>>,
+}
+
+/// Buttons pressed this frame, merged from keyboard and any gamepad.
+#[derive(Default)]
+struct MenuNav {
+    up: bool,
+    down: bool,
+    left: bool,
+    right: bool,
+    accept: bool,
+    back: bool,
+}
+
+const FOCUSED: Color = Color::srgb(1.0, 0.82, 0.2);
+const UNFOCUSED: Color = Color::srgb(0.85, 0.85, 0.9);
+const DISABLED: Color = Color::srgb(0.35, 0.35, 0.4);
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        let menu_open = in_state(GameState::MainMenu).or(in_state(GameState::Paused));
+        app.init_resource::<MenuState>()
+            .init_resource::<GraphicsSettings>()
+            .add_systems(OnEnter(GameState::MainMenu), open_main_menu)
+            .add_systems(OnEnter(GameState::Paused), open_pause_menu)
+            .add_systems(OnExit(GameState::MainMenu), close_menu)
+            .add_systems(OnExit(GameState::Paused), close_menu)
+            .add_systems(
+                Update,
+                (
+                    pause_game.run_if(in_state(GameState::InGame).and(keyboard_free)),
+                    (navigate_menu.run_if(keyboard_free), render_menu)
+                        .chain()
+                        .run_if(menu_open),
+                    apply_graphics_settings,
+                ),
+            );
+    }
+}
+
+fn open_main_menu(mut commands: Commands, saves: Res<SaveConfig>, mut menu: ResMut<MenuState>) {
+    // Nothing else renders before a session starts, so the menu brings its own camera.
+    commands.spawn((Camera2d, DespawnOnExit(GameState::MainMenu)));
+    menu.reset(MenuScreen::Main, &saves);
+}
+
+fn open_pause_menu(saves: Res<SaveConfig>, mut menu: ResMut<MenuState>) {
+    menu.reset(MenuScreen::Pause, &saves);
+}
+
+fn close_menu(
+    mut commands: Commands,
+    mut menu: ResMut<MenuState>,
+    roots: Query<Entity, With<MenuRoot>>,
+) {
+    menu.stack.clear();
+    for root in &roots {
+        commands.entity(root).despawn();
+    }
+}
+
+fn pause_game(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let pressed = keyboard.just_pressed(KeyCode::Escape)
+        || gamepads
+            .iter()
+            .any(|pad| pad.just_pressed(GamepadButton::Start));
+    if pressed {
+        next_state.set(GameState::Paused);
+    }
+}
+
+fn menu_items(screen: MenuScreen, menu: &MenuState, settings: &MenuSettings) -> Vec<MenuItem> {
+    let percent = |value: f32| format!("{:.0}%", value * 100.0);
+    let on_off = |value: bool| if value { "On" } else { "Off" };
+    let slot_label = |slot: usize| {
+        let used = menu.slot_used(slot);
+        let state = if used { "" } else { " (empty)" };
+        (format!("Slot {}{state}", slot + 1), used)
+    };
+
+    let mut items = match screen {
+        MenuScreen::Main => vec![
+            MenuItem::new("New Game", MenuAction::NewGame),
+            MenuItem::new("Continue", MenuAction::Continue).enabled(menu.latest_slot.is_some()),
+            MenuItem::new("Load", MenuAction::Open(MenuScreen::Load)),
+            MenuItem::new("Settings", MenuAction::Open(MenuScreen::Settings)),
+            MenuItem::new("Quit", MenuAction::Quit),
+        ],
+        MenuScreen::Pause => vec![
+            MenuItem::new("Resume", MenuAction::Resume),
+            MenuItem::new("Skills", MenuAction::Open(MenuScreen::Skills)),
+            MenuItem::new("Save", MenuAction::Open(MenuScreen::Save)),
+            MenuItem::new("Load", MenuAction::Open(MenuScreen::Load)),
+            MenuItem::new("Settings", MenuAction::Open(MenuScreen::Settings)),
+            MenuItem::new("Quit to Menu", MenuAction::QuitToMenu),
+        ],
+        MenuScreen::Load => (0..settings.saves.slots)
+            .map(|slot| {
+                let (label, used) = slot_label(slot);
+                MenuItem::new(label, MenuAction::LoadSlot(slot)).enabled(used)
+            })
+            .collect(),
+        MenuScreen::Save => (0..settings.saves.slots)
+            .map(|slot| MenuItem::new(slot_label(slot).0, MenuAction::SaveSlot(slot)))
+            .collect(),
+        MenuScreen::Settings => vec![
+            MenuItem::new("Audio", MenuAction::Open(MenuScreen::Audio)),
+            MenuItem::new("Controls", MenuAction::Open(MenuScreen::Controls)),
+            MenuItem::new("Camera", MenuAction::Open(MenuScreen::Camera)),
+            MenuItem::new("Graphics", MenuAction::Open(MenuScreen::Graphics)),
+        ],
+        MenuScreen::Audio => vec![
+            MenuItem::new(
+                format!("Master  < {} >", percent(settings.audio.master_volume)),
+                MenuAction::Adjust(Setting::MasterVolume),
+            ),
+            MenuItem::new(
+                format!("Music  < {} >", percent(settings.audio.music_volume)),
+                MenuAction::Adjust(Setting::MusicVolume),
+            ),
+            MenuItem::new(
+                format!("Effects  < {} >", percent(settings.audio.sfx_volume)),
+                MenuAction::Adjust(Setting::SfxVolume),
+            ),
+        ],
+        MenuScreen::Controls => InputAction::ALL
+            .iter()
+            .map(|&action| {
+                let key = settings
+                    .bindings
+                    .get(action)
+                    .map_or("--".to_string(), |b| format!("{:?}", b.primary));
+                MenuItem::new(
+                    format!("{}: {key}", action.label()),
+                    MenuAction::Rebind(action),
+                )
+            })
+            .collect(),
+        MenuScreen::Camera => vec![
+            MenuItem::new(
+                format!("Height  < {:.0} >", settings.camera.height),
+                MenuAction::Adjust(Setting::CameraHeight),
+            ),
+            MenuItem::new(
+                format!(
+                    "Pitch  < {:.0} deg >",
+                    settings.camera.pitch_radians.to_degrees()
+                ),
+                MenuAction::Adjust(Setting::CameraPitch),
+            ),
+            MenuItem::new(
+                format!(
+                    "Screen shake  < {} >",
+                    percent(settings.shake.profile.intensity)
+                ),
+                MenuAction::Adjust(Setting::ShakeIntensity),
+            ),
+        ],
+        MenuScreen::Graphics => vec![
+            MenuItem::new(
+                format!("VSync  < {} >", on_off(settings.graphics.vsync)),
+                MenuAction::Adjust(Setting::VSync),
+            ),
+            MenuItem::new(
+                format!("Shadows  < {} >", on_off(settings.graphics.shadows)),
+                MenuAction::Adjust(Setting::Shadows),
+            ),
+        ],
+        MenuScreen::Skills => settings
+            .skills
+            .skills
+            .iter()
+            .map(|skill| {
+                let status = if settings.progression.has_skill(skill.id) {
+                    "owned".to_string()
+                } else {
+                    format!("{} pt", skill.cost)
+                };
+                let unlockable = settings
+                    .skills
+                    .check_unlock(&settings.progression, skill.id)
+                    .is_ok();
+                MenuItem::new(
+                    format!("{} [{status}]  {}", skill.name, skill.description),
+                    MenuAction::UnlockSkill(skill.id),
+                )
+                .enabled(unlockable)
+            })
+            .collect(),
+    };
+
+    if !matches!(screen, MenuScreen::Main | MenuScreen::Pause) {
+        items.push(MenuItem::new("Back", MenuAction::Back));
+    }
+    items
+}
+
+fn adjust(setting: Setting, direction: f32, settings: &mut MenuSettings) {
+    let step = |value: &mut f32, delta: f32, min: f32, max: f32| {
+        *value = (*value + delta * direction).clamp(min, max);
+    };
+    match setting {
+        Setting::MasterVolume => step(&mut settings.audio.master_volume, 0.05, 0.0, 1.0),
+        Setting::MusicVolume => step(&mut settings.audio.music_volume, 0.05, 0.0, 1.0),
+        Setting::SfxVolume => step(&mut settings.audio.sfx_volume, 0.05, 0.0, 1.0),
+        Setting::CameraHeight => {
+            let camera = &mut *settings.camera;
+            let (min, max) = (camera.min_height, camera.max_height);
+            step(&mut camera.height, camera.zoom_step, min, max);
+        }
+        Setting::CameraPitch => step(
+            &mut settings.camera.pitch_radians,
+            5.0_f32.to_radians(),
+            30.0_f32.to_radians(),
+            90.0_f32.to_radians(),
+        ),
+        Setting::ShakeIntensity => step(&mut settings.shake.profile.intensity, 0.25, 0.0, 1.0),
+        Setting::VSync => settings.graphics.vsync = !settings.graphics.vsync,
+        Setting::Shadows => settings.graphics.shadows = !settings.graphics.shadows,
+    }
+}
+
+impl MenuInput<'_, '_> {
+    /// Reads this frame's navigation. Hovering or clicking a button moves
+    /// the focus to it.
+    fn nav(&self, menu: &mut MenuState) -> MenuNav {
+        let key = |codes: &[KeyCode]| self.keyboard.any_just_pressed(codes.iter().copied());
+        let pad = |button: GamepadButton| self.gamepads.iter().any(|p| p.just_pressed(button));
+        let mut nav = MenuNav {
+            up: key(&[KeyCode::ArrowUp, KeyCode::KeyW]) || pad(GamepadButton::DPadUp),
+            down: key(&[KeyCode::ArrowDown, KeyCode::KeyS]) || pad(GamepadButton::DPadDown),
+            left: key(&[KeyCode::ArrowLeft, KeyCode::KeyA]) || pad(GamepadButton::DPadLeft),
+            right: key(&[KeyCode::ArrowRight, KeyCode::KeyD]) || pad(GamepadButton::DPadRight),
+            accept: key(&[KeyCode::Enter, KeyCode::Space]) || pad(GamepadButton::South),
+            back: key(&[KeyCode::Escape]) || pad(GamepadButton::East) || pad(GamepadButton::Start),
+        };
+
+        for (interaction, button) in &self.buttons {
+            match interaction {
+                Interaction::Hovered => menu.focus = button.0,
+                Interaction::Pressed => {
+                    menu.focus = button.0;
+                    nav.accept = true;
+                }
+                Interaction::None => {}
+            }
+        }
+        nav
+    }
+}
+
+fn navigate_menu(
+    input: MenuInput,
+    mut menu: ResMut<MenuState>,
+    mut settings: MenuSettings,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut save_requests: MessageWriter<SaveGame>,
+    mut load_requests: MessageWriter<LoadGame>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    // While waiting for a rebind, the next key press is the new binding.
+    if let Some(action) = menu.awaiting_rebind {
+        if let Some(&key) = input.keyboard.get_just_pressed().next() {
+            if key != KeyCode::Escape {
+                settings.bindings.rebind(action, key);
+            }
+            menu.awaiting_rebind = None;
+        }
+        return;
+    }
+    let Some(screen) = menu.current() else {
+        return;
+    };
+    // Focus bookkeeping runs every frame; only a real move should redraw.
+    let focused = menu.focus;
+    let state = menu.bypass_change_detection();
+    let nav = input.nav(state);
+    let items = menu_items(screen, state, &settings);
+    if items.is_empty() {
+        return;
+    }
+    if nav.up {
+        state.focus = (state.focus + items.len() - 1) % items.len();
+    }
+    if nav.down {
+        state.focus = (state.focus + 1) % items.len();
+    }
+    state.focus = state.focus.min(items.len() - 1);
+    if state.focus != focused {
+        menu.set_changed();
+    }
+
+    if nav.back {
+        if menu.stack.len() > 1 {
+            menu.stack.pop();
+            menu.focus = 0;
+        } else if screen == MenuScreen::Pause {
+            next_state.set(GameState::InGame);
+        }
+        return;
+    }
+
+    let item = &items[menu.focus];
+    if !item.enabled {
+        return;
+    }
+    if let MenuAction::Adjust(setting) = item.action {
+        if nav.left || nav.right || nav.accept {
+            adjust(setting, if nav.left { -1.0 } else { 1.0 }, &mut settings);
+            menu.set_changed();
+        }
+        return;
+    }
+    if !nav.accept {
+        return;
+    }
+
+    match item.action {
+        MenuAction::NewGame => {
+            load_requests.write(LoadGame { slot: None });
+        }
+        MenuAction::Continue => {
+            load_requests.write(LoadGame {
+                slot: menu.latest_slot,
+            });
+        }
+        MenuAction::LoadSlot(slot) => {
+            load_requests.write(LoadGame { slot: Some(slot) });
+        }
+        MenuAction::SaveSlot(slot) => {
+            save_requests.write(SaveGame { slot });
+            menu.used_slots[slot] = true;
+            menu.latest_slot = Some(slot);
+            menu.stack.pop();
+            menu.focus = 0;
+        }
+        MenuAction::Resume => next_state.set(GameState::InGame),
+        MenuAction::Open(next) => menu.open(next, &settings.saves),
+        MenuAction::Rebind(action) => menu.awaiting_rebind = Some(action),
+        MenuAction::UnlockSkill(id) => {
+            if let Err(err) = settings.skills.unlock(&mut settings.progression, id) {
+                warn!("can't unlock {id}: {err}");
+            }
+            menu.set_changed();
+        }
+        MenuAction::QuitToMenu => next_state.set(GameState::MainMenu),
+        MenuAction::Quit => {
+            exit.write(AppExit::Success);
+        }
+        MenuAction::Back => {
+            menu.stack.pop();
+            menu.focus = 0;
+        }
+        MenuAction::Adjust(_) => {}
+    }
+}
+
+fn render_menu(
+    mut commands: Commands,
+    menu: Res<MenuState>,
+    settings: MenuSettings,
+    roots: Query<Entity, With<MenuRoot>>,
+) {
+    if !menu.is_changed() {
+        return;
+    }
+    for root in &roots {
+        commands.entity(root).despawn();
+    }
+    let Some(screen) = menu.current() else {
+        return;
+    };
+
+    let items = menu_items(screen, &menu, &settings);
+    let hint = match menu.awaiting_rebind {
+        Some(action) => format!("Press a key for {} (Esc to cancel)", action.label()),
+        None if screen == MenuScreen::Skills => format!(
+            "{} skill points available, {} XP",
+            settings.progression.skill_points, settings.progression.xp
+        ),
+        None => "Arrows/D-pad to move, Enter/A to select, Esc/B to go back".to_string(),
+    };
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+            GlobalZIndex(100),
+            MenuRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(screen.title()),
+                TextFont {
+                    font_size: 40.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                Node {
+                    margin: UiRect::bottom(Val::Px(24.0)),
+                    ..default()
+                },
+            ));
+            for (index, item) in items.iter().enumerate() {
+                let color = if !item.enabled {
+                    DISABLED
+                } else if index == menu.focus {
+                    FOCUSED
+                } else {
+                    UNFOCUSED
+                };
+                parent.spawn((
+                    Button,
+                    MenuButton(index),
+                    Node {
+                        padding: UiRect::axes(Val::Px(16.0), Val::Px(6.0)),
+                        ..default()
+                    },
+                    children![(
+                        Text::new(item.label.clone()),
+                        TextFont {
+                            font_size: 22.0,
+                            ..default()
+                        },
+                        TextColor(color),
+                    )],
+                ));
+            }
+            parent.spawn((
+                Text::new(hint),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(UNFOCUSED),
+                Node {
+                    margin: UiRect::top(Val::Px(24.0)),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn apply_graphics_settings(
+    settings: Res<GraphicsSettings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    mut directional: Query<&mut DirectionalLight>,
+    mut point: Query<&mut PointLight>,
+    new_directional: Query<(), Added<DirectionalLight>>,
+    new_point: Query<(), Added<PointLight>>,
+) {
+    if !settings.is_changed() && new_directional.is_empty() && new_point.is_empty() {
+        return;
+    }
+    for mut window in &mut windows {
+        window.present_mode = if settings.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        };
+    }
+    for mut light in &mut directional {
+        light.shadows_enabled = settings.shadows;
+    }
+    for mut light in &mut point {
+        light.shadows_enabled = settings.shadows;
+    }
+}
@@ -1,7 +1,16 @@
 use bevy::prelude::*;
 
-use crate::game::core::GameState;
+use crate::game::console::{ConsoleAppExt, ConsoleCommand};
+use crate::game::core::{GameState, InSession};
+use crate::game::economy::{TransactionKind, Wallet};
 use crate::game::faction::FactionId;
+use crate::game::map::{Blip, BlipKind};
+use crate::game::player::Player;
+use crate::game::progression::GrantXp;
+use crate::game::save::ApplyLoadSet;
+use crate::game::trigger::{TriggerEnter, TriggerVolume};
+use crate::game::ui::Toast;
+use crate::game::weather::WeatherKind;
 
 #[derive(Debug, Clone)]
 pub struct MissionDefinition {
@@ -9,6 +18,14 @@ pub struct MissionDefinition {
     pub faction: FactionId,
     pub title: &'static str,
     pub brief: &'static str,
+    /// Base cash payout, scaled by respect with `faction`.
+    pub reward: u32,
+    pub xp: u32,
+    /// Weather forced while the mission is active.
+    pub weather: Option<WeatherKind>,
+    /// Id of the level trigger that completes the mission when the player
+    /// enters it.
+    pub finish_at: Option<&'static str>,
 }
 
 #[derive(Resource, Default)]
@@ -17,22 +34,176 @@ pub struct MissionLog {
     pub completed: Vec<&'static str>,
 }
 
+/// Every mission that can be offered, by id.
+#[derive(Resource, Debug, Clone)]
+pub struct MissionCatalog {
+    pub missions: Vec<MissionDefinition>,
+}
+
+impl Default for MissionCatalog {
+    fn default() -> Self {
+        Self {
+            missions: vec![
+                MissionDefinition {
+                    id: "street_scramble",
+                    faction: FactionId::RustbornChoir,
+                    title: "Street Scramble",
+                    brief: "Tag the Choir's wall to announce your arrival in Neon Parish.",
+                    reward: 500,
+                    xp: 250,
+                    weather: None,
+                    finish_at: Some("choir_wall"),
+                },
+                MissionDefinition {
+                    id: "oracle_errand",
+                    faction: FactionId::StreetOracles,
+                    title: "Oracle Errand",
+                    brief: "Carry a sealed prophecy across town before it expires.",
+                    reward: 800,
+                    xp: 300,
+                    weather: Some(WeatherKind::Fog),
+                    finish_at: Some("oracle_dropoff"),
+                },
+                MissionDefinition {
+                    id: "jackal_bait",
+                    faction: FactionId::NeonJackals,
+                    title: "Jackal Bait",
+                    brief: "Draw the Jackals out of their den and lose them.",
+                    reward: 1200,
+                    xp: 450,
+                    weather: Some(WeatherKind::Storm),
+                    finish_at: Some("jackal_slip"),
+                },
+            ],
+        }
+    }
+}
+
+impl MissionCatalog {
+    pub fn get(&self, id: &str) -> Option<&MissionDefinition> {
+        self.missions.iter().find(|mission| mission.id == id)
+    }
+}
+
+/// Finishes the active mission and pays out its reward.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct CompleteMission;
+
 pub struct MissionPlugin;
 
 impl Plugin for MissionPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<MissionLog>()
-            .add_systems(OnEnter(GameState::InGame), select_intro_mission);
+            .init_resource::<MissionCatalog>()
+            .add_console_command(
+                ConsoleCommand::new(
+                    "mission start",
+                    "<id>",
+                    "Make a mission the active one.",
+                    start_mission,
+                )
+                .with_completions(|world| {
+                    world
+                        .resource::<MissionCatalog>()
+                        .missions
+                        .iter()
+                        .map(|mission| mission.id.to_string())
+                        .collect()
+                }),
+            )
+            .add_message::<CompleteMission>()
+            .add_systems(OnEnter(InSession), select_intro_mission.after(ApplyLoadSet))
+            .add_systems(OnExit(InSession), reset_mission_log)
+            .add_systems(
+                Update,
+                (finish_at_triggers, complete_missions, mark_objective)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+fn start_mission(world: &mut World, args: &[&str]) -> anyhow::Result<String> {
+    let id = args.first().copied().unwrap_or_default();
+    let Some(mission) = world.resource::<MissionCatalog>().get(id).cloned() else {
+        anyhow::bail!("unknown mission `{id}`");
+    };
+    let title = mission.title;
+    world.resource_mut::<MissionLog>().active = Some(mission);
+    Ok(format!("started {title}"))
+}
+
+/// A fresh game starts on the intro. Loaded games keep whatever they had.
+fn select_intro_mission(catalog: Res<MissionCatalog>, mut mission_log: ResMut<MissionLog>) {
+    let fresh = mission_log.active.is_none() && mission_log.completed.is_empty();
+    if fresh {
+        mission_log.active = catalog.get("street_scramble").cloned();
+    }
+}
+
+/// Missions don't outlive the session that started them.
+fn reset_mission_log(mut mission_log: ResMut<MissionLog>) {
+    *mission_log = MissionLog::default();
+}
+
+fn finish_at_triggers(
+    mission_log: Res<MissionLog>,
+    mut enters: MessageReader<TriggerEnter>,
+    players: Query<(), With<Player>>,
+    mut complete: MessageWriter<CompleteMission>,
+) {
+    let Some(finish_at) = mission_log.active.as_ref().and_then(|m| m.finish_at) else {
+        enters.clear();
+        return;
+    };
+    if enters
+        .read()
+        .any(|enter| enter.id == finish_at && players.contains(enter.entity))
+    {
+        complete.write(CompleteMission);
+    }
+}
+
+/// Puts an objective blip on the active mission's `finish_at` trigger, and
+/// only there.
+fn mark_objective(
+    mut commands: Commands,
+    mission_log: Res<MissionLog>,
+    triggers: Query<(Entity, &TriggerVolume, Option<&Blip>)>,
+) {
+    let finish_at = mission_log.active.as_ref().and_then(|m| m.finish_at);
+    for (entity, trigger, blip) in &triggers {
+        let objective = finish_at == Some(trigger.id);
+        let marked = blip.is_some_and(|blip| blip.kind == BlipKind::Objective);
+        if objective && !marked {
+            commands
+                .entity(entity)
+                .insert(Blip::new(BlipKind::Objective));
+        } else if marked && !objective {
+            commands.entity(entity).remove::<Blip>();
+        }
     }
 }
 
-fn select_intro_mission(mut mission_log: ResMut<MissionLog>) {
-    if mission_log.active.is_none() {
-        mission_log.active = Some(MissionDefinition {
-            id: "street_scramble",
-            faction: FactionId::RustbornChoir,
-            title: "Street Scramble",
-            brief: "Cause havoc to announce your arrival in Neon Parish.",
-        });
+fn complete_missions(
+    mut requests: MessageReader<CompleteMission>,
+    mut mission_log: ResMut<MissionLog>,
+    mut wallet: Wallet,
+    mut xp: MessageWriter<GrantXp>,
+    mut toasts: MessageWriter<Toast>,
+) {
+    for _ in requests.read() {
+        let Some(mission) = mission_log.active.take() else {
+            continue;
+        };
+        let paid = wallet.reward(
+            TransactionKind::Mission,
+            mission.reward,
+            Some(mission.faction),
+            mission.id,
+        );
+        xp.write(GrantXp { amount: mission.xp });
+        mission_log.completed.push(mission.id);
+        toasts.write(Toast::new(format!("{} complete  +${paid}", mission.title)));
     }
 }
@@ -1,50 +1,137 @@
 pub mod ai;
 pub mod audio;
 pub mod camera;
+pub mod camera_effects;
 pub mod combat;
+pub mod console;
 pub mod core;
+pub mod data_file;
+#[cfg(feature = "dev")]
 pub mod debug;
+pub mod destructible;
+pub mod dialogue;
+pub mod economy;
 pub mod faction;
+#[cfg(feature = "dev")]
 pub mod gizmos;
+pub mod headless;
 pub mod input;
+pub mod level;
+pub mod map;
+pub mod menu;
 pub mod mission;
+pub mod music;
 pub mod physics;
+pub mod pickup;
 pub mod player;
+pub mod prefab;
 pub mod progression;
+pub mod save;
+pub mod shop;
+pub mod stats;
+pub mod streaming;
+pub mod time_of_day;
+pub mod trigger;
 pub mod ui;
 pub mod vehicle;
+pub mod weather;
 pub mod world;
 
 use bevy::prelude::*;
+#[cfg(feature = "dev")]
 use bevy_inspector_egui::bevy_egui::EguiPlugin;
 
 use crate::game::{
-    ai::AiPlugin, audio::AudioPlugin, camera::CameraPlugin, combat::CombatPlugin, core::CorePlugin,
-    debug::DebugPlugin, faction::FactionPlugin, gizmos::GizmoHelpersPlugin, input::InputPlugin,
-    mission::MissionPlugin, physics::PhysicsPlugin, player::PlayerPlugin,
-    progression::ProgressionPlugin, ui::UiPlugin, vehicle::VehiclePlugin, world::WorldPlugin,
+    ai::AiPlugin,
+    audio::AudioPlugin,
+    camera::CameraPlugin,
+    camera_effects::CameraEffectsPlugin,
+    combat::CombatPlugin,
+    console::ConsolePlugin,
+    core::CorePlugin,
+    destructible::DestructiblePlugin,
+    dialogue::DialoguePlugin,
+    economy::EconomyPlugin,
+    faction::FactionPlugin,
+    input::InputPlugin,
+    map::MapPlugin,
+    menu::MenuPlugin,
+    mission::MissionPlugin,
+    music::MusicPlugin,
+    physics::PhysicsPlugin,
+    pickup::PickupPlugin,
+    player::PlayerPlugin,
+    prefab::PrefabPlugin,
+    progression::ProgressionPlugin,
+    save::SavePlugin,
+    shop::ShopPlugin,
+    stats::StatsPlugin,
+    streaming::StreamingPlugin,
+    time_of_day::TimeOfDayPlugin,
+    trigger::TriggerPlugin,
+    ui::{Toast, UiPlugin},
+    vehicle::VehiclePlugin,
+    weather::WeatherPlugin,
+    world::WorldPlugin,
 };
+#[cfg(feature = "dev")]
+use crate::game::{console::ConsoleUiPlugin, debug::DebugPlugin, gizmos::GizmoHelpersPlugin};
 
-pub struct GamePlugin;
+/// Gameplay only: state, physics, rules and persistence. Needs no window,
+/// renderer, audio device or egui, so it also runs under `MinimalPlugins`
+/// (see [`headless::HeadlessPlugin`]).
+pub struct SimulationPlugin;
 
-impl Plugin for GamePlugin {
+impl Plugin for SimulationPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(EguiPlugin::default())
-            .add_plugins(CorePlugin)
-            .add_plugins(DebugPlugin)
+        app.add_plugins(CorePlugin)
+            .add_plugins(ConsolePlugin)
             .add_plugins(PhysicsPlugin)
+            .add_plugins(PrefabPlugin)
+            .add_plugins(TriggerPlugin)
             .add_plugins(InputPlugin)
-            .add_plugins(CameraPlugin)
             .add_plugins(WorldPlugin)
+            .add_plugins(StreamingPlugin)
+            .add_plugins(TimeOfDayPlugin)
+            .add_plugins(WeatherPlugin)
             .add_plugins(PlayerPlugin)
             .add_plugins(CombatPlugin)
+            .add_plugins(DestructiblePlugin)
+            .add_plugins(StatsPlugin)
             .add_plugins(VehiclePlugin)
             .add_plugins(AiPlugin)
+            .add_plugins(DialoguePlugin)
             .add_plugins(MissionPlugin)
             .add_plugins(FactionPlugin)
             .add_plugins(ProgressionPlugin)
+            .add_plugins(EconomyPlugin)
+            .add_plugins(PickupPlugin)
+            .add_plugins(ShopPlugin)
+            .add_plugins(SavePlugin)
+            // Gameplay raises toasts even when there's no HUD to show them.
+            .add_message::<Toast>();
+    }
+}
+
+/// The full game: simulation plus camera, audio, HUD and menus. `dev` builds
+/// also get the inspector and debug overlays.
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(SimulationPlugin)
+            .add_plugins(CameraPlugin)
+            .add_plugins(CameraEffectsPlugin)
             .add_plugins(AudioPlugin)
-            .add_plugins(GizmoHelpersPlugin)
-            .add_plugins(UiPlugin);
+            .add_plugins(MusicPlugin)
+            .add_plugins(UiPlugin)
+            .add_plugins(MapPlugin)
+            .add_plugins(MenuPlugin);
+
+        #[cfg(feature = "dev")]
+        app.add_plugins(EguiPlugin::default())
+            .add_plugins(DebugPlugin)
+            .add_plugins(ConsoleUiPlugin)
+            .add_plugins(GizmoHelpersPlugin);
     }
 }
@@ -0,0 +1,387 @@
+use std::time::Duration;
+
+use bevy::audio::{PlaybackMode, Volume};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use crate::game::ai::WantedLevel;
+use crate::game::audio::{AudioMixer, SoundCategory, SoundVoice};
+use crate::game::core::{GameState, InSession};
+use crate::game::faction::FactionId;
+use crate::game::input::PlayerInput;
+use crate::game::player::Player;
+use crate::game::ui::Toast;
+use crate::game::vehicle::{Driving, Vehicle};
+use crate::game::world::District;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RadioTrack {
+    pub path: &'static str,
+    pub seconds: f32,
+}
+
+/// A station's songs with DJ links and ads slotted in between.
+#[derive(Debug, Clone)]
+pub struct RadioStation {
+    pub name: &'static str,
+    pub songs: Vec<RadioTrack>,
+    pub interstitials: Vec<RadioTrack>,
+    pub songs_per_break: usize,
+}
+
+impl RadioStation {
+    /// Broadcast order for one pass through the playlist.
+    pub fn schedule(&self) -> Vec<RadioTrack> {
+        let mut schedule = Vec::new();
+        let mut breaks = self.interstitials.iter().cycle();
+        for (index, song) in self.songs.iter().enumerate() {
+            schedule.push(*song);
+            if self.songs_per_break > 0 && (index + 1) % self.songs_per_break == 0 {
+                schedule.extend(breaks.next().copied());
+            }
+        }
+        schedule
+    }
+
+    /// What's on air after `elapsed` seconds of broadcasting: the schedule
+    /// entry and how far into it we are.
+    pub fn on_air(&self, elapsed: f64) -> Option<(usize, RadioTrack, f32)> {
+        let schedule = self.schedule();
+        let total: f64 = schedule.iter().map(|t| t.seconds.max(0.0) as f64).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut offset = elapsed.rem_euclid(total);
+        let last = schedule.len() - 1;
+        for (index, track) in schedule.into_iter().enumerate() {
+            let length = track.seconds.max(0.0) as f64;
+            if offset < length || index == last {
+                return Some((index, track, offset.min(length) as f32));
+            }
+            offset -= length;
+        }
+        None
+    }
+}
+
+fn track(path: &'static str, seconds: f32) -> RadioTrack {
+    RadioTrack { path, seconds }
+}
+
+/// In-vehicle radio. Every station keeps broadcasting on a shared clock, so
+/// retuning picks up mid-track rather than from the top.
+#[derive(Resource, Debug, Clone)]
+pub struct Radio {
+    pub stations: Vec<RadioStation>,
+    pub tuned: Option<usize>,
+    pub clock: f64,
+}
+
+impl Default for Radio {
+    fn default() -> Self {
+        Self {
+            stations: vec![
+                RadioStation {
+                    name: "Rust FM",
+                    songs: vec![
+                        track("radio/rust_fm/borrowed_time.ogg", 184.0),
+                        track("radio/rust_fm/lifetime_elision.ogg", 201.0),
+                        track("radio/rust_fm/no_unsafe_love.ogg", 176.0),
+                        track("radio/rust_fm/fearless.ogg", 212.0),
+                    ],
+                    interstitials: vec![
+                        track("radio/rust_fm/dj_morning.ogg", 24.0),
+                        track("radio/rust_fm/ad_crates.ogg", 30.0),
+                    ],
+                    songs_per_break: 2,
+                },
+                RadioStation {
+                    name: "Neon 88.1",
+                    songs: vec![
+                        track("radio/neon/night_drive.ogg", 240.0),
+                        track("radio/neon/chrome_rain.ogg", 198.0),
+                        track("radio/neon/jackal_run.ogg", 223.0),
+                    ],
+                    interstitials: vec![
+                        track("radio/neon/dj_midnight.ogg", 18.0),
+                        track("radio/neon/ad_synth_oil.ogg", 28.0),
+                    ],
+                    songs_per_break: 1,
+                },
+            ],
+            tuned: Some(0),
+            clock: 0.0,
+        }
+    }
+}
+
+impl Radio {
+    pub fn station(&self) -> Option<&RadioStation> {
+        self.tuned.and_then(|index| self.stations.get(index))
+    }
+
+    /// Steps to the next station, switching off after the last one.
+    pub fn tune_next(&mut self) {
+        self.tuned = match self.tuned {
+            None if !self.stations.is_empty() => Some(0),
+            Some(index) if index + 1 < self.stations.len() => Some(index + 1),
+            _ => None,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MusicIntensity {
+    Calm,
+    Tense,
+    Chase,
+}
+
+impl MusicIntensity {
+    pub fn from_wanted(stars: u8) -> Self {
+        match stars {
+            0 => MusicIntensity::Calm,
+            1 | 2 => MusicIntensity::Tense,
+            _ => MusicIntensity::Chase,
+        }
+    }
+
+    fn slug(self) -> &'static str {
+        match self {
+            MusicIntensity::Calm => "calm",
+            MusicIntensity::Tense => "tense",
+            MusicIntensity::Chase => "chase",
+        }
+    }
+}
+
+/// On-foot score. Districts owned by a faction play that faction's theme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MusicTheme {
+    City,
+    Faction(FactionId),
+}
+
+impl MusicTheme {
+    fn slug(self) -> &'static str {
+        match self {
+            MusicTheme::City => "city",
+            MusicTheme::Faction(FactionId::RustbornChoir) => "rustborn_choir",
+            MusicTheme::Faction(FactionId::VelvetAlgorithm) => "velvet_algorithm",
+            MusicTheme::Faction(FactionId::StreetOracles) => "street_oracles",
+            MusicTheme::Faction(FactionId::NeonJackals) => "neon_jackals",
+            MusicTheme::Faction(FactionId::TideSyndicate) => "tide_syndicate",
+        }
+    }
+
+    pub fn path(self, intensity: MusicIntensity) -> String {
+        format!("music/{}_{}.ogg", self.slug(), intensity.slug())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MusicCue {
+    Radio { station: usize, entry: usize },
+    Theme(MusicTheme, MusicIntensity),
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct MusicDirector {
+    pub district: Option<&'static str>,
+    pub theme: MusicTheme,
+    pub volume: f32,
+    pub crossfade_secs: f32,
+    cue: Option<MusicCue>,
+}
+
+impl Default for MusicDirector {
+    fn default() -> Self {
+        Self {
+            district: None,
+            theme: MusicTheme::City,
+            volume: 1.0,
+            crossfade_secs: 1.5,
+            cue: None,
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+struct MusicVoice {
+    fading_out: bool,
+}
+
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Radio>()
+            .init_resource::<MusicDirector>()
+            .add_systems(OnEnter(InSession), reset_director)
+            .add_systems(
+                Update,
+                (
+                    tick_radio,
+                    cycle_station,
+                    track_district,
+                    direct_music,
+                    fade_music,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+fn reset_director(mut director: ResMut<MusicDirector>) {
+    *director = MusicDirector {
+        volume: director.volume,
+        crossfade_secs: director.crossfade_secs,
+        ..default()
+    };
+}
+
+fn tick_radio(time: Res<Time>, mut radio: ResMut<Radio>) {
+    radio.clock += time.delta_secs_f64();
+}
+
+fn cycle_station(
+    input: Res<PlayerInput>,
+    player: Query<(), (With<Player>, With<Driving>)>,
+    mut radio: ResMut<Radio>,
+    mut toasts: MessageWriter<Toast>,
+) {
+    if !input.radio_next || player.is_empty() {
+        return;
+    }
+    radio.tune_next();
+    let name = radio.station().map_or("Radio off", |station| station.name);
+    toasts.write(Toast::new(name));
+}
+
+fn track_district(
+    mut director: ResMut<MusicDirector>,
+    mut toasts: MessageWriter<Toast>,
+    player: Query<&Transform, With<Player>>,
+    districts: Query<(&District, &GlobalTransform)>,
+) {
+    let Ok(player) = player.single() else {
+        return;
+    };
+    let district = districts
+        .iter()
+        .filter(|(district, tx)| district.contains(tx.translation(), player.translation))
+        .min_by(|(a, _), (b, _)| (a.size.x * a.size.y).total_cmp(&(b.size.x * b.size.y)))
+        .map(|(district, _)| *district);
+
+    let name = district.map(|d| d.name);
+    if director.district == name {
+        return;
+    }
+    director.district = name;
+    director.theme = district
+        .and_then(|d| d.faction)
+        .map_or(MusicTheme::City, MusicTheme::Faction);
+    if let Some(name) = name {
+        toasts.write(Toast::new(name));
+    }
+}
+
+/// What the player should be hearing: the radio while driving, otherwise the
+/// district theme at the wanted level's intensity.
+#[derive(SystemParam)]
+struct Listener<'w, 's> {
+    radio: Res<'w, Radio>,
+    wanted: Res<'w, WantedLevel>,
+    player: Query<'w, 's, &'static Driving, With<Player>>,
+    vehicles: Query<'w, 's, (), With<Vehicle>>,
+}
+
+impl Listener<'_, '_> {
+    /// The cue, its track and how far into the track to start.
+    fn desired(&self, theme: MusicTheme) -> Option<(MusicCue, String, f32)> {
+        let driving = self
+            .player
+            .single()
+            .is_ok_and(|driving| self.vehicles.contains(driving.0));
+        if !driving {
+            let intensity = MusicIntensity::from_wanted(self.wanted.stars);
+            return Some((
+                MusicCue::Theme(theme, intensity),
+                theme.path(intensity),
+                0.0,
+            ));
+        }
+        // Radio cues carry how far into the track the station already is.
+        let station = self.radio.tuned?;
+        let (entry, track, offset) = self.radio.station()?.on_air(self.radio.clock)?;
+        Some((
+            MusicCue::Radio { station, entry },
+            track.path.to_string(),
+            offset,
+        ))
+    }
+}
+
+fn direct_music(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut director: ResMut<MusicDirector>,
+    mut mixer: ResMut<AudioMixer>,
+    listener: Listener,
+    mut voices: Query<&mut MusicVoice>,
+) {
+    let desired = listener.desired(director.theme);
+    let cue = desired.as_ref().map(|(cue, _, _)| *cue);
+    if cue == director.cue {
+        return;
+    }
+    director.cue = cue;
+    for mut voice in &mut voices {
+        voice.fading_out = true;
+    }
+    let Some((cue, path, start)) = desired else {
+        return;
+    };
+
+    let voice = mixer.voice(SoundCategory::Music, u8::MAX, 0.0);
+    commands.spawn((
+        AudioPlayer::new(asset_server.load(path)),
+        PlaybackSettings {
+            mode: if matches!(cue, MusicCue::Theme(..)) {
+                PlaybackMode::Loop
+            } else {
+                PlaybackMode::Despawn
+            },
+            volume: Volume::Linear(0.0),
+            start_position: Some(Duration::from_secs_f32(start)),
+            ..default()
+        },
+        voice,
+        MusicVoice { fading_out: false },
+        DespawnOnExit(InSession),
+    ));
+}
+
+fn fade_music(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    director: Res<MusicDirector>,
+    mut voices: Query<(Entity, &MusicVoice, &mut SoundVoice)>,
+) {
+    let step = director.volume * time.delta_secs() / director.crossfade_secs.max(0.01);
+    for (entity, music, mut voice) in &mut voices {
+        if music.fading_out {
+            voice.volume -= step;
+            if voice.volume <= 0.0 {
+                commands.entity(entity).despawn();
+            }
+        } else if voice.volume != director.volume {
+            voice.volume = if voice.volume < director.volume {
+                (voice.volume + step).min(director.volume)
+            } else {
+                (voice.volume - step).max(director.volume)
+            };
+        }
+    }
+}
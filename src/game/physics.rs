@@ -1,6 +1,7 @@
 use avian3d::prelude::*;
 use bevy::prelude::*;
 
+use crate::game::console::{ConsoleAppExt, ConsoleCommand, arg};
 use crate::game::core::GameState;
 
 #[derive(Resource, Clone)]
@@ -22,6 +23,85 @@ impl Default for PhysicsConfig {
     }
 }
 
+/// Gameplay collision categories. Every collider spawned by the game should
+/// carry [`GameLayer::layers`] so filtering stays consistent across modules.
+#[derive(PhysicsLayer, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GameLayer {
+    #[default]
+    Default,
+    Player,
+    Npc,
+    Vehicle,
+    Static,
+    Prop,
+    Projectile,
+    Trigger,
+    Pickup,
+    /// Fragments of broken props. Kept off characters so they don't trip on them.
+    Debris,
+}
+
+/// Which layer pairs interact. Pairs are symmetric; anything not listed is ignored.
+const LAYER_INTERACTIONS: &[(GameLayer, GameLayer)] = &[
+    (GameLayer::Default, GameLayer::Default),
+    (GameLayer::Default, GameLayer::Player),
+    (GameLayer::Default, GameLayer::Npc),
+    (GameLayer::Default, GameLayer::Vehicle),
+    (GameLayer::Default, GameLayer::Static),
+    (GameLayer::Default, GameLayer::Prop),
+    (GameLayer::Player, GameLayer::Npc),
+    (GameLayer::Player, GameLayer::Vehicle),
+    (GameLayer::Player, GameLayer::Static),
+    (GameLayer::Player, GameLayer::Prop),
+    (GameLayer::Player, GameLayer::Projectile),
+    (GameLayer::Player, GameLayer::Trigger),
+    (GameLayer::Player, GameLayer::Pickup),
+    (GameLayer::Npc, GameLayer::Npc),
+    (GameLayer::Npc, GameLayer::Vehicle),
+    (GameLayer::Npc, GameLayer::Static),
+    (GameLayer::Npc, GameLayer::Projectile),
+    (GameLayer::Npc, GameLayer::Trigger),
+    (GameLayer::Vehicle, GameLayer::Vehicle),
+    (GameLayer::Vehicle, GameLayer::Static),
+    (GameLayer::Vehicle, GameLayer::Prop),
+    (GameLayer::Vehicle, GameLayer::Projectile),
+    (GameLayer::Vehicle, GameLayer::Trigger),
+    (GameLayer::Static, GameLayer::Prop),
+    (GameLayer::Static, GameLayer::Projectile),
+    (GameLayer::Prop, GameLayer::Prop),
+    (GameLayer::Prop, GameLayer::Projectile),
+    (GameLayer::Debris, GameLayer::Default),
+    (GameLayer::Debris, GameLayer::Vehicle),
+    (GameLayer::Debris, GameLayer::Static),
+    (GameLayer::Debris, GameLayer::Prop),
+    (GameLayer::Debris, GameLayer::Debris),
+];
+
+impl GameLayer {
+    /// Mask of every layer this one interacts with, derived from [`LAYER_INTERACTIONS`].
+    pub fn filters(self) -> LayerMask {
+        let mut mask = LayerMask::NONE;
+        for &(a, b) in LAYER_INTERACTIONS {
+            if a == self {
+                mask.add(b);
+            }
+            if b == self {
+                mask.add(a);
+            }
+        }
+        mask
+    }
+
+    pub fn layers(self) -> CollisionLayers {
+        CollisionLayers::new(self, self.filters())
+    }
+
+    /// Spatial query filter that hits what a collider on this layer would hit.
+    pub fn query_filter(self) -> SpatialQueryFilter {
+        SpatialQueryFilter::from_mask(self.filters())
+    }
+}
+
 pub struct PhysicsPlugin;
 
 impl Plugin for PhysicsPlugin {
@@ -44,11 +124,28 @@ impl Plugin for PhysicsPlugin {
             app.add_plugins(PhysicsDebugPlugin::default());
         }
 
+        app.add_console_command(ConsoleCommand::new(
+            "physics gravity",
+            "[y | x y z]",
+            "Show or set gravity.",
+            gravity_command,
+        ));
         app.add_systems(OnEnter(GameState::Paused), pause_physics);
         app.add_systems(OnExit(GameState::Paused), resume_physics);
     }
 }
 
+fn gravity_command(world: &mut World, args: &[&str]) -> anyhow::Result<String> {
+    let gravity = match args.len() {
+        0 => world.resource::<Gravity>().0,
+        1 => Vec3::new(0.0, arg(args, 0, "y")?, 0.0),
+        3 => Vec3::new(arg(args, 0, "x")?, arg(args, 1, "y")?, arg(args, 2, "z")?),
+        _ => anyhow::bail!("expected one or three values"),
+    };
+    world.resource_mut::<Gravity>().0 = gravity;
+    Ok(format!("gravity {gravity}"))
+}
+
 fn pause_physics(mut time: ResMut<Time<Physics>>) {
     time.pause();
 }
@@ -0,0 +1,513 @@
+use std::collections::HashMap;
+
+use bevy::pbr::{MeshMaterial3d, StandardMaterial};
+use bevy::prelude::*;
+
+use crate::game::combat::{Armor, Health, Weapon, WeaponInventory};
+use crate::game::core::{GameState, InSession};
+use crate::game::economy::{TransactionKind, Wallet};
+use crate::game::player::Player;
+use crate::game::save::ApplyLoadSet;
+use crate::game::stats::{ModifierSource, Stat, StatModifier, Stats};
+use crate::game::ui::Toast;
+
+#[derive(Debug, Clone)]
+pub enum PickupEffect {
+    Health(f32),
+    Armor(f32),
+    Cash(u32),
+    Weapon(Weapon),
+    /// Rounds added to the equipped weapon's reserve.
+    Ammo(u32),
+    Buff(StatModifier),
+}
+
+impl PickupEffect {
+    pub fn label(&self) -> String {
+        match self {
+            PickupEffect::Health(amount) => format!("+{amount:.0} health"),
+            PickupEffect::Armor(amount) => format!("+{amount:.0} armor"),
+            PickupEffect::Cash(amount) => format!("+${amount}"),
+            PickupEffect::Weapon(weapon) => weapon.name.to_string(),
+            PickupEffect::Ammo(rounds) => format!("+{rounds} ammo"),
+            PickupEffect::Buff(_) => "Power-up".to_string(),
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            PickupEffect::Health(_) => Color::srgb(0.9, 0.2, 0.25),
+            PickupEffect::Armor(_) => Color::srgb(0.3, 0.6, 1.0),
+            PickupEffect::Cash(_) => Color::srgb(0.3, 0.9, 0.4),
+            PickupEffect::Weapon(_) | PickupEffect::Ammo(_) => Color::srgb(0.85, 0.75, 0.3),
+            PickupEffect::Buff(_) => Color::srgb(0.8, 0.35, 0.95),
+        }
+    }
+}
+
+/// A spot that keeps a pickup stocked, restocking `respawn_secs` after it's
+/// collected. `None` never restocks.
+#[derive(Component, Debug, Clone)]
+pub struct PickupSpawner {
+    pub effect: PickupEffect,
+    pub respawn_secs: Option<f32>,
+    stocked: Option<Entity>,
+    cooldown: f32,
+}
+
+impl PickupSpawner {
+    pub fn new(effect: PickupEffect, respawn_secs: Option<f32>) -> Self {
+        Self {
+            effect,
+            respawn_secs,
+            stocked: None,
+            cooldown: 0.0,
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct Pickup {
+    pub effect: PickupEffect,
+}
+
+/// One of the hidden tokens tracked by [`Collectibles`].
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Collectible {
+    pub id: &'static str,
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct PickupConfig {
+    /// Pickups inside this radius drift toward the player.
+    pub magnet_radius: f32,
+    pub magnet_speed: f32,
+    /// Pickups inside this radius are collected.
+    pub collect_radius: f32,
+}
+
+impl Default for PickupConfig {
+    fn default() -> Self {
+        Self {
+            magnet_radius: 3.5,
+            magnet_speed: 10.0,
+            collect_radius: 1.0,
+        }
+    }
+}
+
+/// The hidden token set. `found` is persisted in save files.
+#[derive(Resource, Debug, Clone)]
+pub struct Collectibles {
+    pub tokens: Vec<(&'static str, Vec3)>,
+    pub found: Vec<&'static str>,
+    pub reward_each: u32,
+    pub completion_bonus: u32,
+}
+
+impl Default for Collectibles {
+    fn default() -> Self {
+        Self {
+            tokens: vec![
+                ("token_rooftop", Vec3::new(-42.0, 0.8, 61.0)),
+                ("token_alley", Vec3::new(88.0, 0.8, -17.0)),
+                ("token_pier", Vec3::new(-120.0, 0.8, -140.0)),
+                ("token_overpass", Vec3::new(155.0, 0.8, 96.0)),
+                ("token_chapel", Vec3::new(12.0, 0.8, -205.0)),
+            ],
+            found: Vec::new(),
+            reward_each: 100,
+            completion_bonus: 5000,
+        }
+    }
+}
+
+impl Collectibles {
+    pub fn is_complete(&self) -> bool {
+        self.tokens.iter().all(|(id, _)| self.found.contains(id))
+    }
+}
+
+#[derive(Message, Debug, Clone)]
+pub struct PickupCollected {
+    pub effect: PickupEffect,
+}
+
+/// Spawns a loose, one-off pickup, e.g. loot from a broken crate.
+#[derive(Message, Debug, Clone)]
+pub struct DropPickup {
+    pub effect: PickupEffect,
+    pub position: Vec3,
+}
+
+#[derive(Resource)]
+struct PickupAssets {
+    mesh: Handle<Mesh>,
+    /// One per pickup colour, made on first use.
+    materials: HashMap<[u8; 4], Handle<StandardMaterial>>,
+    token_mesh: Handle<Mesh>,
+    token_material: Handle<StandardMaterial>,
+}
+
+impl PickupAssets {
+    fn material(
+        &mut self,
+        materials: &mut Assets<StandardMaterial>,
+        color: Color,
+    ) -> Handle<StandardMaterial> {
+        self.materials
+            .entry(color.to_srgba().to_u8_array())
+            .or_insert_with(|| {
+                materials.add(StandardMaterial {
+                    base_color: color,
+                    emissive: color.to_linear() * 0.5,
+                    ..default()
+                })
+            })
+            .clone()
+    }
+}
+
+impl FromWorld for PickupAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let mesh = meshes.add(Cuboid::new(0.5, 0.5, 0.5));
+        let token_mesh = meshes.add(Torus::new(0.2, 0.35));
+        let token_material =
+            world
+                .resource_mut::<Assets<StandardMaterial>>()
+                .add(StandardMaterial {
+                    base_color: Color::srgb(1.0, 0.8, 0.2),
+                    emissive: LinearRgba::rgb(2.0, 1.4, 0.2),
+                    ..default()
+                });
+        Self {
+            mesh,
+            materials: HashMap::new(),
+            token_mesh,
+            token_material,
+        }
+    }
+}
+
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PickupConfig>()
+            .init_resource::<Collectibles>()
+            .init_resource::<PickupAssets>()
+            .add_message::<PickupCollected>()
+            .add_message::<DropPickup>()
+            .add_systems(
+                OnEnter(InSession),
+                (
+                    spawn_pickup_spawners,
+                    spawn_collectibles.after(ApplyLoadSet),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    restock_spawners,
+                    drop_pickups,
+                    spin_pickups,
+                    attract_pickups,
+                    collect_pickups,
+                    collect_tokens,
+                    announce_pickups,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+fn spawn_pickup_spawners(mut commands: Commands) {
+    let adrenaline =
+        StatModifier::mul(Stat::SprintSpeed, ModifierSource::Pickup("adrenaline"), 1.3)
+            .for_secs(20.0);
+    let spawners = [
+        (
+            Vec3::new(6.0, 0.6, 8.0),
+            PickupEffect::Health(50.0),
+            Some(30.0),
+        ),
+        (
+            Vec3::new(-8.0, 0.6, 10.0),
+            PickupEffect::Armor(50.0),
+            Some(45.0),
+        ),
+        (
+            Vec3::new(14.0, 0.6, -4.0),
+            PickupEffect::Ammo(24),
+            Some(20.0),
+        ),
+        (
+            Vec3::new(-16.0, 0.6, -12.0),
+            PickupEffect::Weapon(Weapon {
+                name: "SMG",
+                clip: 30,
+                clip_size: 30,
+                reserve: 90,
+                spread_degrees: 6.0,
+            }),
+            Some(60.0),
+        ),
+        (
+            Vec3::new(20.0, 0.6, 18.0),
+            PickupEffect::Buff(adrenaline),
+            Some(90.0),
+        ),
+        // A one-off stash; gone for the session once taken.
+        (Vec3::new(-24.0, 0.6, 22.0), PickupEffect::Cash(250), None),
+    ];
+    for (position, effect, respawn) in spawners {
+        commands.spawn((
+            PickupSpawner::new(effect, respawn),
+            Transform::from_translation(position),
+            DespawnOnExit(InSession),
+            Name::new("Pickup spawner"),
+        ));
+    }
+}
+
+fn spawn_collectibles(
+    mut commands: Commands,
+    collectibles: Res<Collectibles>,
+    assets: Res<PickupAssets>,
+) {
+    for &(id, position) in &collectibles.tokens {
+        if collectibles.found.contains(&id) {
+            continue;
+        }
+        commands.spawn((
+            Collectible { id },
+            Mesh3d(assets.token_mesh.clone()),
+            MeshMaterial3d(assets.token_material.clone()),
+            Transform::from_translation(position),
+            DespawnOnExit(InSession),
+            Name::new(id),
+        ));
+    }
+}
+
+fn restock_spawners(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut assets: ResMut<PickupAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut spawners: Query<(Entity, &mut PickupSpawner, &Transform)>,
+    pickups: Query<(), With<Pickup>>,
+) {
+    for (entity, mut spawner, transform) in &mut spawners {
+        if let Some(stocked) = spawner.stocked {
+            if pickups.contains(stocked) {
+                continue;
+            }
+            // Collected: start the restock timer, or retire for good.
+            spawner.stocked = None;
+            match spawner.respawn_secs {
+                Some(seconds) => spawner.cooldown = seconds,
+                None => {
+                    commands.entity(entity).despawn();
+                    continue;
+                }
+            }
+        }
+        spawner.cooldown -= time.delta_secs();
+        if spawner.cooldown > 0.0 {
+            continue;
+        }
+        let pickup = commands
+            .spawn(pickup_bundle(
+                &mut assets,
+                &mut materials,
+                spawner.effect.clone(),
+                *transform,
+            ))
+            .id();
+        spawner.stocked = Some(pickup);
+    }
+}
+
+fn drop_pickups(
+    mut commands: Commands,
+    mut drops: MessageReader<DropPickup>,
+    mut assets: ResMut<PickupAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for drop in drops.read() {
+        commands.spawn(pickup_bundle(
+            &mut assets,
+            &mut materials,
+            drop.effect.clone(),
+            Transform::from_translation(drop.position),
+        ));
+    }
+}
+
+fn pickup_bundle(
+    assets: &mut PickupAssets,
+    materials: &mut Assets<StandardMaterial>,
+    effect: PickupEffect,
+    transform: Transform,
+) -> impl Bundle {
+    let material = assets.material(materials, effect.color());
+    (
+        Pickup { effect },
+        Mesh3d(assets.mesh.clone()),
+        MeshMaterial3d(material),
+        transform,
+        DespawnOnExit(InSession),
+    )
+}
+
+type Spinning = Or<(With<Pickup>, With<Collectible>)>;
+
+fn spin_pickups(time: Res<Time>, mut pickups: Query<&mut Transform, Spinning>) {
+    for mut transform in &mut pickups {
+        transform.rotate_y(2.0 * time.delta_secs());
+    }
+}
+
+fn attract_pickups(
+    time: Res<Time>,
+    config: Res<PickupConfig>,
+    player: Query<&Transform, (With<Player>, Without<Pickup>)>,
+    mut pickups: Query<&mut Transform, With<Pickup>>,
+) {
+    let Ok(player) = player.single() else {
+        return;
+    };
+    for mut transform in &mut pickups {
+        let offset = player.translation - transform.translation;
+        let distance = offset.length();
+        if distance < config.magnet_radius && distance > f32::EPSILON {
+            let step = (config.magnet_speed * time.delta_secs()).min(distance);
+            transform.translation += offset / distance * step;
+        }
+    }
+}
+
+type Collector<'a> = (
+    &'a Transform,
+    &'a mut Health,
+    Option<&'a mut Armor>,
+    Option<&'a mut WeaponInventory>,
+    Option<&'a mut Stats>,
+);
+
+fn collect_pickups(
+    mut commands: Commands,
+    config: Res<PickupConfig>,
+    mut wallet: Wallet,
+    mut player: Query<Collector, With<Player>>,
+    pickups: Query<(Entity, &Pickup, &Transform), Without<Player>>,
+    mut collected: MessageWriter<PickupCollected>,
+) {
+    let Ok((player_tx, mut health, mut armor, mut inventory, mut stats)) = player.single_mut()
+    else {
+        return;
+    };
+    for (entity, pickup, transform) in &pickups {
+        if transform.translation.distance(player_tx.translation) > config.collect_radius {
+            continue;
+        }
+        // Leave pickups that would do nothing for someone who needs them.
+        let applied = match &pickup.effect {
+            PickupEffect::Health(amount) => {
+                let missing = health.max - health.current;
+                health.current += amount.min(missing);
+                missing > 0.0
+            }
+            PickupEffect::Armor(amount) => match armor.as_mut() {
+                Some(armor) if armor.current < armor.max => {
+                    armor.current = (armor.current + amount).min(armor.max);
+                    true
+                }
+                _ => false,
+            },
+            PickupEffect::Cash(amount) => {
+                wallet.reward(TransactionKind::Pickup, *amount, None, "pickup");
+                true
+            }
+            PickupEffect::Weapon(weapon) => inventory
+                .as_mut()
+                .is_some_and(|inventory| give_weapon(inventory, weapon)),
+            PickupEffect::Ammo(rounds) => inventory
+                .as_mut()
+                .and_then(|inventory| {
+                    let equipped = inventory.equipped;
+                    inventory.weapons.get_mut(equipped)
+                })
+                .map(|weapon| weapon.reserve += rounds)
+                .is_some(),
+            PickupEffect::Buff(modifier) => stats
+                .as_mut()
+                .map(|stats| stats.replace_modifier(*modifier))
+                .is_some(),
+        };
+        if applied {
+            commands.entity(entity).despawn();
+            collected.write(PickupCollected {
+                effect: pickup.effect.clone(),
+            });
+        }
+    }
+}
+
+/// Adds the weapon, or tops up ammo if we already carry one of the same name.
+fn give_weapon(inventory: &mut WeaponInventory, weapon: &Weapon) -> bool {
+    match inventory.weapons.iter_mut().find(|w| w.name == weapon.name) {
+        Some(owned) => owned.reserve += weapon.clip + weapon.reserve,
+        None => inventory.weapons.push(weapon.clone()),
+    }
+    true
+}
+
+fn collect_tokens(
+    mut commands: Commands,
+    config: Res<PickupConfig>,
+    mut collectibles: ResMut<Collectibles>,
+    mut wallet: Wallet,
+    mut toasts: MessageWriter<Toast>,
+    player: Query<&Transform, With<Player>>,
+    tokens: Query<(Entity, &Collectible, &Transform), Without<Player>>,
+) {
+    let Ok(player) = player.single() else {
+        return;
+    };
+    for (entity, token, transform) in &tokens {
+        if transform.translation.distance(player.translation) > config.collect_radius {
+            continue;
+        }
+        commands.entity(entity).despawn();
+        if collectibles.found.contains(&token.id) {
+            continue;
+        }
+        collectibles.found.push(token.id);
+        let reward = collectibles.reward_each;
+        wallet.reward(TransactionKind::Pickup, reward, None, token.id);
+        toasts.write(Toast::new(format!(
+            "Hidden token {}/{}",
+            collectibles.found.len(),
+            collectibles.tokens.len()
+        )));
+
+        if collectibles.is_complete() {
+            let bonus = collectibles.completion_bonus;
+            wallet.reward(TransactionKind::Pickup, bonus, None, "all hidden tokens");
+            toasts.write(Toast::new(format!("All hidden tokens found!  +${bonus}")));
+        }
+    }
+}
+
+fn announce_pickups(
+    mut collected: MessageReader<PickupCollected>,
+    mut toasts: MessageWriter<Toast>,
+) {
+    for pickup in collected.read() {
+        toasts.write(Toast::new(pickup.effect.label()));
+    }
+}
@@ -1,10 +1,15 @@
+use anyhow::Context;
 use avian3d::prelude::*;
-use bevy::pbr::{MeshMaterial3d, StandardMaterial};
 use bevy::prelude::*;
 
-use crate::game::combat::Health;
-use crate::game::core::GameState;
+use crate::game::combat::{Armor, Health, Weapon, WeaponInventory};
+use crate::game::console::{ConsoleAppExt, ConsoleCommand, arg};
+use crate::game::core::{GameState, InSession};
 use crate::game::input::PlayerInput;
+use crate::game::physics::GameLayer;
+use crate::game::prefab::{Overrides, Prefab, PrefabAppExt, PrefabShape, PrefabVisual, Prefabs};
+use crate::game::stats::{Stat, Stats};
+use crate::game::vehicle::Driving;
 
 #[derive(Component)]
 pub struct Player;
@@ -12,9 +17,6 @@ pub struct Player;
 #[derive(Component)]
 pub struct PlayerPawn;
 
-#[derive(Component)]
-pub struct PlayerVisual;
-
 #[derive(Component, Clone, Copy)]
 pub struct PlayerController {
     pub walk_speed: f32,
@@ -26,6 +28,10 @@ pub struct PlayerController {
     pub lateral_damping: f32,
     pub jump_speed: f32,
     pub radius: f32,
+    pub ground_probe: f32,
+    pub max_slope_radians: f32,
+    pub coyote_time: f32,
+    pub jump_buffer_time: f32,
 }
 
 impl Default for PlayerController {
@@ -40,6 +46,10 @@ impl Default for PlayerController {
             lateral_damping: 10.0,
             jump_speed: 8.5,
             radius: 0.6,
+            ground_probe: 0.15,
+            max_slope_radians: 50.0_f32.to_radians(),
+            coyote_time: 0.12,
+            jump_buffer_time: 0.15,
         }
     }
 }
@@ -49,59 +59,199 @@ pub struct PlayerFacing {
     pub yaw: f32,
 }
 
+/// Result of the downward ground probe, refreshed every frame before movement.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct GroundContact {
+    pub grounded: bool,
+    pub entity: Option<Entity>,
+    pub normal: Vec3,
+    pub slope_radians: f32,
+    /// Velocity of whatever we're standing on at the contact point.
+    pub platform_velocity: Vec3,
+    pub platform_yaw_rate: f32,
+    pub time_since_grounded: f32,
+    pub jump_buffer: f32,
+}
+
+/// The player while they're walking rather than driving.
+type OnFoot = (With<Player>, Without<Driving>);
+
+/// Layers the player can stand on.
+const GROUND_LAYERS: [GameLayer; 4] = [
+    GameLayer::Default,
+    GameLayer::Static,
+    GameLayer::Prop,
+    GameLayer::Vehicle,
+];
+
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::InGame), spawn_player)
-            .add_systems(Update, drive_player.run_if(in_state(GameState::InGame)));
+        app.register_prefab(player_prefab())
+            .add_console_command(ConsoleCommand::new(
+                "teleport",
+                "<x> <z>",
+                "Move the player to a point on the ground.",
+                teleport,
+            ))
+            .add_console_command(ConsoleCommand::new(
+                "god",
+                "",
+                "Toggle player invulnerability.",
+                toggle_god,
+            ))
+            .add_systems(OnEnter(InSession), spawn_player)
+            .add_systems(
+                Update,
+                (detect_ground, drive_player)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
     }
 }
 
-fn spawn_player(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-) {
+fn teleport(world: &mut World, args: &[&str]) -> anyhow::Result<String> {
+    let x: f32 = arg(args, 0, "x")?;
+    let z: f32 = arg(args, 1, "z")?;
+    let mut player = world
+        .query_filtered::<(&mut Transform, &mut Position, &mut LinearVelocity), With<Player>>();
+    let (mut transform, mut position, mut velocity) =
+        player.single_mut(world).context("no player")?;
+    let target = Vec3::new(x, transform.translation.y, z);
+    transform.translation = target;
+    position.0 = target;
+    velocity.0 = Vec3::ZERO;
+    Ok(format!("teleported to {x} {z}"))
+}
+
+fn toggle_god(world: &mut World, _args: &[&str]) -> anyhow::Result<String> {
+    let mut player = world.query_filtered::<&mut Health, With<Player>>();
+    let mut health = player.single_mut(world).context("no player")?;
+    health.invulnerable = !health.invulnerable;
+    Ok(format!(
+        "god mode {}",
+        if health.invulnerable { "on" } else { "off" }
+    ))
+}
+
+fn spawn_player(mut prefabs: Prefabs) {
+    let transform = Transform::from_xyz(0.0, 1.2, 0.0);
+    if let Err(err) = prefabs.spawn("player", Overrides::at(transform)) {
+        error!("{err}");
+    }
+}
+
+fn player_prefab() -> Prefab {
     let controller = PlayerController::default();
+    let mut visual = PrefabVisual::new(
+        PrefabShape::Sphere(controller.radius),
+        Color::srgb(0.7, 0.18, 0.3),
+    );
+    visual.metallic = 0.15;
+    // Render-only mesh; keep colliders on the parent body.
+    visual.child = true;
+    Prefab::new("player")
+        .body(
+            RigidBody::Dynamic,
+            PrefabShape::Sphere(controller.radius),
+            GameLayer::Player,
+        )
+        .mass(110.0)
+        .visual(visual)
+        .with(move |player| {
+            player.insert((
+                Player,
+                PlayerPawn,
+                controller,
+                Friction::new(0.02),
+                LinearDamping(0.0),
+                AngularDamping(0.0),
+                LockedAxes::new().lock_rotation_x().lock_rotation_z(),
+                Health::new(150.0),
+                Stats::default()
+                    .with_base(Stat::MaxHealth, 150.0)
+                    .with_base(Stat::WalkSpeed, controller.walk_speed)
+                    .with_base(Stat::SprintSpeed, controller.sprint_speed)
+                    .with_base(Stat::Acceleration, controller.acceleration),
+                Armor::new(50.0),
+                WeaponInventory {
+                    weapons: vec![Weapon {
+                        name: "Pistol",
+                        clip: 12,
+                        clip_size: 12,
+                        reserve: 48,
+                        spread_degrees: 3.0,
+                    }],
+                    equipped: 0,
+                },
+                PlayerFacing { yaw: 0.0 },
+                GroundContact::default(),
+                Name::new("Player"),
+            ));
+        })
+}
 
-    let mut player = commands.spawn((
-        Player,
-        PlayerPawn,
-        controller,
-        RigidBody::Dynamic,
-        Collider::sphere(controller.radius),
-        Friction::new(0.02),
-        Mass(110.0),
-        LinearDamping(0.0),
-        AngularDamping(0.0),
-        LockedAxes::new().lock_rotation_x().lock_rotation_z(),
-        Transform::from_xyz(0.0, 1.2, 0.0).with_rotation(Quat::from_rotation_y(0.0)),
-        GlobalTransform::IDENTITY,
-    ));
-
-    player.insert((
-        Health::new(150.0),
-        PlayerFacing { yaw: 0.0 },
-        Name::new("Player"),
-    ));
-
-    player.with_children(|parent| {
-        parent.spawn((
-            // Render-only mesh; keep colliders on the parent body.
-            Mesh3d(meshes.add(Sphere::new(controller.radius).mesh().uv(24, 16))),
-            MeshMaterial3d(materials.add(StandardMaterial {
-                base_color: Color::srgb(0.7, 0.18, 0.3),
-                metallic: 0.15,
-                perceptual_roughness: 0.5,
-                ..default()
-            })),
-            Transform::IDENTITY,
-            GlobalTransform::IDENTITY,
-            PlayerVisual,
-            Name::new("PlayerVisualMesh"),
-        ));
-    });
+fn detect_ground(
+    time: Res<Time>,
+    spatial_query: SpatialQuery,
+    mut player_query: Query<
+        (
+            Entity,
+            &PlayerController,
+            &Transform,
+            &LinearVelocity,
+            &mut GroundContact,
+        ),
+        OnFoot,
+    >,
+    colliders: Query<&ColliderOf>,
+    bodies: Query<(&LinearVelocity, Option<&AngularVelocity>, &GlobalTransform)>,
+) {
+    let Some((entity, config, transform, velocity, mut ground)) = player_query.iter_mut().next()
+    else {
+        return;
+    };
+
+    // Cast a slightly smaller sphere so we don't start the cast already touching walls.
+    let cast_radius = config.radius * 0.9;
+    let filter = SpatialQueryFilter::from_mask(GROUND_LAYERS).with_excluded_entities([entity]);
+    let hit = spatial_query.cast_shape(
+        &Collider::sphere(cast_radius),
+        transform.translation,
+        Quat::IDENTITY,
+        Dir3::NEG_Y,
+        &ShapeCastConfig::from_max_distance(config.radius - cast_radius + config.ground_probe),
+        &filter,
+    );
+
+    ground.entity = hit.map(|hit| hit.entity);
+    ground.normal = hit.map_or(Vec3::Y, |hit| hit.normal1);
+    ground.slope_radians = ground.normal.angle_between(Vec3::Y);
+    ground.platform_velocity = Vec3::ZERO;
+    ground.platform_yaw_rate = 0.0;
+
+    if let Some(hit) = hit {
+        let body = colliders.get(hit.entity).map_or(hit.entity, |c| c.body);
+        if let Ok((platform_linear, platform_angular, platform_tx)) = bodies.get(body) {
+            let angular = platform_angular.map_or(Vec3::ZERO, |a| a.0);
+            let lever = hit.point1 - platform_tx.translation();
+            ground.platform_velocity = platform_linear.0 + angular.cross(lever);
+            ground.platform_yaw_rate = angular.y;
+        }
+    }
+
+    // Still rising from a jump counts as airborne even if the probe grazes the ground.
+    let rising = velocity.y - ground.platform_velocity.y > 1.0;
+    ground.grounded = hit.is_some() && ground.slope_radians <= config.max_slope_radians && !rising;
+
+    let delta = time.delta_secs();
+    if ground.grounded {
+        ground.time_since_grounded = 0.0;
+    } else {
+        ground.time_since_grounded += delta;
+    }
+    ground.jump_buffer = (ground.jump_buffer - delta).max(0.0);
 }
 
 fn drive_player(
@@ -112,34 +262,39 @@ fn drive_player(
             &PlayerController,
             &mut LinearVelocity,
             &mut PlayerFacing,
-            &Transform,
+            &mut GroundContact,
+            &Stats,
         ),
-        With<Player>,
+        OnFoot,
     >,
 ) {
-    let Some((config, mut velocity, mut facing, transform)) = player_query.iter_mut().next() else {
+    let Some((config, mut velocity, mut facing, mut ground, stats)) =
+        player_query.iter_mut().next()
+    else {
         return;
     };
 
+    let delta = time.delta_secs();
+
     // GTA2-style: left/right steer, forward/back throttle.
     let turn_input = (input.movement.x + input.yaw_input).clamp(-1.0, 1.0);
-    let turn_rate = turn_input * config.turn_speed;
+    let turn_rate = turn_input * config.turn_speed + ground.platform_yaw_rate;
     if turn_rate.abs() > 0.001 {
-        facing.yaw = (facing.yaw + turn_rate * time.delta_secs()).rem_euclid(std::f32::consts::TAU);
+        facing.yaw = (facing.yaw + turn_rate * delta).rem_euclid(std::f32::consts::TAU);
     }
 
-    let delta = time.delta_secs();
     let forward_input = input.movement.y.clamp(-1.0, 1.0);
     let forward_dir = Quat::from_rotation_y(facing.yaw) * Vec3::NEG_Z;
-    let max_speed = if input.sprint {
-        config.sprint_speed
+    let max_speed = stats.get(if input.sprint {
+        Stat::SprintSpeed
     } else {
-        config.walk_speed
-    };
+        Stat::WalkSpeed
+    });
 
-    let on_ground = transform.translation.y <= config.radius + 0.05 && velocity.y.abs() < 0.2;
-    let mut planar = Vec3::new(velocity.x, 0.0, velocity.z);
-    if on_ground {
+    // Movement is computed relative to whatever we stand on so platforms carry us along.
+    let platform = ground.platform_velocity;
+    let mut planar = Vec3::new(velocity.x - platform.x, 0.0, velocity.z - platform.z);
+    if ground.grounded {
         // Kill sideways drift so the pawn doesn't skate when turning/stopping.
         let forward_velocity = forward_dir * planar.dot(forward_dir);
         let lateral_velocity = planar - forward_velocity;
@@ -154,15 +309,37 @@ fn drive_player(
                 planar = planar.normalize_or_zero() * new_speed;
             }
         } else {
-            planar += forward_dir * (forward_input * config.acceleration * delta);
+            planar += forward_dir * (forward_input * stats.get(Stat::Acceleration) * delta);
+        }
+    } else if ground.entity.is_some() {
+        // Too steep to stand on: don't let the player push further up the slope.
+        let uphill = -Vec3::new(ground.normal.x, 0.0, ground.normal.z).normalize_or_zero();
+        let into_slope = planar.dot(uphill);
+        if into_slope > 0.0 {
+            planar -= uphill * into_slope;
         }
     }
 
     planar = planar.clamp_length_max(max_speed);
 
-    if on_ground && input.jump {
-        velocity.y = config.jump_speed;
+    let mut vertical = velocity.y - platform.y;
+    if ground.grounded && ground.slope_radians > 0.01 {
+        // Follow the slope instead of launching off the top of ramps.
+        let along_slope = planar - ground.normal * planar.dot(ground.normal);
+        planar = Vec3::new(along_slope.x, 0.0, along_slope.z);
+        vertical = along_slope.y;
+    }
+
+    if input.jump {
+        ground.jump_buffer = config.jump_buffer_time;
+    }
+    if ground.jump_buffer > 0.0 && ground.time_since_grounded <= config.coyote_time {
+        vertical = config.jump_speed;
+        ground.jump_buffer = 0.0;
+        // Consume coyote time so a buffered press can't double jump.
+        ground.time_since_grounded = config.coyote_time + f32::EPSILON;
+        ground.grounded = false;
     }
 
-    velocity.0 = Vec3::new(planar.x, velocity.y, planar.z);
+    velocity.0 = Vec3::new(planar.x, vertical, planar.z) + platform;
 }
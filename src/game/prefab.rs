@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use avian3d::prelude::*;
+use bevy::ecs::system::{RunSystemOnce, SystemParam};
+use bevy::pbr::{MeshMaterial3d, StandardMaterial};
+use bevy::prelude::*;
+
+use crate::game::console::{ConsoleAppExt, ConsoleCommand};
+use crate::game::core::InSession;
+use crate::game::physics::GameLayer;
+use crate::game::player::{Player, PlayerFacing};
+
+/// Shape shared by a prefab's collider and mesh.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrefabShape {
+    Cuboid(Vec3),
+    Cylinder { radius: f32, height: f32 },
+    Capsule { radius: f32, length: f32 },
+    Sphere(f32),
+}
+
+impl PrefabShape {
+    pub fn collider(self) -> Collider {
+        match self {
+            PrefabShape::Cuboid(size) => Collider::cuboid(size.x, size.y, size.z),
+            PrefabShape::Cylinder { radius, height } => Collider::cylinder(radius, height),
+            PrefabShape::Capsule { radius, length } => Collider::capsule(radius, length),
+            PrefabShape::Sphere(radius) => Collider::sphere(radius),
+        }
+    }
+
+    pub fn mesh(self) -> Mesh {
+        match self {
+            PrefabShape::Cuboid(size) => Cuboid::from_size(size).into(),
+            PrefabShape::Cylinder { radius, height } => Cylinder::new(radius, height).into(),
+            PrefabShape::Capsule { radius, length } => Capsule3d::new(radius, length).into(),
+            PrefabShape::Sphere(radius) => Sphere::new(radius).mesh().uv(24, 16),
+        }
+    }
+
+    /// Full extents of the shape's bounding box.
+    pub fn size(self) -> Vec3 {
+        match self {
+            PrefabShape::Cuboid(size) => size,
+            PrefabShape::Cylinder { radius, height } => {
+                Vec3::new(radius * 2.0, height, radius * 2.0)
+            }
+            PrefabShape::Capsule { radius, length } => {
+                Vec3::new(radius * 2.0, length + radius * 2.0, radius * 2.0)
+            }
+            PrefabShape::Sphere(radius) => Vec3::splat(radius * 2.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PrefabVisual {
+    pub shape: PrefabShape,
+    pub color: Color,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub unlit: bool,
+    pub alpha_mode: AlphaMode,
+    /// Put the mesh on a child entity, leaving the body free to carry only
+    /// physics.
+    pub child: bool,
+}
+
+impl PrefabVisual {
+    pub fn new(shape: PrefabShape, color: Color) -> Self {
+        Self {
+            shape,
+            color,
+            metallic: 0.0,
+            roughness: 0.5,
+            unlit: false,
+            alpha_mode: AlphaMode::Opaque,
+            child: false,
+        }
+    }
+
+    fn material(&self, color: Color) -> StandardMaterial {
+        StandardMaterial {
+            base_color: color,
+            metallic: self.metallic,
+            perceptual_roughness: self.roughness,
+            unlit: self.unlit,
+            alpha_mode: self.alpha_mode,
+            ..default()
+        }
+    }
+}
+
+/// Adds a prefab's gameplay components after its physics and visual ones.
+pub type PrefabComponents = Arc<dyn Fn(&mut EntityCommands) + Send + Sync>;
+
+/// A spawnable archetype, declared once and spawned by name through
+/// [`Prefabs`].
+#[derive(Clone)]
+pub struct Prefab {
+    pub name: &'static str,
+    pub body: Option<RigidBody>,
+    pub collider: Option<PrefabShape>,
+    pub layer: Option<GameLayer>,
+    pub mass: Option<f32>,
+    pub visual: Option<PrefabVisual>,
+    pub components: Option<PrefabComponents>,
+}
+
+impl Prefab {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            body: None,
+            collider: None,
+            layer: None,
+            mass: None,
+            visual: None,
+            components: None,
+        }
+    }
+
+    pub fn body(mut self, body: RigidBody, collider: PrefabShape, layer: GameLayer) -> Self {
+        self.body = Some(body);
+        self.collider = Some(collider);
+        self.layer = Some(layer);
+        self
+    }
+
+    /// A collider with no body of its own, e.g. for a trigger.
+    pub fn collider(mut self, collider: PrefabShape) -> Self {
+        self.collider = Some(collider);
+        self
+    }
+
+    pub fn mass(mut self, mass: f32) -> Self {
+        self.mass = Some(mass);
+        self
+    }
+
+    pub fn visual(mut self, visual: PrefabVisual) -> Self {
+        self.visual = Some(visual);
+        self
+    }
+
+    pub fn with(
+        mut self,
+        components: impl Fn(&mut EntityCommands) + Send + Sync + 'static,
+    ) -> Self {
+        self.components = Some(Arc::new(components));
+        self
+    }
+}
+
+/// Per-instance changes to a prefab. Anything else can be inserted on the
+/// returned [`EntityCommands`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Overrides {
+    pub transform: Transform,
+    /// Draws the instance in this colour instead of the prefab's.
+    pub color: Option<Color>,
+}
+
+impl Overrides {
+    pub fn at(transform: Transform) -> Self {
+        Self {
+            transform,
+            color: None,
+        }
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
+/// Render-only child mesh of a prefab with [`PrefabVisual::child`] set.
+#[derive(Component, Debug)]
+pub struct VisualMesh;
+
+#[derive(Resource, Default, Clone)]
+pub struct PrefabRegistry {
+    prefabs: Vec<Prefab>,
+}
+
+impl PrefabRegistry {
+    pub fn get(&self, name: &str) -> Option<&Prefab> {
+        self.prefabs
+            .iter()
+            .find(|prefab| prefab.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.prefabs.iter().map(|prefab| prefab.name)
+    }
+}
+
+/// Mesh and material handles shared by every instance of a prefab.
+/// Materials are per colour, so recoloured instances share them too.
+#[derive(Resource, Default)]
+struct PrefabCache {
+    meshes: HashMap<&'static str, Handle<Mesh>>,
+    materials: HashMap<(&'static str, [u8; 4]), Handle<StandardMaterial>>,
+}
+
+pub trait PrefabAppExt {
+    fn register_prefab(&mut self, prefab: Prefab) -> &mut Self;
+}
+
+impl PrefabAppExt for App {
+    fn register_prefab(&mut self, prefab: Prefab) -> &mut Self {
+        let mut registry = self
+            .init_resource::<PrefabRegistry>()
+            .world_mut()
+            .resource_mut::<PrefabRegistry>();
+        registry
+            .prefabs
+            .retain(|existing| existing.name != prefab.name);
+        registry.prefabs.push(prefab);
+        self
+    }
+}
+
+/// Spawns registered prefabs by name.
+#[derive(SystemParam)]
+pub struct Prefabs<'w, 's> {
+    pub commands: Commands<'w, 's>,
+    registry: Res<'w, PrefabRegistry>,
+    cache: ResMut<'w, PrefabCache>,
+    meshes: ResMut<'w, Assets<Mesh>>,
+    materials: ResMut<'w, Assets<StandardMaterial>>,
+}
+
+impl Prefabs<'_, '_> {
+    pub fn registry(&self) -> &PrefabRegistry {
+        &self.registry
+    }
+
+    /// The shared material for `name` in `color`, e.g. to repaint an
+    /// instance. `None` if there's no such prefab or it has no visual.
+    pub fn material_for(&mut self, name: &str, color: Color) -> Option<Handle<StandardMaterial>> {
+        let prefab = self.registry.get(name)?;
+        let (name, visual) = (prefab.name, prefab.visual?);
+        Some(self.material(name, &visual, color))
+    }
+
+    fn material(
+        &mut self,
+        name: &'static str,
+        visual: &PrefabVisual,
+        color: Color,
+    ) -> Handle<StandardMaterial> {
+        self.cache
+            .materials
+            .entry((name, color.to_srgba().to_u8_array()))
+            .or_insert_with(|| self.materials.add(visual.material(color)))
+            .clone()
+    }
+
+    /// Spawns `name` for the current session.
+    pub fn spawn(
+        &mut self,
+        name: &str,
+        overrides: Overrides,
+    ) -> anyhow::Result<EntityCommands<'_>> {
+        let Some(prefab) = self.registry.get(name) else {
+            anyhow::bail!("unknown prefab `{name}`");
+        };
+        let prefab = prefab.clone();
+
+        let visual = prefab.visual.map(|visual| {
+            let mesh = self
+                .cache
+                .meshes
+                .entry(prefab.name)
+                .or_insert_with(|| self.meshes.add(visual.shape.mesh()))
+                .clone();
+            let color = overrides.color.unwrap_or(visual.color);
+            let material = self.material(prefab.name, &visual, color);
+            (visual.child, Mesh3d(mesh), MeshMaterial3d(material))
+        });
+
+        let mut entity = self.commands.spawn((
+            overrides.transform,
+            DespawnOnExit(InSession),
+            Name::new(prefab.name),
+        ));
+        if let Some(body) = prefab.body {
+            entity.insert(body);
+        }
+        if let Some(shape) = prefab.collider {
+            entity.insert(shape.collider());
+        }
+        if let Some(layer) = prefab.layer {
+            entity.insert(layer.layers());
+        }
+        if let Some(mass) = prefab.mass {
+            entity.insert(Mass(mass));
+        }
+        match visual {
+            Some((true, mesh, material)) => {
+                entity.with_child((
+                    mesh,
+                    material,
+                    Transform::IDENTITY,
+                    VisualMesh,
+                    Name::new(format!("{} visual", prefab.name)),
+                ));
+            }
+            Some((false, mesh, material)) => {
+                entity.insert((mesh, material));
+            }
+            None => {}
+        }
+        if let Some(components) = &prefab.components {
+            components(&mut entity);
+        }
+        Ok(entity)
+    }
+}
+
+pub struct PrefabPlugin;
+
+impl Plugin for PrefabPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PrefabRegistry>()
+            .init_resource::<PrefabCache>()
+            .add_console_command(
+                ConsoleCommand::new(
+                    "spawn",
+                    "<prefab>",
+                    "Spawn a prefab in front of the player.",
+                    spawn_command,
+                )
+                .with_completions(|world| {
+                    world
+                        .resource::<PrefabRegistry>()
+                        .names()
+                        .map(String::from)
+                        .collect()
+                }),
+            );
+    }
+}
+
+fn spawn_command(world: &mut World, args: &[&str]) -> anyhow::Result<String> {
+    let name = args.first().copied().unwrap_or_default().to_string();
+    world
+        .run_system_once_with(spawn_in_front_of_player, name.clone())
+        .map_err(|err| anyhow::anyhow!("{err}"))??;
+    Ok(format!("spawned {name}"))
+}
+
+fn spawn_in_front_of_player(
+    In(name): In<String>,
+    mut prefabs: Prefabs,
+    player: Query<(&Transform, &PlayerFacing), With<Player>>,
+) -> anyhow::Result<()> {
+    let Ok((transform, facing)) = player.single() else {
+        anyhow::bail!("no player to spawn next to");
+    };
+    let size = prefabs
+        .registry()
+        .get(&name)
+        .and_then(|prefab| prefab.collider.or(prefab.visual.map(|visual| visual.shape)))
+        .map_or(Vec3::ONE, PrefabShape::size);
+    let rotation = Quat::from_rotation_y(facing.yaw);
+    let offset = rotation * Vec3::NEG_Z * (size.z * 0.5 + 2.0);
+    let position = Vec3::new(
+        transform.translation.x + offset.x,
+        size.y * 0.5 + 0.1,
+        transform.translation.z + offset.z,
+    );
+    prefabs.spawn(
+        &name,
+        Overrides::at(Transform::from_translation(position).with_rotation(rotation)),
+    )?;
+    Ok(())
+}
@@ -1,15 +1,328 @@
+use std::fmt;
+
 use bevy::prelude::*;
 
+use crate::game::combat::{DamageEvent, Killed};
+use crate::game::player::Player;
+use crate::game::stats::{ModifierSource, Stat, StatModifier, Stats};
+use crate::game::ui::Toast;
+
 #[derive(Resource, Default)]
 pub struct Progression {
     pub cash: u32,
     pub skill_points: u32,
+    pub xp: u32,
+    pub unlocked_skills: Vec<&'static str>,
+}
+
+impl Progression {
+    /// Adds experience and returns how many skill points it earned.
+    pub fn add_xp(&mut self, amount: u32, xp_per_point: u32) -> u32 {
+        let per_point = xp_per_point.max(1);
+        let before = self.xp / per_point;
+        self.xp = self.xp.saturating_add(amount);
+        let earned = self.xp / per_point - before;
+        self.skill_points += earned;
+        earned
+    }
+
+    pub fn has_skill(&self, id: &str) -> bool {
+        self.unlocked_skills.contains(&id)
+    }
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct XpConfig {
+    pub xp_per_skill_point: u32,
+    pub xp_per_damage: f32,
+    pub xp_per_kill: u32,
+}
+
+impl Default for XpConfig {
+    fn default() -> Self {
+        Self {
+            xp_per_skill_point: 1000,
+            xp_per_damage: 0.2,
+            xp_per_kill: 50,
+        }
+    }
+}
+
+#[derive(Message, Debug, Clone, Copy)]
+pub struct GrantXp {
+    pub amount: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SkillEffect {
+    pub stat: Stat,
+    pub add: f32,
+    pub mul: f32,
+}
+
+const fn add(stat: Stat, add: f32) -> SkillEffect {
+    SkillEffect {
+        stat,
+        add,
+        mul: 1.0,
+    }
+}
+
+const fn mul(stat: Stat, mul: f32) -> SkillEffect {
+    SkillEffect {
+        stat,
+        add: 0.0,
+        mul,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SkillDefinition {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub cost: u32,
+    pub requires: &'static [&'static str],
+    pub effects: Vec<SkillEffect>,
+}
+
+fn skill(
+    id: &'static str,
+    name: &'static str,
+    description: &'static str,
+    cost: u32,
+    requires: &'static [&'static str],
+    effects: Vec<SkillEffect>,
+) -> SkillDefinition {
+    SkillDefinition {
+        id,
+        name,
+        description,
+        cost,
+        requires,
+        effects,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkillError {
+    Unknown,
+    AlreadyUnlocked,
+    MissingPrerequisite(&'static str),
+    NotEnoughPoints { cost: u32, available: u32 },
+}
+
+impl fmt::Display for SkillError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkillError::Unknown => write!(f, "no such skill"),
+            SkillError::AlreadyUnlocked => write!(f, "already unlocked"),
+            SkillError::MissingPrerequisite(id) => write!(f, "requires `{id}`"),
+            SkillError::NotEnoughPoints { cost, available } => {
+                write!(f, "costs {cost} points, {available} available")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SkillError {}
+
+#[derive(Resource, Debug, Clone)]
+pub struct SkillTree {
+    pub skills: Vec<SkillDefinition>,
+}
+
+impl Default for SkillTree {
+    fn default() -> Self {
+        Self {
+            skills: vec![
+                skill(
+                    "fleet_foot",
+                    "Fleet Foot",
+                    "Sprint 10% faster.",
+                    1,
+                    &[],
+                    vec![mul(Stat::SprintSpeed, 1.1)],
+                ),
+                skill(
+                    "marathon",
+                    "Marathon",
+                    "Sprint another 1.5 m/s faster.",
+                    2,
+                    &["fleet_foot"],
+                    vec![add(Stat::SprintSpeed, 1.5)],
+                ),
+                skill(
+                    "thick_skin",
+                    "Thick Skin",
+                    "+25 max health.",
+                    1,
+                    &[],
+                    vec![add(Stat::MaxHealth, 25.0)],
+                ),
+                skill(
+                    "iron_body",
+                    "Iron Body",
+                    "+20% max health.",
+                    2,
+                    &["thick_skin"],
+                    vec![mul(Stat::MaxHealth, 1.2)],
+                ),
+                skill(
+                    "steady_hands",
+                    "Steady Hands",
+                    "20% tighter weapon spread.",
+                    1,
+                    &[],
+                    vec![mul(Stat::WeaponSpread, 0.8)],
+                ),
+                skill(
+                    "deadeye",
+                    "Deadeye",
+                    "A further 25% tighter spread.",
+                    2,
+                    &["steady_hands"],
+                    vec![mul(Stat::WeaponSpread, 0.75)],
+                ),
+                skill(
+                    "wheelman",
+                    "Wheelman",
+                    "15% better vehicle handling.",
+                    1,
+                    &[],
+                    vec![mul(Stat::VehicleHandling, 1.15)],
+                ),
+                skill(
+                    "getaway_driver",
+                    "Getaway Driver",
+                    "A further 20% better handling.",
+                    2,
+                    &["wheelman"],
+                    vec![mul(Stat::VehicleHandling, 1.2)],
+                ),
+            ],
+        }
+    }
+}
+
+impl SkillTree {
+    pub fn get(&self, id: &str) -> Option<&SkillDefinition> {
+        self.skills.iter().find(|skill| skill.id == id)
+    }
+
+    pub fn check_unlock(
+        &self,
+        progression: &Progression,
+        id: &str,
+    ) -> Result<&SkillDefinition, SkillError> {
+        let skill = self.get(id).ok_or(SkillError::Unknown)?;
+        if progression.has_skill(skill.id) {
+            return Err(SkillError::AlreadyUnlocked);
+        }
+        if let Some(missing) = skill
+            .requires
+            .iter()
+            .find(|required| !progression.has_skill(required))
+        {
+            return Err(SkillError::MissingPrerequisite(missing));
+        }
+        if progression.skill_points < skill.cost {
+            return Err(SkillError::NotEnoughPoints {
+                cost: skill.cost,
+                available: progression.skill_points,
+            });
+        }
+        Ok(skill)
+    }
+
+    pub fn unlock(&self, progression: &mut Progression, id: &str) -> Result<(), SkillError> {
+        let skill = self.check_unlock(progression, id)?;
+        progression.skill_points -= skill.cost;
+        progression.unlocked_skills.push(skill.id);
+        Ok(())
+    }
 }
 
 pub struct ProgressionPlugin;
 
 impl Plugin for ProgressionPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<Progression>();
+        app.init_resource::<Progression>()
+            .init_resource::<XpConfig>()
+            .init_resource::<SkillTree>()
+            .add_message::<GrantXp>()
+            .add_systems(
+                Update,
+                (award_combat_xp, grant_xp, apply_skill_modifiers).chain(),
+            );
+    }
+}
+
+fn award_combat_xp(
+    config: Res<XpConfig>,
+    mut damage: MessageReader<DamageEvent>,
+    mut kills: MessageReader<Killed>,
+    player: Query<Entity, With<Player>>,
+    mut grants: MessageWriter<GrantXp>,
+) {
+    let Ok(player) = player.single() else {
+        damage.clear();
+        kills.clear();
+        return;
+    };
+    let dealt: f32 = damage
+        .read()
+        .filter(|event| event.source == Some(player) && event.entity != player)
+        .map(|event| event.amount)
+        .sum();
+    let kills = kills.read().filter(|kill| kill.by == Some(player)).count() as u32;
+    let amount = (dealt * config.xp_per_damage).round() as u32 + kills * config.xp_per_kill;
+    if amount > 0 {
+        grants.write(GrantXp { amount });
+    }
+}
+
+fn grant_xp(
+    config: Res<XpConfig>,
+    mut grants: MessageReader<GrantXp>,
+    mut progression: ResMut<Progression>,
+    mut toasts: MessageWriter<Toast>,
+) {
+    for grant in grants.read() {
+        let earned = progression.add_xp(grant.amount, config.xp_per_skill_point);
+        if earned > 0 {
+            toasts.write(Toast::new(format!("+{earned} skill point")));
+        }
+    }
+}
+
+/// Rebuilds the player's skill modifiers whenever the unlocked set might
+/// have changed.
+fn apply_skill_modifiers(
+    tree: Res<SkillTree>,
+    progression: Res<Progression>,
+    mut player: Query<&mut Stats, With<Player>>,
+) {
+    let Ok(mut stats) = player.single_mut() else {
+        return;
+    };
+    if !progression.is_changed() && !stats.is_added() {
+        return;
+    }
+    stats.remove_where(|source| matches!(source, ModifierSource::Skill(_)));
+    for skill in progression
+        .unlocked_skills
+        .iter()
+        .filter_map(|id| tree.get(id))
+    {
+        for effect in &skill.effects {
+            stats.add_modifier(StatModifier {
+                stat: effect.stat,
+                source: ModifierSource::Skill(skill.id),
+                add: effect.add,
+                mul: effect.mul,
+                remaining: None,
+            });
+        }
     }
 }
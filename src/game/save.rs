@@ -0,0 +1,437 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use crate::game::ai::WantedLevel;
+use crate::game::core::{GameState, InSession};
+use crate::game::economy::{Ledger, Transaction, TransactionKind};
+use crate::game::faction::FactionRespect;
+use crate::game::mission::{MissionCatalog, MissionLog};
+use crate::game::pickup::Collectibles;
+use crate::game::player::Player;
+use crate::game::progression::{Progression, SkillTree};
+use crate::game::shop::{Garage, StoredVehicle};
+use crate::game::ui::Toast;
+use crate::game::vehicle::VehicleModel;
+
+/// Everything persisted between sessions. Stored as `key=value` lines so
+/// files stay diffable and unknown keys from newer builds are ignored.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SaveData {
+    pub cash: u32,
+    pub skill_points: u32,
+    pub xp: u32,
+    pub skills: Vec<String>,
+    /// Hidden tokens found so far.
+    pub collectibles: Vec<String>,
+    pub respect: [i32; 5],
+    pub player_position: Option<Vec3>,
+    /// Recent ledger entries, oldest first.
+    pub ledger: Vec<Transaction>,
+    /// Garaged vehicles as model name and sRGB paint.
+    pub garage: Vec<(String, [f32; 3])>,
+    pub active_mission: Option<String>,
+    pub completed_missions: Vec<String>,
+}
+
+impl SaveData {
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![
+            format!("cash={}", self.cash),
+            format!("skill_points={}", self.skill_points),
+            format!("xp={}", self.xp),
+            format!("skills={}", self.skills.join(",")),
+            format!("collectibles={}", self.collectibles.join(",")),
+            format!("respect={}", join(&self.respect)),
+            format!("completed_missions={}", self.completed_missions.join(",")),
+        ];
+        if let Some(mission) = &self.active_mission {
+            lines.push(format!("active_mission={mission}"));
+        }
+        if let Some(pos) = self.player_position {
+            lines.push(format!("player_position={},{},{}", pos.x, pos.y, pos.z));
+        }
+        for (model, [r, g, b]) in &self.garage {
+            lines.push(format!("garage={model},{r},{g},{b}"));
+        }
+        for entry in &self.ledger {
+            lines.push(format!(
+                "transaction={},{},{},{}",
+                entry.kind.label(),
+                entry.amount,
+                entry.balance,
+                entry.memo.replace('\n', " ")
+            ));
+        }
+        lines.join("\n") + "\n"
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut data = SaveData::default();
+        for (line_no, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .with_context(|| format!("line {}: expected key=value", line_no + 1))?;
+            let context = || format!("line {}: bad value for `{key}`", line_no + 1);
+            match key {
+                "cash" => data.cash = value.parse().with_context(context)?,
+                "skill_points" => data.skill_points = value.parse().with_context(context)?,
+                "xp" => data.xp = value.parse().with_context(context)?,
+                "skills" => data.skills = split_ids(value),
+                "collectibles" => data.collectibles = split_ids(value),
+                "completed_missions" => data.completed_missions = split_ids(value),
+                "active_mission" => data.active_mission = Some(value.to_string()),
+                "respect" => {
+                    let values = split::<i32>(value).with_context(context)?;
+                    for (slot, value) in data.respect.iter_mut().zip(values) {
+                        *slot = value;
+                    }
+                }
+                "player_position" => {
+                    let values = split::<f32>(value).with_context(context)?;
+                    let [x, y, z] = values[..] else {
+                        anyhow::bail!("{}", context());
+                    };
+                    data.player_position = Some(Vec3::new(x, y, z));
+                }
+                "transaction" => data
+                    .ledger
+                    .push(parse_transaction(value).with_context(context)?),
+                "garage" => {
+                    let (model, paint) = value.split_once(',').with_context(context)?;
+                    let paint = split::<f32>(paint).with_context(context)?;
+                    let [r, g, b] = paint[..] else {
+                        anyhow::bail!("{}", context());
+                    };
+                    data.garage.push((model.to_string(), [r, g, b]));
+                }
+                _ => {}
+            }
+        }
+        Ok(data)
+    }
+}
+
+fn parse_transaction(value: &str) -> Result<Transaction> {
+    // The memo is last so it may contain commas.
+    let mut fields = value.splitn(4, ',');
+    let mut next = || fields.next().context("missing field");
+    let kind = next()?;
+    let kind = TransactionKind::from_label(kind)
+        .with_context(|| format!("unknown transaction kind `{kind}`"))?;
+    Ok(Transaction {
+        kind,
+        amount: next()?.parse()?,
+        balance: next()?.parse()?,
+        memo: next()?.to_string(),
+    })
+}
+
+fn join<T: ToString>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn split_ids(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn split<T: std::str::FromStr>(value: &str) -> Result<Vec<T>, T::Err> {
+    value.split(',').map(|v| v.trim().parse()).collect()
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct SaveConfig {
+    pub directory: PathBuf,
+    pub slots: usize,
+}
+
+impl Default for SaveConfig {
+    fn default() -> Self {
+        Self {
+            directory: PathBuf::from("saves"),
+            slots: 3,
+        }
+    }
+}
+
+impl SaveConfig {
+    pub fn slot_path(&self, slot: usize) -> PathBuf {
+        self.directory.join(format!("slot{slot}.sav"))
+    }
+
+    /// Occupied slots with their last write time.
+    pub fn occupied_slots(&self) -> Vec<(usize, SystemTime)> {
+        (0..self.slots)
+            .filter_map(|slot| {
+                let modified = fs::metadata(self.slot_path(slot)).ok()?.modified().ok()?;
+                Some((slot, modified))
+            })
+            .collect()
+    }
+
+    pub fn latest_slot(&self) -> Option<usize> {
+        self.occupied_slots()
+            .into_iter()
+            .max_by_key(|(_, modified)| *modified)
+            .map(|(slot, _)| slot)
+    }
+}
+
+#[derive(Message, Debug, Clone, Copy)]
+pub struct SaveGame {
+    pub slot: usize,
+}
+
+/// Load a slot, or start fresh with `slot: None`. A running session is torn
+/// down first, then the game enters `InGame` with the loaded state.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct LoadGame {
+    pub slot: Option<usize>,
+}
+
+/// Loaded data waiting for the next session to start.
+#[derive(Resource, Debug, Default)]
+struct PendingLoad(Option<SaveData>);
+
+/// Restores [`PendingLoad`] on entering `InSession`. Session content that
+/// depends on saved progress, such as collectibles, spawns after this.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ApplyLoadSet;
+
+/// Resources that make up the saved progress.
+#[derive(SystemParam)]
+struct Records<'w> {
+    progression: ResMut<'w, Progression>,
+    skill_tree: Res<'w, SkillTree>,
+    ledger: ResMut<'w, Ledger>,
+    respect: ResMut<'w, FactionRespect>,
+    collectibles: ResMut<'w, Collectibles>,
+    garage: ResMut<'w, Garage>,
+    wanted: ResMut<'w, WantedLevel>,
+    missions: ResMut<'w, MissionLog>,
+    catalog: Res<'w, MissionCatalog>,
+}
+
+impl Records<'_> {
+    fn snapshot(&self, player_position: Option<Vec3>) -> SaveData {
+        let progression = &self.progression;
+        SaveData {
+            cash: progression.cash,
+            skill_points: progression.skill_points,
+            xp: progression.xp,
+            skills: progression
+                .unlocked_skills
+                .iter()
+                .map(|id| id.to_string())
+                .collect(),
+            respect: self.respect.respect,
+            player_position,
+            ledger: self.ledger.entries.iter().cloned().collect(),
+            collectibles: self
+                .collectibles
+                .found
+                .iter()
+                .map(|id| id.to_string())
+                .collect(),
+            garage: self
+                .garage
+                .stored
+                .iter()
+                .map(|stored| {
+                    let paint = stored.paint.to_srgba();
+                    (
+                        stored.model.to_string(),
+                        [paint.red, paint.green, paint.blue],
+                    )
+                })
+                .collect(),
+            active_mission: self
+                .missions
+                .active
+                .as_ref()
+                .map(|mission| mission.id.to_string()),
+            completed_missions: self
+                .missions
+                .completed
+                .iter()
+                .map(|id| id.to_string())
+                .collect(),
+        }
+    }
+
+    fn restore(&mut self, data: &SaveData) {
+        *self.progression = Progression {
+            cash: data.cash,
+            skill_points: data.skill_points,
+            xp: data.xp,
+            // Skills removed from the tree since the save was written are dropped.
+            unlocked_skills: data
+                .skills
+                .iter()
+                .filter_map(|id| self.skill_tree.get(id).map(|skill| skill.id))
+                .collect(),
+        };
+        self.ledger.entries = data.ledger.iter().cloned().collect();
+        self.respect.respect = data.respect;
+        let known: Vec<_> = self.collectibles.tokens.iter().map(|(id, _)| *id).collect();
+        self.collectibles.found = known
+            .into_iter()
+            .filter(|id| data.collectibles.iter().any(|found| found == id))
+            .collect();
+        // Likewise for vehicle models that no longer exist.
+        self.garage.stored = data
+            .garage
+            .iter()
+            .filter_map(|(model, [r, g, b])| {
+                VehicleModel::find(model).map(|model| StoredVehicle {
+                    model: model.name,
+                    paint: Color::srgb(*r, *g, *b),
+                })
+            })
+            .collect();
+        // And for missions.
+        self.missions.active = data
+            .active_mission
+            .as_ref()
+            .and_then(|id| self.catalog.get(id).cloned());
+        self.missions.completed = data
+            .completed_missions
+            .iter()
+            .filter_map(|id| self.catalog.get(id).map(|mission| mission.id))
+            .collect();
+        self.wanted.clear();
+    }
+}
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SaveConfig>()
+            .init_resource::<PendingLoad>()
+            .add_message::<SaveGame>()
+            .add_message::<LoadGame>()
+            .add_systems(OnEnter(InSession), apply_pending_load.in_set(ApplyLoadSet))
+            .add_systems(
+                Update,
+                (
+                    write_saves,
+                    load_games,
+                    start_pending_load.run_if(in_state(GameState::MainMenu)),
+                    place_loaded_player.run_if(in_state(GameState::InGame)),
+                ),
+            );
+    }
+}
+
+fn write_file(config: &SaveConfig, slot: usize, data: &SaveData) -> Result<()> {
+    fs::create_dir_all(&config.directory)
+        .with_context(|| format!("creating {}", config.directory.display()))?;
+    let path = config.slot_path(slot);
+    fs::write(&path, data.to_text()).with_context(|| format!("writing {}", path.display()))
+}
+
+fn read_file(config: &SaveConfig, slot: usize) -> Result<SaveData> {
+    let path = config.slot_path(slot);
+    let text = fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+    SaveData::parse(&text).with_context(|| format!("parsing {}", path.display()))
+}
+
+fn write_saves(
+    mut requests: MessageReader<SaveGame>,
+    mut toasts: MessageWriter<Toast>,
+    config: Res<SaveConfig>,
+    records: Records,
+    player: Query<&Transform, With<Player>>,
+) {
+    for request in requests.read() {
+        let data = records.snapshot(player.single().ok().map(|tx| tx.translation));
+        match write_file(&config, request.slot, &data) {
+            Ok(()) => {
+                toasts.write(Toast::new("Game saved"));
+            }
+            Err(err) => {
+                error!("save failed: {err:#}");
+                toasts.write(Toast::new("Save failed"));
+            }
+        }
+    }
+}
+
+/// Reads the requested slot and leaves any running session, so the world
+/// is rebuilt from scratch around the loaded state.
+fn load_games(
+    mut requests: MessageReader<LoadGame>,
+    config: Res<SaveConfig>,
+    mut pending: ResMut<PendingLoad>,
+    session: Option<Res<State<InSession>>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(request) = requests.read().last() else {
+        return;
+    };
+    let data = match request.slot {
+        Some(slot) => match read_file(&config, slot) {
+            Ok(data) => data,
+            Err(err) => {
+                error!("load failed: {err:#}");
+                return;
+            }
+        },
+        None => SaveData::default(),
+    };
+    pending.0 = Some(data);
+    next_state.set(if session.is_some() {
+        GameState::MainMenu
+    } else {
+        GameState::InGame
+    });
+}
+
+/// Second half of loading from inside a session: once it has been torn
+/// down, start the next one.
+fn start_pending_load(pending: Res<PendingLoad>, mut next_state: ResMut<NextState<GameState>>) {
+    if pending.0.is_some() {
+        next_state.set(GameState::InGame);
+    }
+}
+
+fn apply_pending_load(pending: Res<PendingLoad>, mut records: Records) {
+    if let Some(data) = &pending.0 {
+        records.restore(data);
+    }
+}
+
+/// The player spawns with the session, so their position is restored once
+/// they exist.
+fn place_loaded_player(
+    mut pending: ResMut<PendingLoad>,
+    mut player: Query<&mut Transform, With<Player>>,
+) {
+    if pending.0.is_none() {
+        return;
+    }
+    let Ok(mut transform) = player.single_mut() else {
+        return;
+    };
+    if let Some(position) = pending.0.take().and_then(|data| data.player_position) {
+        transform.translation = position;
+    }
+}
@@ -0,0 +1,653 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::ui::{FlexDirection, PositionType, UiRect, Val};
+use serde::Deserialize;
+
+use crate::game::ai::WantedLevel;
+use crate::game::combat::{Health, Weapon, WeaponInventory};
+use crate::game::core::{GameState, InSession};
+use crate::game::economy::{TransactionKind, Wallet};
+use crate::game::input::{NUMBER_KEYS, PlayerInput, keyboard_free};
+use crate::game::map::{Blip, BlipKind};
+use crate::game::player::Player;
+use crate::game::prefab::{Overrides, Prefab, PrefabAppExt, PrefabShape, PrefabVisual, Prefabs};
+use crate::game::time_of_day::{WorldClock, format_hour};
+use crate::game::trigger::TriggerVolume;
+use crate::game::ui::Toast;
+use crate::game::vehicle::{Driving, Vehicle, VehicleModel, VehiclePaint, spawn_vehicle};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum ServiceKind {
+    WeaponStore,
+    PaintShop,
+    Garage,
+    Hospital,
+}
+
+impl ServiceKind {
+    pub const ALL: [ServiceKind; 4] = [
+        ServiceKind::WeaponStore,
+        ServiceKind::PaintShop,
+        ServiceKind::Garage,
+        ServiceKind::Hospital,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ServiceKind::WeaponStore => "weapon_store",
+            ServiceKind::PaintShop => "paint_shop",
+            ServiceKind::Garage => "garage",
+            ServiceKind::Hospital => "hospital",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.label() == label)
+    }
+
+    /// Opening and closing hour, or `None` if it never closes.
+    pub fn hours(self) -> Option<(f32, f32)> {
+        match self {
+            ServiceKind::WeaponStore => Some((8.0, 22.0)),
+            ServiceKind::PaintShop => Some((7.0, 2.0)),
+            ServiceKind::Garage | ServiceKind::Hospital => None,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            ServiceKind::WeaponStore => Color::srgb(0.85, 0.75, 0.3),
+            ServiceKind::PaintShop => Color::srgb(0.8, 0.35, 0.95),
+            ServiceKind::Garage => Color::srgb(0.5, 0.55, 0.6),
+            ServiceKind::Hospital => Color::srgb(0.9, 0.25, 0.3),
+        }
+    }
+}
+
+/// A shop or service the player can walk (or drive) into and open with Interact.
+#[derive(Component, Debug, Clone, Copy)]
+#[require(TriggerVolume = TriggerVolume::new("service"))]
+pub struct ServiceLocation {
+    pub kind: ServiceKind,
+    pub name: &'static str,
+}
+
+/// Prices and stock for every service.
+#[derive(Resource, Debug, Clone)]
+pub struct ServiceCatalog {
+    pub weapons: Vec<(Weapon, u32)>,
+    pub ammo_rounds: u32,
+    pub ammo_price: u32,
+    pub respray_price: u32,
+    pub paints: Vec<Color>,
+    pub hospital_fee: u32,
+}
+
+impl Default for ServiceCatalog {
+    fn default() -> Self {
+        let weapon = |name, clip_size, spread_degrees| Weapon {
+            name,
+            clip: clip_size,
+            clip_size,
+            reserve: clip_size * 3,
+            spread_degrees,
+        };
+        Self {
+            weapons: vec![
+                (weapon("Pistol", 12, 3.0), 400),
+                (weapon("SMG", 30, 6.0), 1500),
+                (weapon("Shotgun", 6, 12.0), 2200),
+                (weapon("Rifle", 20, 1.5), 4000),
+            ],
+            ammo_rounds: 30,
+            ammo_price: 60,
+            respray_price: 500,
+            paints: vec![
+                Color::srgb(0.75, 0.1, 0.12),
+                Color::srgb(0.1, 0.3, 0.75),
+                Color::srgb(0.9, 0.9, 0.92),
+                Color::srgb(0.08, 0.08, 0.1),
+                Color::srgb(0.95, 0.7, 0.1),
+            ],
+            hospital_fee: 250,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredVehicle {
+    pub model: &'static str,
+    pub paint: Color,
+}
+
+/// Vehicles parked in the garage. Persisted in save files.
+#[derive(Resource, Debug, Clone)]
+pub struct Garage {
+    pub capacity: usize,
+    pub stored: Vec<StoredVehicle>,
+}
+
+impl Default for Garage {
+    fn default() -> Self {
+        Self {
+            capacity: 4,
+            stored: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum ServiceAction {
+    BuyWeapon(usize),
+    BuyAmmo,
+    Respray,
+    StoreVehicle,
+    RetrieveVehicle(usize),
+    Heal,
+    Leave,
+}
+
+struct ServiceItem {
+    label: String,
+    price: u32,
+    action: ServiceAction,
+    enabled: bool,
+}
+
+/// The service the player is standing in, and whether its panel is open.
+#[derive(Resource, Debug, Default)]
+pub struct ServicePanel {
+    pub nearby: Option<Entity>,
+    pub open: bool,
+    /// The nearby service is outside its opening hours.
+    pub closed: bool,
+}
+
+#[derive(Component)]
+struct ServicePanelRoot;
+
+#[derive(Component)]
+struct ServiceButton(usize);
+
+pub struct ShopPlugin;
+
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        for kind in ServiceKind::ALL {
+            app.register_prefab(service_prefab(kind));
+        }
+        app.init_resource::<ServiceCatalog>()
+            .init_resource::<Garage>()
+            .init_resource::<ServicePanel>()
+            .add_systems(OnEnter(InSession), spawn_service_locations)
+            .add_systems(
+                Update,
+                (
+                    track_nearby_service,
+                    toggle_service_panel,
+                    use_service.run_if(keyboard_free),
+                    render_service_panel,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+/// Each kind of shop is a prefab named after its label.
+fn service_prefab(kind: ServiceKind) -> Prefab {
+    let mut pad = PrefabVisual::new(
+        PrefabShape::Cylinder {
+            radius: 3.0,
+            height: 0.05,
+        },
+        kind.color().with_alpha(0.6),
+    );
+    pad.alpha_mode = AlphaMode::Blend;
+    pad.unlit = true;
+    Prefab::new(kind.label())
+        .collider(PrefabShape::Cylinder {
+            radius: 3.0,
+            height: 2.0,
+        })
+        .visual(pad)
+        .with(move |location| {
+            location.insert((
+                ServiceLocation {
+                    kind,
+                    name: kind.label(),
+                },
+                Blip::new(BlipKind::Shop),
+            ));
+        })
+}
+
+fn spawn_service_locations(mut prefabs: Prefabs) {
+    let locations = [
+        (
+            ServiceKind::WeaponStore,
+            "Lead Pipe Arms",
+            Vec3::new(30.0, 0.0, -20.0),
+        ),
+        (
+            ServiceKind::PaintShop,
+            "Spray Shop",
+            Vec3::new(-36.0, 0.0, -28.0),
+        ),
+        (
+            ServiceKind::Garage,
+            "Lockup Garage",
+            Vec3::new(-30.0, 0.0, 34.0),
+        ),
+        (
+            ServiceKind::Hospital,
+            "St. Ferris Hospital",
+            Vec3::new(40.0, 0.0, 30.0),
+        ),
+    ];
+    for (kind, name, position) in locations {
+        if let Err(err) = spawn_service_location(&mut prefabs, kind, name, position) {
+            error!("{err}");
+        }
+    }
+}
+
+/// A shop entrance pad.
+pub fn spawn_service_location(
+    prefabs: &mut Prefabs,
+    kind: ServiceKind,
+    name: &'static str,
+    position: Vec3,
+) -> anyhow::Result<Entity> {
+    let mut location = prefabs.spawn(
+        kind.label(),
+        Overrides::at(Transform::from_translation(position)),
+    )?;
+    location.insert((ServiceLocation { kind, name }, Name::new(name)));
+    Ok(location.id())
+}
+
+fn track_nearby_service(
+    clock: Res<WorldClock>,
+    mut panel: ResMut<ServicePanel>,
+    player: Query<(Entity, Option<&Driving>), With<Player>>,
+    locations: Query<(Entity, &TriggerVolume, &ServiceLocation)>,
+) {
+    let Ok((player, driving)) = player.single() else {
+        return;
+    };
+    let vehicle = driving.map(|driving| driving.0);
+    let nearby = locations
+        .iter()
+        .find(|(_, volume, _)| {
+            volume
+                .occupants
+                .iter()
+                .any(|&occupant| occupant == player || Some(occupant) == vehicle)
+        })
+        .map(|(entity, _, location)| (entity, location.kind));
+    let closed = nearby
+        .and_then(|(_, kind)| kind.hours())
+        .is_some_and(|(open, close)| !clock.is_between(open, close));
+    let nearby = nearby.map(|(entity, _)| entity);
+    if panel.nearby != nearby || panel.closed != closed {
+        panel.nearby = nearby;
+        panel.closed = closed;
+        panel.open = false;
+    }
+}
+
+fn toggle_service_panel(input: Res<PlayerInput>, mut panel: ResMut<ServicePanel>) {
+    if input.interact && panel.nearby.is_some() && !panel.closed {
+        panel.open = !panel.open;
+    }
+}
+
+fn service_items(
+    kind: ServiceKind,
+    catalog: &ServiceCatalog,
+    garage: &Garage,
+    health: Option<&Health>,
+    inventory: Option<&WeaponInventory>,
+    vehicle: Option<&Vehicle>,
+) -> Vec<ServiceItem> {
+    let item = |label: String, price, action, enabled| ServiceItem {
+        label,
+        price,
+        action,
+        enabled,
+    };
+    let mut items = match kind {
+        ServiceKind::WeaponStore => {
+            let mut items: Vec<_> = catalog
+                .weapons
+                .iter()
+                .enumerate()
+                .map(|(index, (weapon, price))| {
+                    let owned = inventory
+                        .is_some_and(|inv| inv.weapons.iter().any(|w| w.name == weapon.name));
+                    let label = if owned {
+                        format!("{} (owned, buys ammo)", weapon.name)
+                    } else {
+                        weapon.name.to_string()
+                    };
+                    item(label, *price, ServiceAction::BuyWeapon(index), true)
+                })
+                .collect();
+            let has_weapon = inventory.is_some_and(|inv| inv.equipped().is_some());
+            items.push(item(
+                format!("{} rounds for equipped weapon", catalog.ammo_rounds),
+                catalog.ammo_price,
+                ServiceAction::BuyAmmo,
+                has_weapon,
+            ));
+            items
+        }
+        ServiceKind::PaintShop => vec![item(
+            match vehicle {
+                Some(vehicle) => format!("Respray {} and lose the cops", vehicle.name),
+                None => "Respray (bring a vehicle)".to_string(),
+            },
+            catalog.respray_price,
+            ServiceAction::Respray,
+            vehicle.is_some(),
+        )],
+        ServiceKind::Garage => {
+            let mut items = vec![item(
+                format!(
+                    "Store vehicle ({}/{})",
+                    garage.stored.len(),
+                    garage.capacity
+                ),
+                0,
+                ServiceAction::StoreVehicle,
+                vehicle.is_some() && garage.stored.len() < garage.capacity,
+            )];
+            items.extend(garage.stored.iter().enumerate().map(|(index, stored)| {
+                item(
+                    format!("Take out {}", stored.model),
+                    0,
+                    ServiceAction::RetrieveVehicle(index),
+                    vehicle.is_none(),
+                )
+            }));
+            items
+        }
+        ServiceKind::Hospital => vec![item(
+            "Patch up to full health".to_string(),
+            catalog.hospital_fee,
+            ServiceAction::Heal,
+            health.is_some_and(|health| health.current < health.max),
+        )],
+    };
+    items.push(item("Leave".to_string(), 0, ServiceAction::Leave, true));
+    items
+}
+
+type Customer<'a> = (&'a mut Health, &'a mut WeaponInventory, Option<&'a Driving>);
+
+/// Number keys or a clicked button, whichever picked an item this frame.
+#[derive(SystemParam)]
+struct ServiceSelection<'w, 's> {
+    keyboard: Res<'w, ButtonInput<KeyCode>>,
+    buttons: Query<'w, 's, (&'static Interaction, &'static ServiceButton), Changed<Interaction>>,
+}
+
+impl ServiceSelection<'_, '_> {
+    fn pressed(&self) -> Option<usize> {
+        NUMBER_KEYS
+            .iter()
+            .position(|key| self.keyboard.just_pressed(*key))
+            .or_else(|| {
+                self.buttons
+                    .iter()
+                    .find(|(interaction, _)| **interaction == Interaction::Pressed)
+                    .map(|(_, button)| button.0)
+            })
+    }
+}
+
+/// Everything a purchase can pay for or change.
+#[derive(SystemParam)]
+struct Till<'w, 's> {
+    prefabs: Prefabs<'w, 's>,
+    wallet: Wallet<'w>,
+    garage: ResMut<'w, Garage>,
+    wanted: ResMut<'w, WantedLevel>,
+    toasts: MessageWriter<'w, Toast>,
+}
+
+/// What the services have on offer.
+#[derive(SystemParam)]
+struct Stock<'w> {
+    catalog: Res<'w, ServiceCatalog>,
+    garage: Res<'w, Garage>,
+}
+
+fn use_service(
+    selection: ServiceSelection,
+    mut panel: ResMut<ServicePanel>,
+    catalog: Res<ServiceCatalog>,
+    mut till: Till,
+    mut player: Query<(Entity, Customer), With<Player>>,
+    mut vehicles: Query<(&Vehicle, &mut VehiclePaint)>,
+    locations: Query<(&ServiceLocation, &Transform)>,
+) {
+    let (Some(location), true) = (panel.nearby, panel.open) else {
+        return;
+    };
+    let Ok((service, location_tx)) = locations.get(location) else {
+        return;
+    };
+    let Ok((player, (mut health, mut inventory, driving))) = player.single_mut() else {
+        return;
+    };
+    let Some(index) = selection.pressed() else {
+        return;
+    };
+
+    let vehicle_entity = driving.map(|driving| driving.0);
+    let vehicle = vehicle_entity.and_then(|entity| vehicles.get(entity).ok().map(|(v, _)| v));
+    let items = service_items(
+        service.kind,
+        &catalog,
+        &till.garage,
+        Some(&health),
+        Some(&inventory),
+        vehicle,
+    );
+    let Some(item) = items.get(index).filter(|item| item.enabled) else {
+        return;
+    };
+    if item.price > 0
+        && let Err(err) = till.wallet.spend(
+            TransactionKind::Service,
+            item.price,
+            format!("{}: {}", service.name, item.label),
+        )
+    {
+        till.toasts
+            .write(Toast::new(format!("Can't buy {}: {err}", item.label)));
+        return;
+    }
+
+    match item.action {
+        ServiceAction::BuyWeapon(index) => {
+            let weapon = &catalog.weapons[index].0;
+            match inventory.weapons.iter_mut().find(|w| w.name == weapon.name) {
+                Some(owned) => owned.reserve += weapon.clip_size * 2,
+                None => inventory.weapons.push(weapon.clone()),
+            }
+        }
+        ServiceAction::BuyAmmo => {
+            let equipped = inventory.equipped;
+            if let Some(weapon) = inventory.weapons.get_mut(equipped) {
+                weapon.reserve += catalog.ammo_rounds;
+            }
+        }
+        ServiceAction::Respray => {
+            if let Some(mut paint) = vehicle_entity
+                .and_then(|e| vehicles.get_mut(e).ok())
+                .map(|(_, p)| p)
+            {
+                let current = catalog.paints.iter().position(|c| *c == paint.0);
+                let next = current.map_or(0, |i| (i + 1) % catalog.paints.len());
+                paint.0 = catalog.paints[next];
+            }
+            till.wanted.clear();
+        }
+        ServiceAction::StoreVehicle => {
+            if let Some(entity) = vehicle_entity
+                && let Ok((vehicle, paint)) = vehicles.get(entity)
+            {
+                till.garage.stored.push(StoredVehicle {
+                    model: vehicle.name,
+                    paint: paint.0,
+                });
+                till.prefabs.commands.entity(entity).despawn();
+                till.prefabs.commands.entity(player).remove::<Driving>();
+            }
+        }
+        ServiceAction::RetrieveVehicle(index) => {
+            let stored = till.garage.stored.remove(index);
+            if let Some(model) = VehicleModel::find(stored.model) {
+                let spot = location_tx.translation + Vec3::new(0.0, model.size.y * 0.5 + 0.1, 6.0);
+                if let Err(err) = spawn_vehicle(
+                    &mut till.prefabs,
+                    model,
+                    stored.paint,
+                    Transform::from_translation(spot),
+                ) {
+                    error!("{err}");
+                }
+            }
+        }
+        ServiceAction::Heal => health.current = health.max,
+        ServiceAction::Leave => panel.open = false,
+    }
+    // Contents changed; rebuild the panel.
+    panel.set_changed();
+}
+
+fn render_service_panel(
+    mut commands: Commands,
+    panel: Res<ServicePanel>,
+    stock: Stock,
+    player: Query<(&Health, &WeaponInventory, Option<&Driving>), With<Player>>,
+    vehicles: Query<&Vehicle>,
+    locations: Query<&ServiceLocation>,
+    roots: Query<Entity, With<ServicePanelRoot>>,
+) {
+    if !panel.is_changed() {
+        return;
+    }
+    for root in &roots {
+        commands.entity(root).despawn();
+    }
+    let Some(location) = panel.nearby.and_then(|e| locations.get(e).ok()) else {
+        return;
+    };
+    let Ok((health, inventory, driving)) = player.single() else {
+        return;
+    };
+    if !panel.open {
+        let hint = match location.kind.hours() {
+            Some((open, _)) if panel.closed => {
+                format!(
+                    "{} is closed, opens at {}",
+                    location.name,
+                    format_hour(open)
+                )
+            }
+            _ => format!("E: enter {}", location.name),
+        };
+        commands.spawn((
+            hint_node(),
+            ServicePanelRoot,
+            DespawnOnExit(InSession),
+            children![panel_text(hint, 16.0, Color::WHITE)],
+        ));
+        return;
+    }
+
+    let vehicle = driving.and_then(|driving| vehicles.get(driving.0).ok());
+    let items = service_items(
+        location.kind,
+        &stock.catalog,
+        &stock.garage,
+        Some(health),
+        Some(inventory),
+        vehicle,
+    );
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(24.0),
+                top: Val::Percent(25.0),
+                width: Val::Px(340.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(14.0)),
+                border: UiRect::left(Val::Px(4.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.02, 0.02, 0.04, 0.88)),
+            BorderColor::all(location.kind.color()),
+            ServicePanelRoot,
+            DespawnOnExit(InSession),
+        ))
+        .with_children(|parent| {
+            parent.spawn(panel_text(location.name, 24.0, location.kind.color()));
+            for (index, item) in items.iter().enumerate() {
+                let price = if item.price > 0 {
+                    format!("  ${}", item.price)
+                } else {
+                    String::new()
+                };
+                let color = if item.enabled {
+                    Color::WHITE
+                } else {
+                    Color::srgb(0.4, 0.4, 0.45)
+                };
+                parent.spawn((
+                    Button,
+                    ServiceButton(index),
+                    Node {
+                        margin: UiRect::top(Val::Px(6.0)),
+                        ..default()
+                    },
+                    children![panel_text(
+                        format!("{}. {}{price}", index + 1, item.label),
+                        16.0,
+                        color
+                    )],
+                ));
+            }
+            parent.spawn(panel_text(
+                "Number keys or click to buy, E to close",
+                12.0,
+                Color::srgb(0.7, 0.7, 0.75),
+            ));
+        });
+}
+
+fn hint_node() -> Node {
+    Node {
+        position_type: PositionType::Absolute,
+        bottom: Val::Px(48.0),
+        width: Val::Percent(100.0),
+        justify_content: JustifyContent::Center,
+        ..default()
+    }
+}
+
+fn panel_text(text: impl Into<String>, size: f32, color: Color) -> impl Bundle {
+    (
+        Text::new(text),
+        TextFont {
+            font_size: size,
+            ..default()
+        },
+        TextColor(color),
+    )
+}
@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::game::combat::Health;
+use crate::game::core::GameState;
+use crate::game::vehicle::Vehicle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stat {
+    MaxHealth,
+    WalkSpeed,
+    SprintSpeed,
+    /// On foot this is how quickly we reach walking speed; for vehicles, how
+    /// quickly the engine pulls toward the throttle target.
+    Acceleration,
+    /// Vehicle top speed.
+    TopSpeed,
+    /// Multiplier on weapon spread; lower is tighter.
+    WeaponSpread,
+    /// Multiplier on steering and acceleration of a driven vehicle.
+    VehicleHandling,
+}
+
+impl Stat {
+    fn default_base(self) -> f32 {
+        match self {
+            Stat::WeaponSpread | Stat::VehicleHandling => 1.0,
+            _ => 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModifierSource {
+    Skill(&'static str),
+    Pickup(&'static str),
+    Drug(&'static str),
+    Upgrade(&'static str),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatModifier {
+    pub stat: Stat,
+    pub source: ModifierSource,
+    pub add: f32,
+    pub mul: f32,
+    /// Seconds left before the modifier expires; `None` lasts until removed.
+    pub remaining: Option<f32>,
+}
+
+impl StatModifier {
+    pub fn add(stat: Stat, source: ModifierSource, add: f32) -> Self {
+        Self {
+            stat,
+            source,
+            add,
+            mul: 1.0,
+            remaining: None,
+        }
+    }
+
+    pub fn mul(stat: Stat, source: ModifierSource, mul: f32) -> Self {
+        Self {
+            stat,
+            source,
+            add: 0.0,
+            mul,
+            remaining: None,
+        }
+    }
+
+    pub fn for_secs(mut self, seconds: f32) -> Self {
+        self.remaining = Some(seconds);
+        self
+    }
+}
+
+/// Base values with modifiers layered on top, shared by the player, NPCs and
+/// vehicles. Gameplay reads [`Stats::get`]; modifiers never touch the base.
+/// Effective values are cached and rebuilt whenever the inputs change.
+#[derive(Component, Debug, Clone, Default)]
+pub struct Stats {
+    base: HashMap<Stat, f32>,
+    modifiers: Vec<StatModifier>,
+    effective: HashMap<Stat, f32>,
+}
+
+impl Stats {
+    pub fn with_base(mut self, stat: Stat, value: f32) -> Self {
+        self.set_base(stat, value);
+        self
+    }
+
+    pub fn set_base(&mut self, stat: Stat, value: f32) {
+        self.base.insert(stat, value);
+        self.recompute();
+    }
+
+    pub fn base(&self, stat: Stat) -> f32 {
+        self.base.get(&stat).copied().unwrap_or(stat.default_base())
+    }
+
+    /// `(base + adds) * muls`.
+    pub fn get(&self, stat: Stat) -> f32 {
+        self.effective
+            .get(&stat)
+            .copied()
+            .unwrap_or_else(|| self.base(stat))
+    }
+
+    pub fn add_modifier(&mut self, modifier: StatModifier) {
+        self.modifiers.push(modifier);
+        self.recompute();
+    }
+
+    /// Replaces any modifier from the same source on the same stat, so
+    /// re-collecting a buff refreshes it rather than stacking.
+    pub fn replace_modifier(&mut self, modifier: StatModifier) {
+        self.modifiers
+            .retain(|m| m.source != modifier.source || m.stat != modifier.stat);
+        self.add_modifier(modifier);
+    }
+
+    pub fn remove_where(&mut self, remove: impl Fn(ModifierSource) -> bool) {
+        let before = self.modifiers.len();
+        self.modifiers.retain(|m| !remove(m.source));
+        if self.modifiers.len() != before {
+            self.recompute();
+        }
+    }
+
+    /// Counts down timed modifiers. Returns whether any expired.
+    fn tick(&mut self, delta: f32) -> bool {
+        let mut expired = false;
+        for modifier in &mut self.modifiers {
+            if let Some(remaining) = &mut modifier.remaining {
+                *remaining -= delta;
+                expired |= *remaining <= 0.0;
+            }
+        }
+        if expired {
+            self.modifiers
+                .retain(|m| m.remaining.is_none_or(|remaining| remaining > 0.0));
+            self.recompute();
+        }
+        expired
+    }
+
+    fn recompute(&mut self) {
+        self.effective.clear();
+        for (stat, base) in &self.base {
+            self.effective.insert(*stat, *base);
+        }
+        let mut muls: HashMap<Stat, f32> = HashMap::new();
+        for modifier in &self.modifiers {
+            *self
+                .effective
+                .entry(modifier.stat)
+                .or_insert(modifier.stat.default_base()) += modifier.add;
+            *muls.entry(modifier.stat).or_insert(1.0) *= modifier.mul;
+        }
+        for (stat, mul) in muls {
+            if let Some(value) = self.effective.get_mut(&stat) {
+                *value *= mul;
+            }
+        }
+    }
+}
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                init_vehicle_stats,
+                tick_modifiers.run_if(in_state(GameState::InGame)),
+                sync_max_health,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Seeds stats for vehicles spawned without them from their spec.
+fn init_vehicle_stats(mut commands: Commands, vehicles: Query<(Entity, &Vehicle), Without<Stats>>) {
+    for (entity, vehicle) in &vehicles {
+        commands.entity(entity).insert(
+            Stats::default()
+                .with_base(Stat::TopSpeed, vehicle.max_speed)
+                .with_base(Stat::Acceleration, vehicle.acceleration),
+        );
+    }
+}
+
+fn tick_modifiers(time: Res<Time>, mut query: Query<&mut Stats>) {
+    let delta = time.delta_secs();
+    for mut stats in &mut query {
+        // Counting down alone isn't a change worth reacting to; expiry is.
+        if stats.bypass_change_detection().tick(delta) {
+            stats.set_changed();
+        }
+    }
+}
+
+/// Keeps `Health::max` in step with the `MaxHealth` stat. Raising the cap
+/// heals by the same amount; lowering it only clamps.
+fn sync_max_health(mut query: Query<(&Stats, &mut Health), Changed<Stats>>) {
+    for (stats, mut health) in &mut query {
+        let max = stats.get(Stat::MaxHealth);
+        if max <= 0.0 || max == health.max {
+            continue;
+        }
+        let gained = (max - health.max).max(0.0);
+        health.max = max;
+        health.current = (health.current + gained).min(max);
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+use bevy::tasks::futures::check_ready;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+
+use crate::game::camera::TopDownCamera;
+use crate::game::core::InSession;
+use crate::game::destructible::Destroyed;
+use crate::game::player::Player;
+use crate::game::prefab::Prefabs;
+use crate::game::world::{ChunkProp, WorldConfig, chunk_props, spawn_chunk_prop};
+
+#[derive(Resource, Debug, Clone)]
+pub struct StreamingConfig {
+    /// Edge length of a square chunk in metres.
+    pub chunk_size: f32,
+    /// Chunks closer than this to the camera are loaded...
+    pub load_distance: f32,
+    /// ...and stay loaded until they're further than this.
+    pub unload_distance: f32,
+    /// Generation tasks started per frame.
+    pub max_requests_per_frame: usize,
+}
+
+impl Default for StreamingConfig {
+    fn default() -> Self {
+        Self {
+            chunk_size: 64.0,
+            load_distance: 80.0,
+            unload_distance: 120.0,
+            max_requests_per_frame: 4,
+        }
+    }
+}
+
+impl StreamingConfig {
+    pub fn chunk_at(&self, position: Vec3) -> IVec2 {
+        (position.xz() / self.chunk_size).floor().as_ivec2()
+    }
+
+    /// Ground distance from `position` to the nearest point of the chunk.
+    pub fn distance_to(&self, coord: IVec2, position: Vec3) -> f32 {
+        let min = coord.as_vec2() * self.chunk_size;
+        let max = min + Vec2::splat(self.chunk_size);
+        position.xz().clamp(min, max).distance(position.xz())
+    }
+}
+
+/// State a chunk keeps while it's unloaded.
+#[derive(Debug, Clone, Default)]
+pub struct ChunkDirty {
+    /// [`ChunkProp::id`]s that were destroyed and shouldn't come back.
+    pub destroyed: HashSet<u32>,
+}
+
+/// Which chunks are loaded or on their way, plus everything that has to
+/// outlive them.
+#[derive(Resource, Default)]
+pub struct WorldChunks {
+    loaded: HashMap<IVec2, Vec<(Entity, u32)>>,
+    pending: HashMap<IVec2, Task<Vec<ChunkProp>>>,
+    pub dirty: HashMap<IVec2, ChunkDirty>,
+}
+
+impl WorldChunks {
+    pub fn is_loaded(&self, coord: IVec2) -> bool {
+        self.loaded.contains_key(&coord)
+    }
+
+    pub fn is_pending(&self, coord: IVec2) -> bool {
+        self.pending.contains_key(&coord)
+    }
+
+    pub fn loaded(&self) -> impl Iterator<Item = IVec2> + '_ {
+        self.loaded.keys().copied()
+    }
+
+    /// The chunk and prop id an entity was streamed in as.
+    pub fn prop_of(&self, entity: Entity) -> Option<(IVec2, u32)> {
+        self.loaded.iter().find_map(|(coord, props)| {
+            props
+                .iter()
+                .find(|(prop, _)| *prop == entity)
+                .map(|(_, id)| (*coord, *id))
+        })
+    }
+}
+
+pub struct StreamingPlugin;
+
+impl Plugin for StreamingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StreamingConfig>()
+            .init_resource::<WorldChunks>()
+            .add_systems(OnExit(InSession), reset_chunks)
+            .add_systems(
+                Update,
+                (record_destroyed_props, stream_chunks, finish_chunks)
+                    .chain()
+                    .run_if(in_state(InSession)),
+            );
+    }
+}
+
+/// Streams around the camera, or the player when there's no camera (e.g.
+/// headless).
+fn stream_focus(
+    camera: &Query<&GlobalTransform, With<TopDownCamera>>,
+    player: &Query<&GlobalTransform, With<Player>>,
+) -> Option<Vec3> {
+    camera
+        .single()
+        .or_else(|_| player.single())
+        .ok()
+        .map(GlobalTransform::translation)
+}
+
+fn stream_chunks(
+    mut commands: Commands,
+    config: Res<StreamingConfig>,
+    world_config: Res<WorldConfig>,
+    mut chunks: ResMut<WorldChunks>,
+    camera: Query<&GlobalTransform, With<TopDownCamera>>,
+    player: Query<&GlobalTransform, With<Player>>,
+) {
+    let Some(focus) = stream_focus(&camera, &player) else {
+        return;
+    };
+
+    // Unload first so a chunk that's drifted out of range frees its
+    // entities before anything new is spawned.
+    let far: Vec<IVec2> = chunks
+        .loaded
+        .keys()
+        .copied()
+        .filter(|coord| config.distance_to(*coord, focus) > config.unload_distance)
+        .collect();
+    for coord in far {
+        for (entity, _) in chunks.loaded.remove(&coord).unwrap_or_default() {
+            commands.entity(entity).try_despawn();
+        }
+    }
+    // Dropping a task cancels it.
+    chunks
+        .pending
+        .retain(|coord, _| config.distance_to(*coord, focus) <= config.unload_distance);
+
+    let bounds = world_config.ground_size * 0.5;
+    let first = config.chunk_at(Vec3::new(-bounds.x, 0.0, -bounds.y));
+    let last = config.chunk_at(Vec3::new(bounds.x, 0.0, bounds.y) - 0.001);
+    let reach = (config.load_distance / config.chunk_size).ceil() as i32;
+    let center = config.chunk_at(focus);
+    let mut wanted: Vec<(IVec2, f32)> = Vec::new();
+    for x in (center.x - reach).max(first.x)..=(center.x + reach).min(last.x) {
+        for z in (center.y - reach).max(first.y)..=(center.y + reach).min(last.y) {
+            let coord = IVec2::new(x, z);
+            let distance = config.distance_to(coord, focus);
+            if distance <= config.load_distance
+                && !chunks.is_loaded(coord)
+                && !chunks.is_pending(coord)
+            {
+                wanted.push((coord, distance));
+            }
+        }
+    }
+    wanted.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    let pool = AsyncComputeTaskPool::get();
+    for (coord, _) in wanted.into_iter().take(config.max_requests_per_frame) {
+        let chunk_size = config.chunk_size;
+        let task = pool.spawn(async move { chunk_props(coord, chunk_size) });
+        chunks.pending.insert(coord, task);
+    }
+}
+
+fn finish_chunks(mut prefabs: Prefabs, mut chunks: ResMut<WorldChunks>) {
+    let chunks = chunks.as_mut();
+    let mut finished = Vec::new();
+    for (coord, task) in &mut chunks.pending {
+        if let Some(props) = check_ready(task) {
+            finished.push((*coord, props));
+        }
+    }
+    for (coord, props) in finished {
+        chunks.pending.remove(&coord);
+        let destroyed = chunks.dirty.get(&coord).map(|dirty| &dirty.destroyed);
+        let spawned = props
+            .iter()
+            .filter(|prop| !destroyed.is_some_and(|destroyed| destroyed.contains(&prop.id)))
+            .filter_map(|prop| match spawn_chunk_prop(&mut prefabs, prop) {
+                Ok(entity) => Some((entity, prop.id)),
+                Err(err) => {
+                    error!("{err}");
+                    None
+                }
+            })
+            .collect();
+        chunks.loaded.insert(coord, spawned);
+    }
+}
+
+fn record_destroyed_props(
+    mut destroyed: MessageReader<Destroyed>,
+    mut chunks: ResMut<WorldChunks>,
+) {
+    for event in destroyed.read() {
+        let Some((coord, id)) = chunks.prop_of(event.entity) else {
+            continue;
+        };
+        chunks.dirty.entry(coord).or_default().destroyed.insert(id);
+        if let Some(props) = chunks.loaded.get_mut(&coord) {
+            props.retain(|(entity, _)| *entity != event.entity);
+        }
+    }
+}
+
+/// Session entities are already gone; forget about them. A new session
+/// starts with an intact city.
+fn reset_chunks(mut chunks: ResMut<WorldChunks>) {
+    *chunks = WorldChunks::default();
+}
@@ -0,0 +1,295 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use bevy::prelude::*;
+
+use crate::game::console::{ConsoleAppExt, ConsoleCommand, arg};
+use crate::game::core::{GameState, InSession};
+use crate::game::weather::Weather;
+
+/// In-game time of day. Advances with virtual time, so it follows the time
+/// scale and stops while paused.
+#[derive(Resource, Debug, Clone)]
+pub struct WorldClock {
+    /// Hours since midnight, `0.0..24.0`.
+    pub hour: f32,
+    pub day: u32,
+    /// Real seconds per in-game hour at a time scale of 1.
+    pub seconds_per_hour: f32,
+}
+
+impl Default for WorldClock {
+    fn default() -> Self {
+        Self {
+            hour: 9.0,
+            day: 1,
+            seconds_per_hour: 60.0,
+        }
+    }
+}
+
+impl WorldClock {
+    pub fn advance(&mut self, seconds: f32) {
+        self.hour += seconds / self.seconds_per_hour.max(f32::EPSILON);
+        while self.hour >= 24.0 {
+            self.hour -= 24.0;
+            self.day += 1;
+        }
+    }
+
+    pub fn set_hour(&mut self, hour: f32) {
+        self.hour = hour.rem_euclid(24.0);
+    }
+
+    /// Whether the clock is in `[start, end)`, wrapping past midnight when
+    /// `end < start`.
+    pub fn is_between(&self, start: f32, end: f32) -> bool {
+        if start <= end {
+            (start..end).contains(&self.hour)
+        } else {
+            self.hour >= start || self.hour < end
+        }
+    }
+
+    pub fn is_night(&self) -> bool {
+        self.is_between(DUSK, DAWN)
+    }
+
+    /// Sun angle above the horizon: zero at 06:00, straight up at noon,
+    /// negative overnight.
+    pub fn sun_elevation(&self) -> f32 {
+        (self.hour - 6.0) / 12.0 * PI
+    }
+
+    pub fn label(&self) -> String {
+        format_hour(self.hour)
+    }
+}
+
+pub const DAWN: f32 = 6.0;
+pub const DUSK: f32 = 20.0;
+
+/// `13.5` -> `"13:30"`.
+pub fn format_hour(hour: f32) -> String {
+    let minutes = (hour.rem_euclid(24.0) * 60.0).floor() as u32;
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AmbientKey {
+    pub hour: f32,
+    pub color: Color,
+    pub brightness: f32,
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct DaylightConfig {
+    pub sun_illuminance: f32,
+    pub moon_illuminance: f32,
+    /// Sun path rotation around the vertical axis.
+    pub azimuth: f32,
+    /// Ambient keyframes in hour order; the last wraps to the first.
+    pub ambient: Vec<AmbientKey>,
+    /// When street and neon lights switch on and off.
+    pub lights_on: f32,
+    pub lights_off: f32,
+}
+
+impl Default for DaylightConfig {
+    fn default() -> Self {
+        let key = |hour, color, brightness| AmbientKey {
+            hour,
+            color,
+            brightness,
+        };
+        let night = Color::srgb(0.12, 0.14, 0.28);
+        let day = Color::srgb(0.45, 0.48, 0.55);
+        Self {
+            sun_illuminance: 50_000.0,
+            moon_illuminance: 600.0,
+            azimuth: 0.6,
+            ambient: vec![
+                key(5.0, night, 80.0),
+                key(7.0, Color::srgb(0.85, 0.6, 0.5), 180.0),
+                key(10.0, day, 300.0),
+                key(17.0, day, 300.0),
+                key(19.0, Color::srgb(0.85, 0.45, 0.35), 180.0),
+                key(21.0, night, 80.0),
+            ],
+            lights_on: 19.5,
+            lights_off: 6.5,
+        }
+    }
+}
+
+impl DaylightConfig {
+    pub fn ambient_at(&self, hour: f32) -> (Color, f32) {
+        let Some(first) = self.ambient.first() else {
+            return (Color::WHITE, 0.0);
+        };
+        let last = self.ambient[self.ambient.len() - 1];
+        // Pair each key with the next, closing the loop through midnight.
+        let (from, to) = self
+            .ambient
+            .windows(2)
+            .map(|pair| (pair[0], pair[1]))
+            .find(|(from, to)| (from.hour..to.hour).contains(&hour))
+            .unwrap_or((last, *first));
+        let span = (to.hour - from.hour).rem_euclid(24.0).max(f32::EPSILON);
+        let t = ((hour - from.hour).rem_euclid(24.0) / span).clamp(0.0, 1.0);
+        (
+            from.color.mix(&to.color, t),
+            from.brightness.lerp(to.brightness, t),
+        )
+    }
+}
+
+#[derive(Component)]
+pub struct Sun;
+
+#[derive(Component)]
+pub struct Moon;
+
+/// A light that's only on after dark, at `intensity` lumens.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct NightLight {
+    pub intensity: f32,
+}
+
+pub struct TimeOfDayPlugin;
+
+impl Plugin for TimeOfDayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldClock>()
+            .init_resource::<DaylightConfig>()
+            .add_console_command(ConsoleCommand::new(
+                "time",
+                "[hour]",
+                "Show or set the time of day.",
+                time_command,
+            ))
+            .add_systems(OnEnter(InSession), spawn_sky_lights)
+            .add_systems(
+                Update,
+                (
+                    tick_clock.run_if(in_state(GameState::InGame)),
+                    (move_sky_lights, update_ambient, switch_night_lights),
+                )
+                    .chain()
+                    .run_if(in_state(InSession)),
+            );
+    }
+}
+
+fn time_command(world: &mut World, args: &[&str]) -> anyhow::Result<String> {
+    let mut clock = world.resource_mut::<WorldClock>();
+    if !args.is_empty() {
+        clock.set_hour(arg(args, 0, "hour")?);
+    }
+    Ok(format!("day {} {}", clock.day, clock.label()))
+}
+
+fn spawn_sky_lights(mut commands: Commands, config: Res<DaylightConfig>) {
+    commands.spawn((
+        Sun,
+        DirectionalLight {
+            illuminance: config.sun_illuminance,
+            shadows_enabled: true,
+            ..default()
+        },
+        DespawnOnExit(InSession),
+        Name::new("Sun"),
+    ));
+    commands.spawn((
+        Moon,
+        DirectionalLight {
+            illuminance: 0.0,
+            color: Color::srgb(0.6, 0.7, 1.0),
+            shadows_enabled: false,
+            ..default()
+        },
+        DespawnOnExit(InSession),
+        Name::new("Moon"),
+    ));
+}
+
+fn tick_clock(time: Res<Time>, mut clock: ResMut<WorldClock>) {
+    clock.advance(time.delta_secs());
+}
+
+type SunQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static mut Transform, &'static mut DirectionalLight),
+    (With<Sun>, Without<Moon>),
+>;
+type MoonQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static mut Transform, &'static mut DirectionalLight),
+    (With<Moon>, Without<Sun>),
+>;
+
+fn move_sky_lights(
+    clock: Res<WorldClock>,
+    config: Res<DaylightConfig>,
+    weather: Res<Weather>,
+    mut sun: SunQuery,
+    mut moon: MoonQuery,
+) {
+    let elevation = clock.sun_elevation();
+    let daylight = weather.effects.daylight;
+    let aim = |elevation: f32| {
+        // Keep the light above the horizon's far side rather than letting it
+        // shine up through the ground.
+        let elevation = elevation.rem_euclid(2.0 * PI).min(PI);
+        Quat::from_rotation_y(config.azimuth) * Quat::from_rotation_x(-elevation.max(0.05))
+    };
+    if let Ok((mut transform, mut light)) = sun.single_mut() {
+        transform.rotation = aim(elevation);
+        light.illuminance = config.sun_illuminance * daylight * elevation.sin().max(0.0);
+        light.shadows_enabled = elevation.sin() > 0.05 && daylight > 0.5;
+    }
+    if let Ok((mut transform, mut light)) = moon.single_mut() {
+        let elevation = elevation + PI;
+        transform.rotation = aim(elevation);
+        // Brightest at midnight, gone by the time the sun is up.
+        let height = (elevation.rem_euclid(2.0 * PI) - FRAC_PI_2).abs() / FRAC_PI_2;
+        light.illuminance = config.moon_illuminance * daylight * (1.0 - height).max(0.0);
+    }
+}
+
+fn update_ambient(
+    clock: Res<WorldClock>,
+    config: Res<DaylightConfig>,
+    weather: Res<Weather>,
+    ambient: Option<ResMut<AmbientLight>>,
+) {
+    let Some(mut ambient) = ambient else {
+        return;
+    };
+    let (color, brightness) = config.ambient_at(clock.hour);
+    ambient.color = color;
+    // Overcast skies dim the ambient less than direct light.
+    ambient.brightness = brightness * (0.5 + 0.5 * weather.effects.daylight);
+}
+
+fn switch_night_lights(
+    clock: Res<WorldClock>,
+    config: Res<DaylightConfig>,
+    mut lights: Query<(&NightLight, Option<&mut PointLight>, Option<&mut SpotLight>)>,
+) {
+    let on = clock.is_between(config.lights_on, config.lights_off);
+    for (night, point, spot) in &mut lights {
+        let target = if on { night.intensity } else { 0.0 };
+        if let Some(mut point) = point
+            && point.intensity != target
+        {
+            point.intensity = target;
+        }
+        if let Some(mut spot) = spot
+            && spot.intensity != target
+        {
+            spot.intensity = target;
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::game::physics::GameLayer;
+use crate::game::player::Player;
+use crate::game::vehicle::Vehicle;
+
+/// Which entities a [`TriggerVolume`] reacts to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum TriggerFilter {
+    #[default]
+    Any,
+    Player,
+    Vehicle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TriggerMode {
+    /// Fires a single enter, then ignores new occupants.
+    Once,
+    #[default]
+    Repeat,
+}
+
+/// Sensor area that reports entities crossing its boundary. Pair with a
+/// [`Collider`] describing the shape of the zone.
+#[derive(Component, Debug, Clone)]
+#[require(
+    RigidBody::Static,
+    Sensor,
+    CollisionEventsEnabled,
+    CollisionLayers = GameLayer::Trigger.layers()
+)]
+pub struct TriggerVolume {
+    pub id: &'static str,
+    pub filter: TriggerFilter,
+    pub mode: TriggerMode,
+    pub fired: bool,
+    pub occupants: Vec<Entity>,
+}
+
+impl TriggerVolume {
+    pub fn new(id: &'static str) -> Self {
+        Self {
+            id,
+            filter: TriggerFilter::Any,
+            mode: TriggerMode::Repeat,
+            fired: false,
+            occupants: Vec::new(),
+        }
+    }
+
+    pub fn with_filter(mut self, filter: TriggerFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn once(mut self) -> Self {
+        self.mode = TriggerMode::Once;
+        self
+    }
+
+    pub fn is_occupied(&self) -> bool {
+        !self.occupants.is_empty()
+    }
+}
+
+#[derive(Message, Debug, Clone)]
+pub struct TriggerEnter {
+    pub trigger: Entity,
+    pub id: &'static str,
+    pub entity: Entity,
+}
+
+#[derive(Message, Debug, Clone)]
+pub struct TriggerExit {
+    pub trigger: Entity,
+    pub id: &'static str,
+    pub entity: Entity,
+}
+
+pub struct TriggerPlugin;
+
+impl Plugin for TriggerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<TriggerEnter>()
+            .add_message::<TriggerExit>()
+            .add_systems(Update, (detect_trigger_enter, detect_trigger_exit).chain());
+    }
+}
+
+/// Splits a collision pair into (trigger, other body).
+fn trigger_pair(
+    collider1: Entity,
+    collider2: Entity,
+    body1: Option<Entity>,
+    body2: Option<Entity>,
+    triggers: &Query<&mut TriggerVolume>,
+) -> Option<(Entity, Entity)> {
+    if triggers.contains(collider1) {
+        Some((collider1, body2.unwrap_or(collider2)))
+    } else if triggers.contains(collider2) {
+        Some((collider2, body1.unwrap_or(collider1)))
+    } else {
+        None
+    }
+}
+
+fn detect_trigger_enter(
+    mut collisions: MessageReader<CollisionStart>,
+    mut triggers: Query<&mut TriggerVolume>,
+    players: Query<(), With<Player>>,
+    vehicles: Query<(), With<Vehicle>>,
+    mut enter_events: MessageWriter<TriggerEnter>,
+) {
+    for event in collisions.read() {
+        let Some((trigger, entity)) = trigger_pair(
+            event.collider1,
+            event.collider2,
+            event.body1,
+            event.body2,
+            &triggers,
+        ) else {
+            continue;
+        };
+        let Ok(mut volume) = triggers.get_mut(trigger) else {
+            continue;
+        };
+
+        let accepted = match volume.filter {
+            TriggerFilter::Any => true,
+            TriggerFilter::Player => players.contains(entity),
+            TriggerFilter::Vehicle => vehicles.contains(entity),
+        };
+        if !accepted || volume.occupants.contains(&entity) {
+            continue;
+        }
+        if volume.mode == TriggerMode::Once && volume.fired {
+            continue;
+        }
+
+        volume.fired = true;
+        volume.occupants.push(entity);
+        enter_events.write(TriggerEnter {
+            trigger,
+            id: volume.id,
+            entity,
+        });
+    }
+}
+
+fn detect_trigger_exit(
+    mut collisions: MessageReader<CollisionEnd>,
+    mut triggers: Query<&mut TriggerVolume>,
+    mut exit_events: MessageWriter<TriggerExit>,
+) {
+    for event in collisions.read() {
+        let Some((trigger, entity)) = trigger_pair(
+            event.collider1,
+            event.collider2,
+            event.body1,
+            event.body2,
+            &triggers,
+        ) else {
+            continue;
+        };
+        let Ok(mut volume) = triggers.get_mut(trigger) else {
+            continue;
+        };
+
+        // Only entities that were let in get an exit, so filters stay symmetric.
+        let Some(index) = volume.occupants.iter().position(|e| *e == entity) else {
+            continue;
+        };
+        volume.occupants.swap_remove(index);
+        exit_events.write(TriggerExit {
+            trigger,
+            id: volume.id,
+            entity,
+        });
+    }
+}
@@ -1,25 +1,141 @@
 use bevy::prelude::*;
-use bevy::ui::{PositionType, UiRect, Val};
+use bevy::ui::{FlexDirection, PositionType, UiRect, Val};
 
-use crate::game::combat::Health;
-use crate::game::core::GameState;
+use crate::game::ai::{MAX_WANTED_STARS, WantedLevel};
+use crate::game::combat::{Armor, Health, WeaponInventory};
+use crate::game::core::{GameState, InSession};
+use crate::game::dialogue::{Conversation, Subtitle};
+use crate::game::mission::MissionLog;
+use crate::game::player::Player;
 use crate::game::progression::Progression;
 
+/// Pops a short notification at the top of the screen.
+#[derive(Message, Debug, Clone)]
+pub struct Toast {
+    pub text: String,
+    pub seconds: f32,
+}
+
+impl Toast {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            seconds: 3.0,
+        }
+    }
+}
+
 #[derive(Component)]
 struct HudRoot;
 
 #[derive(Component)]
-struct HudLine;
+struct HealthFill;
+
+#[derive(Component)]
+struct ArmorFill;
+
+#[derive(Component)]
+struct AmmoText;
+
+#[derive(Component)]
+struct WantedStar(u8);
+
+#[derive(Component, Default)]
+struct CashCounter {
+    displayed: f32,
+}
+
+#[derive(Component)]
+struct SkillText;
+
+#[derive(Component)]
+struct ObjectiveTitle;
+
+#[derive(Component)]
+struct ObjectiveBrief;
+
+#[derive(Component)]
+struct ToastStack;
+
+#[derive(Component)]
+struct ToastEntry {
+    remaining: f32,
+}
+
+#[derive(Component)]
+struct SubtitleStack;
+
+#[derive(Component)]
+struct SubtitleEntry {
+    remaining: f32,
+}
+
+#[derive(Component)]
+struct DialogueChoices;
+
+/// Older subtitles are dropped once more than this are showing.
+const MAX_SUBTITLES: usize = 3;
+
+const STAR_LIT: Color = Color::srgb(1.0, 0.82, 0.2);
+const STAR_DIM: Color = Color::srgba(1.0, 1.0, 1.0, 0.15);
 
 pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::InGame), spawn_hud)
-            .add_systems(Update, update_hud.run_if(in_state(GameState::InGame)));
+        app.add_message::<Toast>()
+            .add_systems(OnEnter(InSession), spawn_hud)
+            .add_systems(
+                Update,
+                (
+                    update_health_bar,
+                    update_armor_bar,
+                    update_ammo,
+                    update_wanted_stars,
+                    animate_cash,
+                    update_skill_points,
+                    update_objective,
+                    (spawn_toasts, expire_toasts).chain(),
+                    (spawn_subtitles, expire_subtitles).chain(),
+                    update_dialogue_choices,
+                )
+                    .run_if(in_state(GameState::InGame)),
+            );
     }
 }
 
+fn label(text: impl Into<String>, size: f32) -> impl Bundle {
+    (
+        Text::new(text),
+        TextFont {
+            font_size: size,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+    )
+}
+
+fn bar(fill_color: Color, fill: impl Component) -> impl Bundle {
+    (
+        Node {
+            width: Val::Px(220.0),
+            height: Val::Px(12.0),
+            margin: UiRect::bottom(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        children![(
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            BackgroundColor(fill_color),
+            fill,
+        )],
+    )
+}
+
 fn spawn_hud(mut commands: Commands) {
     commands
         .spawn((
@@ -30,43 +146,337 @@ fn spawn_hud(mut commands: Commands) {
                 ..default()
             },
             HudRoot,
+            DespawnOnExit(InSession),
         ))
         .with_children(|parent| {
-            parent.spawn((
-                Node {
+            // Vitals and objective, top-left.
+            parent
+                .spawn(Node {
                     position_type: PositionType::Absolute,
                     top: Val::Px(12.0),
                     left: Val::Px(12.0),
+                    flex_direction: FlexDirection::Column,
                     ..default()
-                },
-                Text::new("HUD initializing..."),
-                TextFont {
-                    font_size: 18.0,
+                })
+                .with_children(|column| {
+                    column.spawn(bar(Color::srgb(0.85, 0.15, 0.2), HealthFill));
+                    column.spawn(bar(Color::srgb(0.3, 0.6, 1.0), ArmorFill));
+                    column.spawn((
+                        Node {
+                            flex_direction: FlexDirection::Column,
+                            margin: UiRect::top(Val::Px(8.0)),
+                            ..default()
+                        },
+                        children![
+                            (label("", 16.0), ObjectiveTitle),
+                            (label("", 13.0), ObjectiveBrief),
+                        ],
+                    ));
+                });
+
+            // Wanted, cash and ammo, top-right.
+            parent
+                .spawn(Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(12.0),
+                    right: Val::Px(12.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::FlexEnd,
+                    ..default()
+                })
+                .with_children(|column| {
+                    column
+                        .spawn(Node {
+                            margin: UiRect::bottom(Val::Px(6.0)),
+                            ..default()
+                        })
+                        .with_children(|row| {
+                            for star in 0..MAX_WANTED_STARS {
+                                row.spawn((
+                                    Node {
+                                        width: Val::Px(14.0),
+                                        height: Val::Px(14.0),
+                                        margin: UiRect::left(Val::Px(4.0)),
+                                        ..default()
+                                    },
+                                    BackgroundColor(STAR_DIM),
+                                    WantedStar(star),
+                                ));
+                            }
+                        });
+                    column.spawn((label("$0", 22.0), CashCounter::default()));
+                    column.spawn((label("", 14.0), SkillText));
+                    column.spawn((label("", 18.0), AmmoText));
+                });
+
+            parent.spawn((
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(48.0),
+                    width: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
                     ..default()
                 },
-                TextColor(Color::WHITE),
-                HudLine,
+                ToastStack,
             ));
+
+            // Subtitles and dialogue replies, bottom-centre.
+            parent
+                .spawn(Node {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(32.0),
+                    width: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..default()
+                })
+                .with_children(|column| {
+                    column.spawn((
+                        Node {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        SubtitleStack,
+                    ));
+                    column.spawn((label("", 16.0), DialogueChoices));
+                });
         });
 }
 
-fn update_hud(
-    player_health: Query<&Health>,
+fn update_health_bar(
+    player: Query<&Health, (With<Player>, Changed<Health>)>,
+    mut fill: Query<&mut Node, With<HealthFill>>,
+) {
+    let Ok(health) = player.single() else {
+        return;
+    };
+    let ratio = if health.max > 0.0 {
+        health.current / health.max
+    } else {
+        0.0
+    };
+    for mut node in &mut fill {
+        node.width = Val::Percent(ratio.clamp(0.0, 1.0) * 100.0);
+    }
+}
+
+fn update_armor_bar(
+    player: Query<&Armor, (With<Player>, Changed<Armor>)>,
+    mut fill: Query<&mut Node, With<ArmorFill>>,
+) {
+    let Ok(armor) = player.single() else {
+        return;
+    };
+    let ratio = if armor.max > 0.0 {
+        armor.current / armor.max
+    } else {
+        0.0
+    };
+    for mut node in &mut fill {
+        node.width = Val::Percent(ratio.clamp(0.0, 1.0) * 100.0);
+    }
+}
+
+fn update_ammo(
+    player: Query<&WeaponInventory, (With<Player>, Changed<WeaponInventory>)>,
+    mut text: Query<&mut Text, With<AmmoText>>,
+) {
+    let Ok(inventory) = player.single() else {
+        return;
+    };
+    let line = match inventory.equipped() {
+        Some(weapon) => format!("{}  {}/{}", weapon.name, weapon.clip, weapon.reserve),
+        None => "Fists".to_string(),
+    };
+    for mut text in &mut text {
+        text.0.clone_from(&line);
+    }
+}
+
+fn update_wanted_stars(
+    wanted: Res<WantedLevel>,
+    new_stars: Query<(), Added<WantedStar>>,
+    mut stars: Query<(&WantedStar, &mut BackgroundColor)>,
+) {
+    if !wanted.is_changed() && new_stars.is_empty() {
+        return;
+    }
+    for (star, mut color) in &mut stars {
+        color.0 = if star.0 < wanted.stars {
+            STAR_LIT
+        } else {
+            STAR_DIM
+        };
+    }
+}
+
+fn animate_cash(
+    time: Res<Time>,
     progression: Res<Progression>,
-    mut query: Query<&mut Text, With<HudLine>>,
+    mut counter: Query<(&mut CashCounter, &mut Text)>,
 ) {
-    let Some(mut text) = query.iter_mut().next() else {
+    let target = progression.cash as f32;
+    for (mut counter, mut text) in &mut counter {
+        if counter.displayed == target {
+            continue;
+        }
+        // Roll quickly toward the real balance, snapping once we're within a dollar.
+        let step = ((target - counter.displayed) * 8.0 * time.delta_secs())
+            .abs()
+            .max(1.0);
+        counter.displayed = if counter.displayed < target {
+            (counter.displayed + step).min(target)
+        } else {
+            (counter.displayed - step).max(target)
+        };
+        text.0 = format!("${:.0}", counter.displayed);
+    }
+}
+
+fn update_skill_points(
+    progression: Res<Progression>,
+    new_text: Query<(), Added<SkillText>>,
+    mut text: Query<&mut Text, With<SkillText>>,
+) {
+    if !progression.is_changed() && new_text.is_empty() {
+        return;
+    }
+    for mut text in &mut text {
+        text.0 = format!("Skill: {}", progression.skill_points);
+    }
+}
+
+fn update_objective(
+    mission_log: Res<MissionLog>,
+    new_panels: Query<(), Added<ObjectiveTitle>>,
+    mut titles: Query<&mut Text, (With<ObjectiveTitle>, Without<ObjectiveBrief>)>,
+    mut briefs: Query<&mut Text, (With<ObjectiveBrief>, Without<ObjectiveTitle>)>,
+) {
+    if !mission_log.is_changed() && new_panels.is_empty() {
         return;
+    }
+    let (title, brief) = match &mission_log.active {
+        Some(mission) => (mission.title, mission.brief),
+        None => ("", ""),
     };
+    for mut text in &mut titles {
+        text.0 = title.to_string();
+    }
+    for mut text in &mut briefs {
+        text.0 = brief.to_string();
+    }
+}
 
-    let health = player_health.iter().next().map(|h| (h.current, h.max));
-    let health_line = match health {
-        Some((current, max)) => format!("HP: {:.0}/{:.0}", current, max),
-        None => "HP: --".to_string(),
+fn spawn_toasts(
+    mut commands: Commands,
+    mut toasts: MessageReader<Toast>,
+    stack: Query<Entity, With<ToastStack>>,
+) {
+    let Ok(stack) = stack.single() else {
+        return;
+    };
+    for toast in toasts.read() {
+        commands.entity(stack).with_child((
+            Node {
+                padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                margin: UiRect::bottom(Val::Px(4.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+            ToastEntry {
+                remaining: toast.seconds,
+            },
+            children![label(toast.text.clone(), 16.0)],
+        ));
+    }
+}
+
+fn expire_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut toasts: Query<(Entity, &mut ToastEntry)>,
+) {
+    for (entity, mut toast) in &mut toasts {
+        toast.remaining -= time.delta_secs();
+        if toast.remaining <= 0.0 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn spawn_subtitles(
+    mut commands: Commands,
+    mut subtitles: MessageReader<Subtitle>,
+    stack: Query<Entity, With<SubtitleStack>>,
+) {
+    let Ok(stack) = stack.single() else {
+        return;
+    };
+    for subtitle in subtitles.read() {
+        let line = if subtitle.speaker.is_empty() {
+            subtitle.text.clone()
+        } else {
+            format!("{}: {}", subtitle.speaker, subtitle.text)
+        };
+        commands.entity(stack).with_child((
+            Node {
+                padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                margin: UiRect::top(Val::Px(4.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            SubtitleEntry {
+                remaining: subtitle.seconds,
+            },
+            children![label(line, 18.0)],
+        ));
+    }
+}
+
+fn expire_subtitles(
+    mut commands: Commands,
+    time: Res<Time>,
+    stack: Query<&Children, With<SubtitleStack>>,
+    mut subtitles: Query<&mut SubtitleEntry>,
+) {
+    let Ok(children) = stack.single() else {
+        return;
     };
+    let mut live = Vec::new();
+    for &entity in children {
+        let Ok(mut subtitle) = subtitles.get_mut(entity) else {
+            continue;
+        };
+        subtitle.remaining -= time.delta_secs();
+        if subtitle.remaining <= 0.0 {
+            commands.entity(entity).despawn();
+        } else {
+            live.push(entity);
+        }
+    }
+    // Children are in the order they were added, oldest first.
+    let excess = live.len().saturating_sub(MAX_SUBTITLES);
+    for entity in live.into_iter().take(excess) {
+        commands.entity(entity).despawn();
+    }
+}
 
-    *text = Text::new(format!(
-        "{health_line}   Cash: ${}   Skill: {}",
-        progression.cash, progression.skill_points
-    ));
+fn update_dialogue_choices(
+    conversation: Res<Conversation>,
+    mut text: Query<&mut Text, With<DialogueChoices>>,
+) {
+    if !conversation.is_changed() {
+        return;
+    }
+    let lines: Vec<String> = conversation
+        .choices
+        .iter()
+        .enumerate()
+        .map(|(index, (choice, _))| format!("{}. {choice}", index + 1))
+        .collect();
+    for mut text in &mut text {
+        text.0 = lines.join("\n");
+    }
 }
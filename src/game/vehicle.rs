@@ -1,6 +1,17 @@
 use avian3d::prelude::*;
+use bevy::ecs::system::RunSystemOnce;
+use bevy::pbr::{MeshMaterial3d, StandardMaterial};
 use bevy::prelude::*;
 
+use crate::game::console::{ConsoleAppExt, ConsoleCommand};
+use crate::game::core::GameState;
+use crate::game::input::PlayerInput;
+use crate::game::physics::GameLayer;
+use crate::game::player::{Player, PlayerFacing};
+use crate::game::prefab::{Overrides, Prefab, PrefabAppExt, PrefabShape, PrefabVisual, Prefabs};
+use crate::game::stats::{Stat, Stats};
+use crate::game::weather::Weather;
+
 #[derive(Component)]
 pub struct Vehicle {
     pub name: &'static str,
@@ -15,30 +26,340 @@ pub struct VehicleInput {
     pub handbrake: bool,
 }
 
+/// A drivable model. [`Vehicle`] components are stamped from these.
+#[derive(Debug, Clone, Copy)]
+pub struct VehicleModel {
+    pub name: &'static str,
+    /// Generic body style, accepted wherever a model name is.
+    pub class: &'static str,
+    pub max_speed: f32,
+    pub acceleration: f32,
+    pub size: Vec3,
+}
+
+pub const VEHICLE_MODELS: &[VehicleModel] = &[
+    VehicleModel {
+        name: "Sparrow",
+        class: "sedan",
+        max_speed: 24.0,
+        acceleration: 3.5,
+        size: Vec3::new(1.8, 1.2, 3.8),
+    },
+    VehicleModel {
+        name: "Bulldog",
+        class: "truck",
+        max_speed: 18.0,
+        acceleration: 2.2,
+        size: Vec3::new(2.2, 1.8, 5.0),
+    },
+    VehicleModel {
+        name: "Viper",
+        class: "sports",
+        max_speed: 34.0,
+        acceleration: 4.5,
+        size: Vec3::new(1.9, 1.0, 4.2),
+    },
+];
+
+impl VehicleModel {
+    /// Looks up a model by name or class, ignoring case.
+    pub fn find(name: &str) -> Option<&'static VehicleModel> {
+        VEHICLE_MODELS.iter().find(|model| {
+            model.name.eq_ignore_ascii_case(name) || model.class.eq_ignore_ascii_case(name)
+        })
+    }
+}
+
+/// Body colour. The paint shop changes it and the material follows.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct VehiclePaint(pub Color);
+
+/// On the player while they're behind the wheel of the given vehicle. The
+/// player's own body is switched off and rides along until they get out.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Driving(pub Entity);
+
+/// How far from a vehicle's body the player can still get in.
+const ENTER_REACH: f32 = 2.0;
+
+/// Yaw rate in radians per second at full lock and top speed.
+const TURN_RATE: f32 = 1.6;
+
 pub struct VehiclePlugin;
 
 impl Plugin for VehiclePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, apply_vehicle_input);
+        for model in VEHICLE_MODELS {
+            app.register_prefab(vehicle_prefab(model));
+        }
+        app.add_console_command(
+            ConsoleCommand::new(
+                "spawn vehicle",
+                "<model>",
+                "Spawn a vehicle in front of the player.",
+                spawn_vehicle_command,
+            )
+            .with_completions(|_| {
+                VEHICLE_MODELS
+                    .iter()
+                    .flat_map(|model| [model.name.to_lowercase(), model.class.to_string()])
+                    .collect()
+            }),
+        )
+        .add_systems(
+            Update,
+            (enter_or_exit_vehicle, drive_vehicle)
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        )
+        .add_systems(Update, (release_driver, apply_vehicle_input, apply_paint));
+    }
+}
+
+fn spawn_vehicle_command(world: &mut World, args: &[&str]) -> anyhow::Result<String> {
+    let name = args.first().copied().unwrap_or_default();
+    let Some(model) = VehicleModel::find(name) else {
+        anyhow::bail!("unknown model `{name}`");
+    };
+    world
+        .run_system_once_with(spawn_in_front_of_player, model)
+        .map_err(|err| anyhow::anyhow!("{err}"))??;
+    Ok(format!("spawned {}", model.name))
+}
+
+fn spawn_in_front_of_player(
+    In(model): In<&'static VehicleModel>,
+    mut prefabs: Prefabs,
+    player: Query<(&Transform, &PlayerFacing), With<Player>>,
+) -> anyhow::Result<()> {
+    let Ok((transform, facing)) = player.single() else {
+        anyhow::bail!("no player to spawn next to");
+    };
+    let rotation = Quat::from_rotation_y(facing.yaw);
+    let offset = rotation * Vec3::NEG_Z * (model.size.z * 0.5 + 2.0);
+    let position = Vec3::new(
+        transform.translation.x + offset.x,
+        model.size.y * 0.5 + 0.1,
+        transform.translation.z + offset.z,
+    );
+    spawn_vehicle(
+        &mut prefabs,
+        model,
+        Color::srgb(0.55, 0.57, 0.6),
+        Transform::from_translation(position).with_rotation(rotation),
+    )?;
+    Ok(())
+}
+
+/// Each model is a prefab named after its class.
+fn vehicle_prefab(model: &'static VehicleModel) -> Prefab {
+    let size = model.size;
+    Prefab::new(model.class)
+        .body(
+            RigidBody::Dynamic,
+            PrefabShape::Cuboid(size),
+            GameLayer::Vehicle,
+        )
+        .mass(1200.0)
+        .visual(PrefabVisual::new(
+            PrefabShape::Cuboid(size),
+            Color::srgb(0.55, 0.57, 0.6),
+        ))
+        .with(move |vehicle| {
+            vehicle.insert((
+                Vehicle {
+                    name: model.name,
+                    max_speed: model.max_speed,
+                    acceleration: model.acceleration,
+                },
+                VehicleInput::default(),
+                // Steering spins it about the vertical; it never rolls over.
+                LockedAxes::new().lock_rotation_x().lock_rotation_z(),
+                Name::new(model.name),
+            ));
+        })
+}
+
+/// Spawns `model` in `paint`.
+pub fn spawn_vehicle(
+    prefabs: &mut Prefabs,
+    model: &VehicleModel,
+    paint: Color,
+    transform: Transform,
+) -> anyhow::Result<Entity> {
+    let mut vehicle = prefabs.spawn(model.class, Overrides::at(transform).color(paint))?;
+    vehicle.insert(VehiclePaint(paint));
+    Ok(vehicle.id())
+}
+
+/// Swaps in the shared material for the new colour. Editing the current one
+/// would repaint every vehicle of the same model and colour.
+fn apply_paint(
+    mut prefabs: Prefabs,
+    mut vehicles: Query<
+        (
+            &Vehicle,
+            &VehiclePaint,
+            &mut MeshMaterial3d<StandardMaterial>,
+        ),
+        Changed<VehiclePaint>,
+    >,
+) {
+    for (vehicle, paint, mut material) in &mut vehicles {
+        let Some(model) = VehicleModel::find(vehicle.name) else {
+            continue;
+        };
+        if let Some(painted) = prefabs.material_for(model.class, paint.0) {
+            material.0 = painted;
+        }
     }
 }
 
 fn apply_vehicle_input(
-    mut query: Query<(&Vehicle, &mut LinearVelocity, &VehicleInput)>,
+    mut query: Query<VehicleBody, With<Vehicle>>,
+    drivers: Query<(&Driving, &Stats), Without<Vehicle>>,
+    weather: Res<Weather>,
     time: Res<Time>,
 ) {
     let delta = time.delta_secs();
-    for (vehicle, mut velocity, input) in &mut query {
-        let target_speed = vehicle.max_speed * input.throttle.clamp(-1.0, 1.0);
-        let blended = velocity
-            .z
-            .lerp(target_speed, (vehicle.acceleration * delta).clamp(0.0, 1.0));
-        velocity.z = blended;
+    let grip = weather.effects.grip;
+    for (entity, stats, transform, mut velocity, mut spin, input) in &mut query {
+        let max_speed = stats.get(Stat::TopSpeed);
+        // The driver's handling scales both how hard it pulls and how sharply it steers.
+        let handling = drivers
+            .iter()
+            .find(|(driving, _)| driving.0 == entity)
+            .map_or(1.0, |(_, stats)| stats.get(Stat::VehicleHandling))
+            * grip;
+        let forward = (transform.rotation * Vec3::NEG_Z)
+            .with_y(0.0)
+            .normalize_or_zero();
+        let speed = velocity.dot(forward);
+        let sideways = velocity.0 - forward * speed - Vec3::Y * velocity.y;
 
+        let target_speed = max_speed * input.throttle.clamp(-1.0, 1.0);
+        let mut speed = speed.lerp(
+            target_speed,
+            (stats.get(Stat::Acceleration) * handling * delta).clamp(0.0, 1.0),
+        );
         if input.handbrake {
-            velocity.z *= 0.9_f32.powf(delta * 60.0);
+            speed *= 0.9_f32.powf(delta * 60.0);
+        }
+        // Tyres bite less in the wet, so some of the slide carries over.
+        let slide = sideways * (1.0 - grip).clamp(0.0, 1.0);
+        velocity.0 = forward * speed + slide + Vec3::Y * velocity.y;
+
+        // Turns only while rolling, and the right way round in reverse.
+        let rolling = (speed / max_speed.max(1.0)).clamp(-1.0, 1.0);
+        spin.y = -input.steer.clamp(-1.0, 1.0) * TURN_RATE * handling * rolling;
+    }
+}
+
+type VehicleBody<'a> = (
+    Entity,
+    &'a Stats,
+    &'a Transform,
+    &'a mut LinearVelocity,
+    &'a mut AngularVelocity,
+    &'a VehicleInput,
+);
+
+type DriverSeat<'a> = (
+    Entity,
+    &'a mut Transform,
+    &'a mut Position,
+    &'a mut LinearVelocity,
+    Option<&'a Driving>,
+);
+
+/// Gets into the nearest vehicle in reach, or out of the current one on the
+/// driver's side.
+fn enter_or_exit_vehicle(
+    mut commands: Commands,
+    input: Res<PlayerInput>,
+    mut player: Query<DriverSeat, With<Player>>,
+    mut vehicles: Query<(Entity, &Transform, &Vehicle, &mut VehicleInput), Without<Player>>,
+) {
+    if !input.enter_vehicle {
+        return;
+    }
+    let Ok((player, mut transform, mut position, mut velocity, driving)) = player.single_mut()
+    else {
+        return;
+    };
+
+    if let Some(driving) = driving {
+        if let Ok((_, vehicle_tx, vehicle, mut controls)) = vehicles.get_mut(driving.0) {
+            *controls = VehicleInput::default();
+            let half_width =
+                VehicleModel::find(vehicle.name).map_or(1.0, |model| model.size.x * 0.5);
+            let side = vehicle_tx.rotation * Vec3::NEG_X * (half_width + 1.0);
+            let exit = Vec3::new(
+                vehicle_tx.translation.x + side.x,
+                transform.translation.y.max(1.2),
+                vehicle_tx.translation.z + side.z,
+            );
+            transform.translation = exit;
+            position.0 = exit;
+            velocity.0 = Vec3::ZERO;
         }
+        commands.entity(player).remove::<Driving>();
+        return;
+    }
+
+    let nearest = vehicles
+        .iter()
+        .filter_map(|(entity, vehicle_tx, vehicle, _)| {
+            let half_size = VehicleModel::find(vehicle.name)?.size * 0.5;
+            let local =
+                vehicle_tx.rotation.inverse() * (transform.translation - vehicle_tx.translation);
+            let gap = (local.abs() - half_size).max(Vec3::ZERO).length();
+            (gap <= ENTER_REACH).then_some((entity, gap))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+    if let Some((vehicle, _)) = nearest {
+        commands.entity(player).insert((
+            Driving(vehicle),
+            RigidBodyDisabled,
+            ColliderDisabled,
+            Visibility::Hidden,
+        ));
+    }
+}
 
-        velocity.x = input.steer.clamp(-1.0, 1.0) * (vehicle.max_speed * 0.1);
+/// Hands the driver's controls to their vehicle and carries them along with
+/// it. Losing the vehicle puts them back on foot.
+fn drive_vehicle(
+    mut commands: Commands,
+    input: Res<PlayerInput>,
+    mut drivers: Query<(Entity, &Driving, &mut Transform, &mut Position), With<Player>>,
+    mut vehicles: Query<(&Transform, &mut VehicleInput), Without<Player>>,
+) {
+    for (player, driving, mut transform, mut position) in &mut drivers {
+        let Ok((vehicle_tx, mut controls)) = vehicles.get_mut(driving.0) else {
+            commands.entity(player).remove::<Driving>();
+            continue;
+        };
+        controls.throttle = input.movement.y;
+        controls.steer = input.movement.x;
+        transform.translation = vehicle_tx.translation;
+        position.0 = vehicle_tx.translation;
+    }
+}
+
+/// Switches the player's body back on once they're out of a vehicle, however
+/// they left it.
+fn release_driver(
+    mut commands: Commands,
+    mut removed: RemovedComponents<Driving>,
+    players: Query<(), With<Player>>,
+) {
+    for entity in removed.read() {
+        if players.contains(entity) {
+            commands
+                .entity(entity)
+                .remove::<(RigidBodyDisabled, ColliderDisabled)>()
+                .insert(Visibility::Inherited);
+        }
     }
 }
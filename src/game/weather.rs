@@ -0,0 +1,345 @@
+use avian3d::prelude::*;
+use bevy::pbr::{DistanceFog, FogFalloff};
+use bevy::prelude::*;
+
+use crate::game::console::{ConsoleAppExt, ConsoleCommand};
+use crate::game::core::{GameState, InSession};
+use crate::game::mission::MissionLog;
+use crate::game::time_of_day::WorldClock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WeatherKind {
+    Clear,
+    Rain,
+    Fog,
+    Storm,
+}
+
+impl WeatherKind {
+    pub const ALL: [WeatherKind; 4] = [
+        WeatherKind::Clear,
+        WeatherKind::Rain,
+        WeatherKind::Fog,
+        WeatherKind::Storm,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WeatherKind::Clear => "clear",
+            WeatherKind::Rain => "rain",
+            WeatherKind::Fog => "fog",
+            WeatherKind::Storm => "storm",
+        }
+    }
+
+    pub fn find(name: &str) -> Option<WeatherKind> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.label().eq_ignore_ascii_case(name))
+    }
+
+    pub fn effects(self) -> WeatherEffects {
+        match self {
+            WeatherKind::Clear => WeatherEffects::CLEAR,
+            WeatherKind::Rain => WeatherEffects {
+                grip: 0.7,
+                ground_friction: 0.6,
+                vision: 0.8,
+                pedestrian_density: 0.6,
+                visibility: 150.0,
+                daylight: 0.6,
+            },
+            WeatherKind::Fog => WeatherEffects {
+                grip: 0.95,
+                ground_friction: 0.9,
+                vision: 0.4,
+                pedestrian_density: 0.8,
+                visibility: 45.0,
+                daylight: 0.7,
+            },
+            WeatherKind::Storm => WeatherEffects {
+                grip: 0.55,
+                ground_friction: 0.45,
+                vision: 0.6,
+                pedestrian_density: 0.2,
+                visibility: 90.0,
+                daylight: 0.35,
+            },
+        }
+    }
+}
+
+/// How the weather scales other systems. Multipliers are `1.0` in clear
+/// weather.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeatherEffects {
+    /// Vehicle tire grip.
+    pub grip: f32,
+    /// Friction of [`WetSurface`]s.
+    pub ground_friction: f32,
+    /// AI vision range.
+    pub vision: f32,
+    pub pedestrian_density: f32,
+    /// Distance in metres at which fog fully hides the scene.
+    pub visibility: f32,
+    /// Sun, moon and ambient brightness.
+    pub daylight: f32,
+}
+
+impl WeatherEffects {
+    pub const CLEAR: WeatherEffects = WeatherEffects {
+        grip: 1.0,
+        ground_friction: 1.0,
+        vision: 1.0,
+        pedestrian_density: 1.0,
+        visibility: 400.0,
+        daylight: 1.0,
+    };
+
+    pub fn lerp(self, other: WeatherEffects, t: f32) -> WeatherEffects {
+        WeatherEffects {
+            grip: self.grip.lerp(other.grip, t),
+            ground_friction: self.ground_friction.lerp(other.ground_friction, t),
+            vision: self.vision.lerp(other.vision, t),
+            pedestrian_density: self.pedestrian_density.lerp(other.pedestrian_density, t),
+            visibility: self.visibility.lerp(other.visibility, t),
+            daylight: self.daylight.lerp(other.daylight, t),
+        }
+    }
+
+    /// Whether every field is within `tolerance` of `other`, relative to the
+    /// larger of its magnitude and one.
+    pub fn close_to(self, other: WeatherEffects, tolerance: f32) -> bool {
+        let near = |a: f32, b: f32| (a - b).abs() <= tolerance * b.abs().max(1.0);
+        near(self.grip, other.grip)
+            && near(self.ground_friction, other.ground_friction)
+            && near(self.vision, other.vision)
+            && near(self.pedestrian_density, other.pedestrian_density)
+            && near(self.visibility, other.visibility)
+            && near(self.daylight, other.daylight)
+    }
+}
+
+/// Blends closer than this snap to their target. Lerping by a small fraction
+/// each frame would otherwise stall a rounding error short of it.
+const BLEND_SNAP_TOLERANCE: f32 = 1e-3;
+
+/// Current weather. Read [`Weather::effects`] rather than matching on
+/// `kind`, so transitions blend smoothly.
+#[derive(Resource, Debug, Clone)]
+pub struct Weather {
+    /// The weather being transitioned towards.
+    pub kind: WeatherKind,
+    pub effects: WeatherEffects,
+    /// Roughly how long a transition takes, in seconds.
+    pub transition_secs: f32,
+    /// Weather the active mission calls for, if any.
+    pub mission_forced: Option<WeatherKind>,
+    /// Set with the `weather` console command. Overrides missions too.
+    pub console_forced: Option<WeatherKind>,
+}
+
+impl Default for Weather {
+    fn default() -> Self {
+        Self {
+            kind: WeatherKind::Clear,
+            effects: WeatherEffects::CLEAR,
+            transition_secs: 20.0,
+            mission_forced: None,
+            console_forced: None,
+        }
+    }
+}
+
+impl Weather {
+    /// The weather overriding the schedule, console first.
+    pub fn forced(&self) -> Option<WeatherKind> {
+        self.console_forced.or(self.mission_forced)
+    }
+
+    /// Jumps straight to `kind`, skipping the transition.
+    pub fn set_immediate(&mut self, kind: WeatherKind) {
+        self.kind = kind;
+        self.effects = kind.effects();
+    }
+}
+
+/// Daily forecast: each entry's weather starts at its hour and lasts until
+/// the next one.
+#[derive(Resource, Debug, Clone)]
+pub struct WeatherSchedule {
+    pub entries: Vec<(f32, WeatherKind)>,
+}
+
+impl Default for WeatherSchedule {
+    fn default() -> Self {
+        Self {
+            entries: vec![
+                (4.0, WeatherKind::Fog),
+                (9.0, WeatherKind::Clear),
+                (15.0, WeatherKind::Rain),
+                (18.0, WeatherKind::Storm),
+                (20.0, WeatherKind::Rain),
+                (23.0, WeatherKind::Clear),
+            ],
+        }
+    }
+}
+
+impl WeatherSchedule {
+    pub fn at(&self, hour: f32) -> WeatherKind {
+        self.entries
+            .iter()
+            .filter(|(start, _)| *start <= hour)
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            // Before the first entry, yesterday's last one still holds.
+            .or_else(|| self.entries.iter().max_by(|a, b| a.0.total_cmp(&b.0)))
+            .map_or(WeatherKind::Clear, |(_, kind)| *kind)
+    }
+}
+
+/// Ground whose [`Friction`] drops when it's wet. `dry` is the friction in
+/// clear weather.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct WetSurface {
+    pub dry: f32,
+}
+
+pub struct WeatherPlugin;
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Weather>()
+            .init_resource::<WeatherSchedule>()
+            .add_console_command(
+                ConsoleCommand::new(
+                    "weather",
+                    "[clear | rain | fog | storm | auto]",
+                    "Show or force the weather.",
+                    weather_command,
+                )
+                .with_completions(|_| {
+                    WeatherKind::ALL
+                        .iter()
+                        .map(|kind| kind.label().to_string())
+                        .chain(["auto".to_string()])
+                        .collect()
+                }),
+            )
+            .add_systems(OnEnter(InSession), start_session_weather)
+            .add_systems(
+                Update,
+                (
+                    (
+                        follow_mission_weather.run_if(resource_changed::<MissionLog>),
+                        follow_schedule,
+                        blend_weather,
+                    )
+                        .chain()
+                        .run_if(in_state(GameState::InGame)),
+                    (wet_surfaces, apply_fog).run_if(in_state(InSession)),
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn weather_command(world: &mut World, args: &[&str]) -> anyhow::Result<String> {
+    let mut weather = world.resource_mut::<Weather>();
+    match args.first().copied() {
+        None => {}
+        Some(name) if name.eq_ignore_ascii_case("auto") => weather.console_forced = None,
+        Some(name) => {
+            let Some(kind) = WeatherKind::find(name) else {
+                anyhow::bail!("unknown weather `{name}`");
+            };
+            weather.console_forced = Some(kind);
+            weather.kind = kind;
+        }
+    }
+    let mode = if weather.console_forced.is_some() {
+        "forced"
+    } else if weather.mission_forced.is_some() {
+        "mission"
+    } else {
+        "scheduled"
+    };
+    Ok(format!("weather {} ({mode})", weather.kind.label()))
+}
+
+fn start_session_weather(
+    clock: Res<WorldClock>,
+    schedule: Res<WeatherSchedule>,
+    mut weather: ResMut<Weather>,
+) {
+    let kind = weather.forced().unwrap_or_else(|| schedule.at(clock.hour));
+    weather.set_immediate(kind);
+}
+
+fn follow_mission_weather(missions: Res<MissionLog>, mut weather: ResMut<Weather>) {
+    weather.mission_forced = missions.active.as_ref().and_then(|mission| mission.weather);
+}
+
+fn follow_schedule(
+    clock: Res<WorldClock>,
+    schedule: Res<WeatherSchedule>,
+    mut weather: ResMut<Weather>,
+) {
+    let kind = weather.forced().unwrap_or_else(|| schedule.at(clock.hour));
+    if weather.kind != kind {
+        weather.kind = kind;
+    }
+}
+
+fn blend_weather(time: Res<Time>, mut weather: ResMut<Weather>) {
+    let target = weather.kind.effects();
+    if weather.effects == target {
+        return;
+    }
+    if weather.effects.close_to(target, BLEND_SNAP_TOLERANCE) {
+        weather.effects = target;
+        return;
+    }
+    let t = (time.delta_secs() * 4.0 / weather.transition_secs.max(f32::EPSILON)).min(1.0);
+    weather.effects = weather.effects.lerp(target, t);
+}
+
+fn wet_surfaces(weather: Res<Weather>, mut surfaces: Query<(&WetSurface, &mut Friction)>) {
+    if !weather.is_changed() {
+        return;
+    }
+    for (surface, mut friction) in &mut surfaces {
+        let coefficient = surface.dry * weather.effects.ground_friction;
+        friction.dynamic_coefficient = coefficient;
+        friction.static_coefficient = coefficient;
+    }
+}
+
+fn apply_fog(
+    mut commands: Commands,
+    weather: Res<Weather>,
+    cameras: Query<(Entity, Option<&mut DistanceFog>), With<Camera3d>>,
+) {
+    let visibility = weather.effects.visibility;
+    let color = Color::srgba(0.5, 0.55, 0.6, 1.0).darker(0.3 * (1.0 - weather.effects.daylight));
+    let falloff = FogFalloff::Linear {
+        start: visibility * 0.2,
+        end: visibility,
+    };
+    for (entity, fog) in cameras {
+        match fog {
+            Some(mut fog) if weather.is_changed() => {
+                fog.color = color;
+                fog.falloff = falloff.clone();
+            }
+            Some(_) => {}
+            None => {
+                commands.entity(entity).insert(DistanceFog {
+                    color,
+                    falloff: falloff.clone(),
+                    ..default()
+                });
+            }
+        }
+    }
+}
@@ -2,7 +2,17 @@ use avian3d::prelude::*;
 use bevy::pbr::{MeshMaterial3d, StandardMaterial};
 use bevy::prelude::*;
 
-use crate::game::core::GameState;
+use crate::game::core::InSession;
+use crate::game::destructible::Destructible;
+use crate::game::faction::FactionId;
+use crate::game::level::LevelPlugin;
+use crate::game::map::{MapFeature, MapFeatureKind};
+use crate::game::physics::GameLayer;
+use crate::game::pickup::PickupEffect;
+use crate::game::prefab::{Overrides, Prefab, PrefabAppExt, PrefabShape, PrefabVisual, Prefabs};
+use crate::game::streaming::StreamingConfig;
+use crate::game::time_of_day::NightLight;
+use crate::game::weather::WetSurface;
 
 #[derive(Resource)]
 pub struct WorldConfig {
@@ -19,15 +29,164 @@ impl Default for WorldConfig {
     }
 }
 
+/// A named area of the city, centred on the entity. Overlapping districts
+/// resolve to the smallest one.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct District {
+    pub name: &'static str,
+    pub size: Vec2,
+    pub faction: Option<FactionId>,
+}
+
+impl District {
+    pub fn contains(&self, center: Vec3, point: Vec3) -> bool {
+        let local = (point - center).xz().abs();
+        local.x <= self.size.x * 0.5 && local.y <= self.size.y * 0.5
+    }
+}
+
+/// Where a pedestrian can walk in from. Streamed in with its chunk.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct PedestrianSpawn;
+
+/// Something placed in a chunk. Generated off the main thread, so it's plain
+/// data until [`spawn_chunk_prop`] turns it into entities.
+#[derive(Debug, Clone)]
+pub enum PropKind {
+    Building { size: Vec3 },
+    StreetLamp,
+    Hydrant,
+    Fence,
+    Crate { loot: Option<PickupEffect> },
+    PedestrianSpawn,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChunkProp {
+    /// Stable within its chunk, so per-chunk state survives a reload.
+    pub id: u32,
+    pub kind: PropKind,
+    pub position: Vec3,
+}
+
+const POLE_HEIGHT: f32 = 5.0;
+const HYDRANT_SIZE: Vec3 = Vec3::new(0.4, 0.8, 0.4);
+const FENCE_SIZE: Vec3 = Vec3::new(2.0, 1.0, 0.1);
+const CRATE_SIZE: Vec3 = Vec3::ONE;
+/// Buildings stay clear of the main avenue and the blocks around the origin.
+const AVENUE_HALF_WIDTH: f32 = 40.0;
+const CENTRAL_BLOCK: f32 = 60.0;
+/// Side streets run along every chunk edge; buildings keep off them.
+const STREET_HALF_WIDTH: f32 = 2.0;
+
+/// The streamed props, as prefabs named after their [`PropKind`].
+fn prop_prefabs() -> Vec<Prefab> {
+    let mut crate_visual = PrefabVisual::new(
+        PrefabShape::Cuboid(CRATE_SIZE),
+        Color::srgb(0.25, 0.35, 0.75),
+    );
+    crate_visual.roughness = 0.6;
+    crate_visual.metallic = 0.05;
+    let mut building_visual =
+        PrefabVisual::new(PrefabShape::Cuboid(Vec3::ONE), Color::srgb(0.3, 0.31, 0.35));
+    building_visual.roughness = 0.8;
+    let pole = PrefabShape::Cylinder {
+        radius: 0.08,
+        height: POLE_HEIGHT,
+    };
+    let hydrant = PrefabShape::Cylinder {
+        radius: HYDRANT_SIZE.x * 0.5,
+        height: HYDRANT_SIZE.y,
+    };
+
+    vec![
+        // A unit cube, scaled per building.
+        Prefab::new("building")
+            .body(
+                RigidBody::Static,
+                PrefabShape::Cuboid(Vec3::ONE),
+                GameLayer::Static,
+            )
+            .visual(building_visual),
+        Prefab::new("street_lamp")
+            .body(RigidBody::Static, pole, GameLayer::Static)
+            .visual(PrefabVisual::new(pole, Color::srgb(0.2, 0.21, 0.23)))
+            .with(|lamp| {
+                lamp.insert(
+                    Destructible::new(200.0, 3_000.0, Vec3::new(0.16, POLE_HEIGHT, 0.16))
+                        .with_fracture(UVec3::new(1, 3, 1)),
+                )
+                .with_child((
+                    PointLight {
+                        color: Color::srgb(1.0, 0.8, 0.55),
+                        range: 18.0,
+                        intensity: 0.0,
+                        ..default()
+                    },
+                    NightLight { intensity: 8_000.0 },
+                    Transform::from_xyz(0.0, POLE_HEIGHT * 0.5, 0.0),
+                ));
+            }),
+        Prefab::new("hydrant")
+            .body(RigidBody::Static, hydrant, GameLayer::Static)
+            .visual(PrefabVisual::new(hydrant, Color::srgb(0.75, 0.12, 0.1)))
+            .with(|hydrant| {
+                hydrant.insert(
+                    Destructible::new(80.0, 1_500.0, HYDRANT_SIZE)
+                        .with_fracture(UVec3::new(1, 2, 1)),
+                );
+            }),
+        Prefab::new("fence")
+            .body(
+                RigidBody::Static,
+                PrefabShape::Cuboid(FENCE_SIZE),
+                GameLayer::Static,
+            )
+            .visual(PrefabVisual::new(
+                PrefabShape::Cuboid(FENCE_SIZE),
+                Color::srgb(0.45, 0.33, 0.2),
+            ))
+            .with(|fence| {
+                fence.insert(
+                    Destructible::new(30.0, 600.0, FENCE_SIZE).with_fracture(UVec3::new(3, 1, 1)),
+                );
+            }),
+        Prefab::new("crate")
+            .body(
+                RigidBody::Dynamic,
+                PrefabShape::Cuboid(CRATE_SIZE),
+                GameLayer::Prop,
+            )
+            .mass(45.0)
+            .visual(crate_visual)
+            .with(|crate_| {
+                crate_.insert((
+                    Destructible::new(40.0, 600.0, CRATE_SIZE),
+                    LinearDamping(0.0),
+                    AngularDamping(0.1),
+                ));
+            }),
+        Prefab::new("pedestrian_spawn").with(|spawn| {
+            spawn.insert(PedestrianSpawn);
+        }),
+    ]
+}
+
 pub struct WorldPlugin;
 
 impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
+        for prefab in prop_prefabs() {
+            app.register_prefab(prefab);
+        }
         app.init_resource::<WorldConfig>()
-            .add_systems(OnEnter(GameState::InGame), spawn_world);
+            .add_plugins(LevelPlugin)
+            .add_systems(OnEnter(InSession), (spawn_world, spawn_street_map));
     }
 }
 
+/// The ground, the district and the shop-front neon. Everything else is
+/// streamed in by chunk; see [`chunk_props`].
 fn spawn_world(
     mut commands: Commands,
     config: Res<WorldConfig>,
@@ -40,15 +199,6 @@ fn spawn_world(
         affects_lightmapped_meshes: true,
     });
 
-    commands.spawn((
-        DirectionalLight {
-            illuminance: 50_000.0,
-            shadows_enabled: true,
-            ..default()
-        },
-        Transform::from_xyz(-15.0, 22.0, -8.0).looking_at(Vec3::ZERO, Vec3::Y),
-    ));
-
     commands.spawn((
         Mesh3d(
             meshes.add(
@@ -62,51 +212,243 @@ fn spawn_world(
             perceptual_roughness: 0.9,
             ..default()
         })),
-    ));
-
-    commands.spawn((
-        PointLight {
-            intensity: 12_000.0,
-            shadows_enabled: true,
-            range: 200.0,
-            ..default()
-        },
-        Transform::from_xyz(0.0, 12.0, 0.0),
+        DespawnOnExit(InSession),
     ));
 
     commands.spawn((
         RigidBody::Static,
         Collider::cuboid(
-            config.ground_size.x * 0.5,
-            config.ground_height * 0.5,
-            config.ground_size.y * 0.5,
+            config.ground_size.x,
+            config.ground_height,
+            config.ground_size.y,
         ),
+        GameLayer::Static.layers(),
         Friction::new(0.1),
+        WetSurface { dry: 0.1 },
         Transform::from_xyz(0.0, -config.ground_height * 0.5, 0.0),
+        MapFeature {
+            kind: MapFeatureKind::District,
+            size: config.ground_size,
+        },
+        District {
+            name: "Downtown",
+            size: config.ground_size,
+            faction: Some(FactionId::RustbornChoir),
+        },
+        DespawnOnExit(InSession),
         Name::new("Ground"),
     ));
 
-    let cube_mesh = meshes.add(Cuboid::new(1.0, 1.0, 1.0));
-    let cube_mat = materials.add(StandardMaterial {
-        base_color: Color::srgb(0.25, 0.35, 0.75),
-        perceptual_roughness: 0.6,
-        metallic: 0.05,
-        ..default()
-    });
+    // Neon signs over the shops. Like the street lamps they only light up
+    // after dark; see [`NightLight`].
+    let neon = [
+        (Vec3::new(30.0, 3.5, -25.0), Color::srgb(1.0, 0.2, 0.7)),
+        (Vec3::new(-36.0, 3.5, -33.0), Color::srgb(0.2, 0.9, 1.0)),
+        (Vec3::new(40.0, 3.5, 25.0), Color::srgb(0.4, 1.0, 0.4)),
+        (Vec3::new(-18.0, 4.0, 6.0), Color::srgb(0.7, 0.3, 1.0)),
+    ];
+    for (position, color) in neon {
+        commands.spawn((
+            PointLight {
+                color,
+                range: 12.0,
+                intensity: 0.0,
+                ..default()
+            },
+            NightLight { intensity: 4_000.0 },
+            Transform::from_translation(position),
+            DespawnOnExit(InSession),
+            Name::new("Neon sign"),
+        ));
+    }
+}
+
+/// The avenue and the side-street grid, for the map. The streets are just
+/// the gaps the building generator leaves; see [`chunk_props`].
+fn spawn_street_map(
+    mut commands: Commands,
+    config: Res<WorldConfig>,
+    streaming: Res<StreamingConfig>,
+) {
+    let extent = config.ground_size * 0.5;
+    let mut road = |center: Vec2, size: Vec2, name: &'static str| {
+        commands.spawn((
+            Transform::from_xyz(center.x, 0.0, center.y),
+            MapFeature {
+                kind: MapFeatureKind::Road,
+                size,
+            },
+            DespawnOnExit(InSession),
+            Name::new(name),
+        ));
+    };
+    road(
+        Vec2::ZERO,
+        Vec2::new(AVENUE_HALF_WIDTH * 2.0, config.ground_size.y),
+        "Avenue",
+    );
+
+    let street = STREET_HALF_WIDTH * 2.0;
+    let first = (-extent / streaming.chunk_size).ceil().as_ivec2();
+    let last = (extent / streaming.chunk_size).floor().as_ivec2();
+    for x in first.x..=last.x {
+        let x = x as f32 * streaming.chunk_size;
+        if x.abs() >= AVENUE_HALF_WIDTH {
+            road(
+                Vec2::new(x, 0.0),
+                Vec2::new(street, config.ground_size.y),
+                "Street",
+            );
+        }
+    }
+    for z in first.y..=last.y {
+        let z = z as f32 * streaming.chunk_size;
+        road(
+            Vec2::new(0.0, z),
+            Vec2::new(config.ground_size.x, street),
+            "Street",
+        );
+    }
+}
 
+/// Hand-placed props anywhere in the city.
+fn authored_props() -> Vec<(PropKind, Vec3)> {
+    let mut props = Vec::new();
+    for i in -4..=4 {
+        for side in [-10.0, 10.0] {
+            let position = Vec3::new(side, POLE_HEIGHT * 0.5, i as f32 * 16.0);
+            props.push((PropKind::StreetLamp, position));
+        }
+    }
+    for position in [
+        Vec3::new(-9.0, 0.0, 8.0),
+        Vec3::new(9.0, 0.0, -24.0),
+        Vec3::new(-9.0, 0.0, -40.0),
+        Vec3::new(9.0, 0.0, 40.0),
+    ] {
+        props.push((PropKind::Hydrant, position + Vec3::Y * HYDRANT_SIZE.y * 0.5));
+    }
+    for i in 0..6 {
+        let position = Vec3::new(-14.0 - i as f32 * FENCE_SIZE.x, FENCE_SIZE.y * 0.5, 20.0);
+        props.push((PropKind::Fence, position));
+    }
     for i in 0..8 {
         let x = -6.0 + (i as f32 % 4.0) * 3.5;
         let z = -6.0 + (i as f32 / 4.0).floor() * 3.5;
-        commands.spawn((
-            RigidBody::Dynamic,
-            Collider::cuboid(0.5, 0.5, 0.5),
-            Mass(45.0),
-            LinearDamping(0.0),
-            AngularDamping(0.1),
-            Mesh3d(cube_mesh.clone()),
-            MeshMaterial3d(cube_mat.clone()),
-            Transform::from_xyz(x, 2.0, z),
-            Name::new(format!("Crate {i}")),
-        ));
+        let loot = match i % 3 {
+            0 => Some(PickupEffect::Cash(25)),
+            1 => Some(PickupEffect::Ammo(12)),
+            _ => None,
+        };
+        props.push((PropKind::Crate { loot }, Vec3::new(x, 2.0, z)));
+    }
+    props
+}
+
+/// Everything in the chunk at `coord`: authored props that fall inside it,
+/// then generated buildings and pedestrian spawns. Deterministic, so a
+/// chunk comes back the same every time it's loaded.
+pub fn chunk_props(coord: IVec2, chunk_size: f32) -> Vec<ChunkProp> {
+    let min = coord.as_vec2() * chunk_size;
+    let max = min + Vec2::splat(chunk_size);
+    let inside = |position: Vec3| {
+        position.x >= min.x && position.x < max.x && position.z >= min.y && position.z < max.y
+    };
+
+    let mut props: Vec<(PropKind, Vec3)> = authored_props()
+        .into_iter()
+        .filter(|(_, position)| inside(*position))
+        .collect();
+
+    for side in [-12.0, 12.0] {
+        let position = Vec3::new(side, 0.0, min.y + chunk_size * 0.5);
+        if inside(position) {
+            props.push((PropKind::PedestrianSpawn, position));
+        }
+    }
+
+    let mut seed = (coord.x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (coord.y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+    let mut random = move || {
+        // splitmix64
+        seed = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = seed;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        ((z ^ (z >> 31)) >> 40) as f32 / (1u64 << 24) as f32
+    };
+    for _ in 0..4 {
+        let size = Vec3::new(
+            8.0 + random() * 10.0,
+            10.0 + random() * 24.0,
+            8.0 + random() * 10.0,
+        );
+        let margin = size.xz() * 0.5 + STREET_HALF_WIDTH;
+        let x = min.x + margin.x + random() * (chunk_size - margin.x * 2.0);
+        let z = min.y + margin.y + random() * (chunk_size - margin.y * 2.0);
+        let near_avenue = x.abs() - margin.x < AVENUE_HALF_WIDTH;
+        let near_centre = x.abs() - margin.x < CENTRAL_BLOCK && z.abs() - margin.y < CENTRAL_BLOCK;
+        let overlaps = props.iter().any(|(kind, other)| match kind {
+            PropKind::Building { size: other_size } => {
+                let gap = (Vec2::new(x, z) - other.xz()).abs();
+                gap.x < (size.x + other_size.x) * 0.5 + 4.0
+                    && gap.y < (size.z + other_size.z) * 0.5 + 4.0
+            }
+            _ => false,
+        });
+        if near_avenue || near_centre || overlaps {
+            continue;
+        }
+        props.push((PropKind::Building { size }, Vec3::new(x, size.y * 0.5, z)));
+    }
+
+    props
+        .into_iter()
+        .enumerate()
+        .map(|(id, (kind, position))| ChunkProp {
+            id: id as u32,
+            kind,
+            position,
+        })
+        .collect()
+}
+
+impl PropKind {
+    pub fn prefab(&self) -> &'static str {
+        match self {
+            PropKind::Building { .. } => "building",
+            PropKind::StreetLamp => "street_lamp",
+            PropKind::Hydrant => "hydrant",
+            PropKind::Fence => "fence",
+            PropKind::Crate { .. } => "crate",
+            PropKind::PedestrianSpawn => "pedestrian_spawn",
+        }
+    }
+}
+
+/// Spawns one streamed prop. The caller owns its lifetime.
+pub fn spawn_chunk_prop(prefabs: &mut Prefabs, prop: &ChunkProp) -> anyhow::Result<Entity> {
+    let transform = Transform::from_translation(prop.position);
+    let transform = match &prop.kind {
+        PropKind::Building { size } => transform.with_scale(*size),
+        _ => transform,
+    };
+    let mut entity = prefabs.spawn(prop.kind.prefab(), Overrides::at(transform))?;
+    match &prop.kind {
+        PropKind::Building { size } => {
+            entity.insert(MapFeature {
+                kind: MapFeatureKind::Building,
+                size: size.xz(),
+            });
+        }
+        PropKind::Crate { loot } => {
+            let loot = loot.clone();
+            entity
+                .insert(Name::new(format!("Crate {}", prop.id)))
+                .entry::<Destructible>()
+                .and_modify(move |mut destructible| destructible.loot = loot);
+        }
+        _ => {}
     }
+    Ok(entity.id())
 }
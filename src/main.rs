@@ -1,8 +1,6 @@
-mod game;
-
+use asphalt_saints::game::GamePlugin;
 use bevy::prelude::*;
 use bevy::window::PresentMode;
-use game::GamePlugin;
 
 fn main() {
     App::new()
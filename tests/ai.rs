@@ -0,0 +1,72 @@
+mod common;
+
+use asphalt_saints::game::ai::{AiConfig, AiRole, Responder, WantedLevel};
+use asphalt_saints::game::combat::Health;
+use asphalt_saints::game::console::run_console_command;
+use asphalt_saints::game::stats::{ModifierSource, Stat, StatModifier, Stats};
+use bevy::prelude::*;
+use common::TestApp;
+
+fn responders(test: &mut TestApp) -> usize {
+    test.world_mut()
+        .query_filtered::<&AiRole, With<Responder>>()
+        .iter(test.world())
+        .filter(|role| matches!(role, AiRole::Cop))
+        .count()
+}
+
+#[test]
+fn responders_follow_the_wanted_level() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+    assert_eq!(responders(&mut test), 0);
+
+    test.world_mut().resource_mut::<WantedLevel>().set(2);
+    test.tick(10);
+    let per_star = test.world().resource::<AiConfig>().cops_per_star;
+    assert_eq!(responders(&mut test), 2 * per_star);
+
+    // Cops placed by hand aren't called off with the rest.
+    run_console_command(test.world_mut(), "spawn cop").unwrap();
+    test.world_mut().resource_mut::<WantedLevel>().clear();
+    test.tick(2);
+    assert_eq!(responders(&mut test), 0);
+    let cops = test
+        .world_mut()
+        .query::<&AiRole>()
+        .iter(test.world())
+        .filter(|role| matches!(role, AiRole::Cop))
+        .count();
+    assert_eq!(cops, 1);
+}
+
+#[test]
+fn npc_health_follows_their_max_health_stat() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+    run_console_command(test.world_mut(), "spawn cop").unwrap();
+    test.tick(1);
+    let cop = test
+        .world_mut()
+        .query_filtered::<Entity, With<AiRole>>()
+        .iter(test.world())
+        .find(|entity| matches!(test.world().get::<AiRole>(*entity), Some(AiRole::Cop)))
+        .expect("no cop");
+    let base = AiRole::Cop.stats();
+    let health = test.world().get::<Health>(cop).unwrap();
+    assert_eq!(health.max, base.get(Stat::MaxHealth));
+    assert!(test.world().get::<Stats>(cop).unwrap().get(Stat::WalkSpeed) > 0.0);
+
+    test.world_mut()
+        .get_mut::<Stats>(cop)
+        .unwrap()
+        .add_modifier(StatModifier::add(
+            Stat::MaxHealth,
+            ModifierSource::Upgrade("body armour"),
+            50.0,
+        ));
+    test.tick(1);
+    let health = test.world().get::<Health>(cop).unwrap();
+    assert_eq!(health.max, base.get(Stat::MaxHealth) + 50.0);
+    assert_eq!(health.current, health.max);
+}
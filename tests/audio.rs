@@ -0,0 +1,67 @@
+mod common;
+
+use std::time::Duration;
+
+use asphalt_saints::game::audio::{AudioPlugin, SoundCategory, SoundVoice};
+use asphalt_saints::game::combat::WeaponFired;
+use asphalt_saints::game::dialogue::Subtitle;
+use bevy::prelude::*;
+use common::TestApp;
+
+fn voices(test: &mut TestApp, category: SoundCategory) -> usize {
+    test.world_mut()
+        .query::<&SoundVoice>()
+        .iter(test.world())
+        .filter(|voice| voice.category == category)
+        .count()
+}
+
+/// Ticks until no `category` voices are left, giving up after a few seconds
+/// of background loading.
+fn wait_for_silence(test: &mut TestApp, category: SoundCategory) -> bool {
+    for _ in 0..300 {
+        if voices(test, category) == 0 {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+        test.tick(1);
+    }
+    false
+}
+
+#[test]
+fn missing_clips_are_dropped_instead_of_holding_voices() {
+    let mut test = TestApp::with(|app| {
+        app.init_asset::<AudioSource>().add_plugins(AudioPlugin);
+    });
+    let player = test.spawn_player();
+
+    test.send(WeaponFired {
+        shooter: player,
+        weapon: "Pistol",
+        origin: Vec3::ZERO,
+        direction: Vec3::NEG_Z,
+    });
+    test.send(Subtitle {
+        speaker: "Nobody".to_string(),
+        text: "...".to_string(),
+        seconds: 1.0,
+        voice: Some("voice/does_not_exist.ogg".to_string()),
+        emitter: None,
+    });
+    test.tick(1);
+    assert_eq!(voices(&mut test, SoundCategory::Dialogue), 1);
+
+    assert!(wait_for_silence(&mut test, SoundCategory::Weapon));
+    assert!(wait_for_silence(&mut test, SoundCategory::Dialogue));
+
+    // Once a clip is known to be missing it isn't started at all.
+    test.send(WeaponFired {
+        shooter: player,
+        weapon: "Pistol",
+        origin: Vec3::ZERO,
+        direction: Vec3::NEG_Z,
+    });
+    test.tick(1);
+    assert_eq!(voices(&mut test, SoundCategory::Weapon), 0);
+}
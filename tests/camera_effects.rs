@@ -0,0 +1,99 @@
+mod common;
+
+use asphalt_saints::game::camera::TopDownCamera;
+use asphalt_saints::game::camera_effects::{
+    CameraEffectsPlugin, CameraShake, Occluding, OcclusionConfig, OcclusionMode,
+};
+use asphalt_saints::game::combat::DamageEvent;
+use asphalt_saints::game::physics::GameLayer;
+use avian3d::prelude::*;
+use bevy::pbr::{MeshMaterial3d, StandardMaterial};
+use bevy::prelude::*;
+use common::TestApp;
+
+/// Offsets and rotations sampled over a fixed run of frames.
+fn shake_run(seed: u32) -> Vec<(Vec3, Quat)> {
+    let mut shake = CameraShake::with_seed(seed);
+    shake.add_trauma(0.8);
+    (0..90)
+        .map(|frame| {
+            if frame == 30 {
+                shake.add_trauma(0.3);
+            }
+            shake.tick(1.0 / 60.0);
+            shake.sample()
+        })
+        .collect()
+}
+
+#[test]
+fn seeded_shake_replays_identically() {
+    let first = shake_run(42);
+    assert_eq!(first, shake_run(42));
+    assert!(first.iter().any(|(offset, _)| offset.length() > 0.0));
+    assert_ne!(first, shake_run(43));
+}
+
+#[test]
+fn getting_hurt_shakes_the_camera() {
+    let mut test = TestApp::with(|app| {
+        app.add_plugins(CameraEffectsPlugin);
+    });
+    let player = test.spawn_player();
+    assert_eq!(test.world().resource::<CameraShake>().trauma, 0.0);
+
+    test.send(DamageEvent {
+        entity: player,
+        amount: 40.0,
+        source: None,
+    });
+    test.tick(2);
+
+    assert!(test.world().resource::<CameraShake>().trauma > 0.3);
+}
+
+#[test]
+fn cutaway_puts_back_the_visibility_it_hid() {
+    let mut test = TestApp::with(|app| {
+        app.add_plugins(CameraEffectsPlugin)
+            .insert_resource(OcclusionConfig {
+                mode: OcclusionMode::Cutaway,
+                ..default()
+            });
+    });
+    let player = test.spawn_player();
+    let below = test.position(player);
+    test.world_mut().spawn((
+        TopDownCamera,
+        Transform::from_translation(below + Vec3::Y * 20.0),
+    ));
+    let material = test
+        .world_mut()
+        .resource_mut::<Assets<StandardMaterial>>()
+        .add(StandardMaterial::default());
+    let roof = test
+        .world_mut()
+        .spawn((
+            RigidBody::Static,
+            Collider::cuboid(3.0, 1.0, 3.0),
+            GameLayer::Static.layers(),
+            Transform::from_translation(below + Vec3::Y * 10.0),
+            MeshMaterial3d(material),
+            Visibility::Visible,
+        ))
+        .id();
+
+    test.tick(3);
+    assert_eq!(
+        test.world().get::<Visibility>(roof),
+        Some(&Visibility::Hidden)
+    );
+
+    test.teleport(roof, below + Vec3::new(30.0, 10.0, 0.0));
+    test.tick(120);
+    assert!(test.world().get::<Occluding>(roof).is_none());
+    assert_eq!(
+        test.world().get::<Visibility>(roof),
+        Some(&Visibility::Visible)
+    );
+}
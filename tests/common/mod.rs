@@ -0,0 +1,192 @@
+//! Headless app harness shared by the integration tests.
+
+// Each test binary compiles its own copy and uses a different subset.
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use asphalt_saints::game::core::GameState;
+use asphalt_saints::game::headless::HeadlessPlugin;
+use asphalt_saints::game::player::{GroundContact, Player};
+use avian3d::prelude::*;
+use bevy::input::ButtonState;
+use bevy::input::keyboard::{Key, KeyboardInput, NativeKey};
+use bevy::input::mouse::MouseButtonInput;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+
+/// Length of one simulated frame. Matches the fixed timestep so every
+/// [`TestApp::tick`] runs exactly one physics step.
+pub const TICK: Duration = Duration::from_nanos(16_666_667);
+
+/// An empty stretch of ground well away from the props, pickups and shops
+/// around the origin.
+pub const OPEN_LOT: Vec3 = Vec3::new(0.0, 1.2, 100.0);
+
+pub struct TestApp {
+    pub app: App,
+}
+
+impl TestApp {
+    /// A headless app that has finished loading and sits in the main menu.
+    pub fn new() -> Self {
+        Self::with(|_| {})
+    }
+
+    /// Like [`TestApp::new`], letting `setup` add plugins from the full game,
+    /// such as audio, before the app is finished.
+    pub fn with(setup: impl FnOnce(&mut App)) -> Self {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, HeadlessPlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(TICK))
+            .insert_resource(Time::<Fixed>::from_duration(TICK));
+        setup(&mut app);
+        app.finish();
+        app.cleanup();
+
+        let mut test = Self { app };
+        // Loading -> MainMenu happens on the first frames.
+        test.tick(2);
+        test
+    }
+
+    pub fn tick(&mut self, ticks: usize) {
+        for _ in 0..ticks {
+            self.app.update();
+        }
+    }
+
+    pub fn world(&self) -> &World {
+        self.app.world()
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+
+    pub fn state(&self) -> GameState {
+        *self.world().resource::<State<GameState>>().get()
+    }
+
+    /// Requests a transition and runs the frame that applies it.
+    pub fn set_state(&mut self, state: GameState) {
+        self.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(state);
+        self.tick(1);
+    }
+
+    /// Starts a session, moves the player to [`OPEN_LOT`] and lets them drop
+    /// onto the ground.
+    pub fn spawn_player(&mut self) -> Entity {
+        self.set_state(GameState::InGame);
+        let player = self.player();
+        self.teleport(player, OPEN_LOT);
+        for _ in 0..120 {
+            self.tick(1);
+            let grounded = self
+                .world_mut()
+                .query_filtered::<&GroundContact, With<Player>>()
+                .single(self.world())
+                .is_ok_and(|ground| ground.grounded);
+            if grounded {
+                break;
+            }
+        }
+        self.player()
+    }
+
+    pub fn player(&mut self) -> Entity {
+        self.world_mut()
+            .query_filtered::<Entity, With<Player>>()
+            .single(self.world())
+            .expect("no player; call spawn_player first")
+    }
+
+    /// Holds `key` down from the next frame until [`TestApp::release`].
+    pub fn press(&mut self, key: KeyCode) {
+        self.key(key, ButtonState::Pressed);
+    }
+
+    pub fn release(&mut self, key: KeyCode) {
+        self.key(key, ButtonState::Released);
+    }
+
+    /// Presses and releases `key`, a frame each.
+    pub fn tap(&mut self, key: KeyCode) {
+        self.press(key);
+        self.tick(1);
+        self.release(key);
+        self.tick(1);
+    }
+
+    fn key(&mut self, key_code: KeyCode, state: ButtonState) {
+        self.world_mut().write_message(KeyboardInput {
+            key_code,
+            logical_key: Key::Unidentified(NativeKey::Unidentified),
+            state,
+            text: None,
+            repeat: false,
+            window: Entity::PLACEHOLDER,
+        });
+    }
+
+    /// Holds `button` down from the next frame until [`TestApp::release_mouse`].
+    pub fn press_mouse(&mut self, button: MouseButton) {
+        self.mouse(button, ButtonState::Pressed);
+    }
+
+    pub fn release_mouse(&mut self, button: MouseButton) {
+        self.mouse(button, ButtonState::Released);
+    }
+
+    fn mouse(&mut self, button: MouseButton, state: ButtonState) {
+        self.world_mut().write_message(MouseButtonInput {
+            button,
+            state,
+            window: Entity::PLACEHOLDER,
+        });
+    }
+
+    pub fn send<M: Message>(&mut self, message: M) {
+        self.world_mut().write_message(message);
+    }
+
+    /// Messages of type `M` written during the last two frames.
+    pub fn messages<M: Message + Clone>(&self) -> Vec<M> {
+        let messages = self.world().resource::<Messages<M>>();
+        messages.get_cursor().read(messages).cloned().collect()
+    }
+
+    /// Entities with a `C`.
+    pub fn count<C: Component>(&mut self) -> usize {
+        self.world_mut()
+            .query_filtered::<(), With<C>>()
+            .iter(self.world())
+            .count()
+    }
+
+    pub fn teleport(&mut self, entity: Entity, position: Vec3) {
+        let mut entity = self.world_mut().entity_mut(entity);
+        entity.insert(Position(position));
+        if let Some(mut transform) = entity.get_mut::<Transform>() {
+            transform.translation = position;
+        }
+    }
+
+    pub fn position(&self, entity: Entity) -> Vec3 {
+        self.world()
+            .get::<Transform>(entity)
+            .expect("entity has no Transform")
+            .translation
+    }
+
+    #[track_caller]
+    pub fn assert_position(&self, entity: Entity, expected: Vec3, tolerance: f32) {
+        let actual = self.position(entity);
+        assert!(
+            actual.distance(expected) <= tolerance,
+            "expected {entity} near {expected} (±{tolerance}), found {actual}"
+        );
+    }
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use asphalt_saints::game::ai::WantedLevel;
+use asphalt_saints::game::combat::Health;
+use asphalt_saints::game::console::{complete, run_console_command};
+use asphalt_saints::game::input::KeyboardCaptured;
+use asphalt_saints::game::mission::MissionLog;
+use asphalt_saints::game::progression::Progression;
+use asphalt_saints::game::shop::ServicePanel;
+use asphalt_saints::game::vehicle::Vehicle;
+use bevy::prelude::*;
+use common::TestApp;
+
+fn run(test: &mut TestApp, line: &str) -> String {
+    run_console_command(test.world_mut(), line)
+        .unwrap_or_else(|err| panic!("`{line}` failed: {err:#}"))
+}
+
+#[test]
+fn give_cash_and_set_wanted() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+
+    run(&mut test, "give cash 500");
+    run(&mut test, "set wanted 3");
+
+    assert_eq!(test.world().resource::<Progression>().cash, 500);
+    assert_eq!(test.world().resource::<WantedLevel>().stars, 3);
+}
+
+#[test]
+fn god_toggles_invulnerability() {
+    let mut test = TestApp::new();
+    let player = test.spawn_player();
+
+    run(&mut test, "god");
+    assert!(test.world().get::<Health>(player).unwrap().invulnerable);
+    run(&mut test, "GOD");
+    assert!(!test.world().get::<Health>(player).unwrap().invulnerable);
+}
+
+#[test]
+fn teleport_moves_the_player() {
+    let mut test = TestApp::new();
+    let player = test.spawn_player();
+
+    run(&mut test, "teleport 40 -25");
+    test.tick(5);
+
+    let position = test.position(player);
+    test.assert_position(player, Vec3::new(40.0, position.y, -25.0), 0.1);
+}
+
+#[test]
+fn spawn_vehicle_and_start_mission() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+
+    run(&mut test, "spawn vehicle sedan");
+    run(&mut test, "mission start jackal_bait");
+    test.tick(1);
+
+    let vehicles = test
+        .world_mut()
+        .query::<&Vehicle>()
+        .iter(test.world())
+        .map(|vehicle| vehicle.name)
+        .collect::<Vec<_>>();
+    assert_eq!(vehicles, ["Sparrow"]);
+    let log = test.world().resource::<MissionLog>();
+    assert_eq!(log.active.as_ref().map(|m| m.id), Some("jackal_bait"));
+}
+
+#[test]
+fn bad_input_reports_errors() {
+    let mut test = TestApp::new();
+
+    assert!(run_console_command(test.world_mut(), "fly").is_err());
+    assert!(run_console_command(test.world_mut(), "give cash lots").is_err());
+    assert!(run_console_command(test.world_mut(), "spawn vehicle blimp").is_err());
+}
+
+#[test]
+fn completes_commands_and_arguments() {
+    let test = TestApp::new();
+
+    assert_eq!(complete(test.world(), "gi"), ["give cash"]);
+    assert_eq!(
+        complete(test.world(), "spawn vehicle s"),
+        [
+            "spawn vehicle sedan",
+            "spawn vehicle sparrow",
+            "spawn vehicle sports",
+        ]
+    );
+    assert!(
+        complete(test.world(), "mission start ")
+            .contains(&"mission start street_scramble".to_string())
+    );
+}
+
+#[test]
+fn typing_in_the_console_leaves_shops_alone() {
+    let mut test = TestApp::new();
+    let player = test.spawn_player();
+    run(&mut test, "give cash 5000");
+    test.teleport(player, Vec3::new(30.0, 1.2, -20.0));
+    test.tick(5);
+    test.world_mut().resource_mut::<ServicePanel>().open = true;
+
+    test.world_mut().resource_mut::<KeyboardCaptured>().0 = true;
+    test.press(KeyCode::Digit1);
+    test.tick(2);
+    test.release(KeyCode::Digit1);
+    test.tick(1);
+    assert_eq!(test.world().resource::<Progression>().cash, 5000);
+
+    test.world_mut().resource_mut::<KeyboardCaptured>().0 = false;
+    test.press(KeyCode::Digit1);
+    test.tick(2);
+    assert!(test.world().resource::<Progression>().cash < 5000);
+}
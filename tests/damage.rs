@@ -0,0 +1,101 @@
+mod common;
+
+use asphalt_saints::game::combat::{
+    Armor, DamageEvent, Health, Killed, WeaponFired, WeaponInventory,
+};
+use asphalt_saints::game::physics::GameLayer;
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use common::TestApp;
+
+#[test]
+fn armor_absorbs_damage_before_health() {
+    let mut test = TestApp::new();
+    let target = test
+        .world_mut()
+        .spawn((Health::new(100.0), Armor::new(50.0)))
+        .id();
+
+    test.send(DamageEvent {
+        entity: target,
+        amount: 70.0,
+        source: None,
+    });
+    test.tick(1);
+
+    assert_eq!(test.world().get::<Armor>(target).unwrap().current, 0.0);
+    assert_eq!(test.world().get::<Health>(target).unwrap().current, 80.0);
+}
+
+#[test]
+fn invulnerable_targets_take_nothing() {
+    let mut test = TestApp::new();
+    let mut health = Health::new(100.0);
+    health.invulnerable = true;
+    let target = test.world_mut().spawn((health, Armor::new(50.0))).id();
+
+    test.send(DamageEvent {
+        entity: target,
+        amount: 500.0,
+        source: None,
+    });
+    test.tick(2);
+
+    assert_eq!(test.world().get::<Armor>(target).unwrap().current, 50.0);
+    assert_eq!(test.world().get::<Health>(target).unwrap().current, 100.0);
+}
+
+#[test]
+fn lethal_damage_reports_the_kill_once_and_despawns() {
+    let mut test = TestApp::new();
+    let attacker = test.world_mut().spawn_empty().id();
+    let target = test.world_mut().spawn(Health::new(40.0)).id();
+
+    for _ in 0..2 {
+        test.send(DamageEvent {
+            entity: target,
+            amount: 30.0,
+            source: Some(attacker),
+        });
+    }
+    test.tick(1);
+
+    let kills = test.messages::<Killed>();
+    assert_eq!(kills.len(), 1);
+    assert_eq!(kills[0].entity, target);
+    assert_eq!(kills[0].by, Some(attacker));
+
+    test.tick(1);
+    assert!(test.world().get_entity(target).is_err());
+}
+
+#[test]
+fn holding_the_trigger_shoots_whatever_the_player_faces() {
+    let mut test = TestApp::new();
+    let player = test.spawn_player();
+    let position = test.position(player);
+    let target = test
+        .world_mut()
+        .spawn((
+            Health::new(100.0),
+            RigidBody::Static,
+            Collider::cuboid(1.0, 2.0, 1.0),
+            GameLayer::Npc.layers(),
+            Transform::from_translation(position + Vec3::NEG_Z * 6.0),
+        ))
+        .id();
+    test.tick(1);
+
+    test.press_mouse(MouseButton::Left);
+    test.tick(2);
+    test.release_mouse(MouseButton::Left);
+
+    let shots = test.messages::<WeaponFired>();
+    assert_eq!(shots.len(), 1, "{shots:?}");
+    assert_eq!(shots[0].shooter, player);
+    assert_eq!(shots[0].weapon, "Pistol");
+    test.tick(1);
+    assert_eq!(test.world().get::<Health>(target).unwrap().current, 80.0);
+    let inventory = test.world().get::<WeaponInventory>(player).unwrap();
+    assert_eq!(inventory.equipped().unwrap().clip, 11);
+}
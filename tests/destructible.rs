@@ -0,0 +1,69 @@
+mod common;
+
+use asphalt_saints::game::combat::DamageEvent;
+use asphalt_saints::game::destructible::{Debris, Destroyed, Destructible, DestructionConfig};
+use asphalt_saints::game::pickup::{Pickup, PickupEffect};
+use bevy::prelude::*;
+use common::{OPEN_LOT, TestApp};
+
+fn spawn_crate(test: &mut TestApp, position: Vec3) -> Entity {
+    test.world_mut()
+        .spawn((
+            Destructible::new(40.0, f32::INFINITY, Vec3::ONE).with_loot(PickupEffect::Cash(25)),
+            Transform::from_translation(position),
+        ))
+        .id()
+}
+
+#[test]
+fn damage_breaks_props_into_debris_and_drops_loot() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+    let pickups = test.count::<Pickup>();
+    let debris = test.count::<Debris>();
+    let prop = spawn_crate(&mut test, OPEN_LOT + Vec3::new(20.0, 0.0, 0.0));
+
+    test.send(DamageEvent {
+        entity: prop,
+        amount: 25.0,
+        source: None,
+    });
+    test.tick(1);
+    assert!(test.world().get_entity(prop).is_ok());
+
+    test.send(DamageEvent {
+        entity: prop,
+        amount: 25.0,
+        source: None,
+    });
+    test.tick(2);
+
+    assert!(test.world().get_entity(prop).is_err());
+    assert_eq!(test.messages::<Destroyed>().len(), 1);
+    assert_eq!(test.count::<Debris>(), debris + 8);
+    assert_eq!(test.count::<Pickup>(), pickups + 1);
+}
+
+#[test]
+fn debris_stays_within_budget() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+    test.world_mut()
+        .resource_mut::<DestructionConfig>()
+        .max_debris = 10;
+
+    for i in 0..4 {
+        let prop = spawn_crate(
+            &mut test,
+            OPEN_LOT + Vec3::new(20.0 + i as f32 * 3.0, 0.0, 0.0),
+        );
+        test.send(DamageEvent {
+            entity: prop,
+            amount: 100.0,
+            source: None,
+        });
+    }
+    test.tick(3);
+
+    assert!(test.count::<Debris>() <= 10);
+}
@@ -0,0 +1,295 @@
+mod common;
+
+use std::path::Path;
+
+use asphalt_saints::game::ai::{AiRole, WantedLevel};
+use asphalt_saints::game::combat::DamageEvent;
+use asphalt_saints::game::console::run_console_command;
+use asphalt_saints::game::dialogue::{
+    BarkSpeaker, BarkTrigger, ChooseResponse, Conversation, DialogueLibrary, MissionGiver,
+    StartConversation, Subtitle, parse_dialogue,
+};
+use asphalt_saints::game::faction::{FactionId, FactionRespect};
+use asphalt_saints::game::mission::MissionLog;
+use asphalt_saints::game::trigger::TriggerVolume;
+use bevy::prelude::*;
+use common::TestApp;
+
+fn subtitles(test: &TestApp) -> Vec<(String, String)> {
+    test.messages::<Subtitle>()
+        .into_iter()
+        .map(|subtitle| (subtitle.speaker, subtitle.text))
+        .collect()
+}
+
+fn choices(test: &TestApp) -> Vec<String> {
+    test.world()
+        .resource::<Conversation>()
+        .choices
+        .iter()
+        .map(|(text, _)| text.clone())
+        .collect()
+}
+
+/// Walks the player into the level trigger with `id`.
+fn walk_into(test: &mut TestApp, id: &str) {
+    let position = test
+        .world_mut()
+        .query::<(&TriggerVolume, &Transform)>()
+        .iter(test.world())
+        .find(|(volume, _)| volume.id == id)
+        .map(|(_, transform)| transform.translation)
+        .unwrap_or_else(|| panic!("no `{id}` trigger in the level"));
+    let player = test.player();
+    test.teleport(player, position + Vec3::Y);
+    test.tick(3);
+}
+
+/// Starts a session and walks the player up to Madame Sable.
+fn meet_sable(test: &mut TestApp) -> Entity {
+    let player = test.spawn_player();
+    let (giver, position) = test
+        .world_mut()
+        .query::<(Entity, &MissionGiver, &Transform)>()
+        .iter(test.world())
+        .find(|(_, giver, _)| giver.name == "Madame Sable")
+        .map(|(entity, _, transform)| (entity, transform.translation))
+        .expect("no Madame Sable in the level");
+    test.teleport(player, position + Vec3::new(2.0, 0.2, 0.0));
+    test.tick(1);
+    giver
+}
+
+#[test]
+fn parses_barks_and_nodes() {
+    let text = r#"
+// comment
+[
+    Bark(
+        speaker: Cop,
+        on: Spotted,
+        when: Wanted(GreaterOrEqual, 1),
+        lines: ["Freeze!", "Stop right there!"],
+    ),
+    Node(
+        id: "hello",
+        text: "Well?",
+        next: [(to: "busy", when: Not(Idle))],
+        choices: [
+            (text: "Work?", to: "busy", when: Respect(NeonJackals, Greater, 5)),
+            (text: "Bye."),
+        ],
+    ),
+    Node(
+        id: "busy",
+        speaker: "Guard",
+        text: "Come back later.",
+        actions: [Respect(NeonJackals, -1)],
+    ),
+]
+"#;
+    let file = parse_dialogue(Path::new("test.dialogue"), text).unwrap();
+    assert_eq!(file.barks.len(), 1);
+    assert_eq!(file.barks[0].speaker, BarkSpeaker::Cop);
+    assert_eq!(file.barks[0].trigger, BarkTrigger::Spotted);
+    assert_eq!(file.barks[0].lines, ["Freeze!", "Stop right there!"]);
+    assert_eq!(file.nodes.len(), 2);
+    assert_eq!(file.nodes[0].redirects.len(), 1);
+    assert_eq!(file.nodes[0].choices[1].target, None);
+}
+
+#[test]
+fn reports_dialogue_problems_with_file_line_and_column() {
+    let error = |entries: &str| {
+        let text = format!("[\n{entries}\n]\n");
+        parse_dialogue(Path::new("broken.dialogue"), &text)
+            .unwrap_err()
+            .to_string()
+    };
+    assert_eq!(
+        error(r#"    Bark(speaker: Mime, on: Spotted, lines: ["Hey!"]),"#),
+        "broken.dialogue:2:23: Unexpected variant named `Mime` in enum `BarkSpeaker`, expected one of `Pedestrian`, `Cop`, or `Gang` instead"
+    );
+    assert_eq!(
+        error(r#"    Node(id: "hello", txt: "Hi."),"#),
+        "broken.dialogue:2:26: Unexpected field named `txt` in `Node`, expected one of `id`, `speaker`, `text`, `voice`, `next`, `choices`, or `actions` instead"
+    );
+    // Checked once the entry, or for links the whole file, has been read.
+    assert_eq!(
+        error("    Bark(speaker: Cop, on: Spotted, lines: []),"),
+        "broken.dialogue:3:1: bark needs at least one line"
+    );
+    assert_eq!(
+        error(
+            r#"    Node(id: "hello", text: "Hi."),
+    Node(id: "hello", text: "Again."),"#
+        ),
+        "broken.dialogue:4:1: duplicate node `hello`"
+    );
+    assert_eq!(
+        error(r#"    Node(id: "hello", text: "Hi.", choices: [(text: "Go", to: "nowhere")]),"#),
+        "broken.dialogue:3:1: `hello` links to unknown node `nowhere`"
+    );
+}
+
+#[test]
+fn shipped_dialogue_loads_cleanly() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+
+    let library = test.world().resource::<DialogueLibrary>();
+    assert!(library.errors.is_empty(), "{:?}", library.errors);
+    assert!(library.nodes.contains_key("oracle_hello"));
+    assert!(
+        library
+            .barks(BarkSpeaker::Cop, BarkTrigger::Spotted)
+            .count()
+            > 0
+    );
+}
+
+#[test]
+fn conversation_choices_follow_respect_and_mission_state() {
+    let mut test = TestApp::new();
+    let giver = meet_sable(&mut test);
+
+    test.send(StartConversation { giver });
+    test.tick(1);
+    assert!(subtitles(&test).contains(&(
+        "Madame Sable".to_string(),
+        "The cards said you'd come, stranger.".to_string()
+    )));
+    test.send(ChooseResponse(0));
+    test.tick(1);
+    assert_eq!(choices(&test), ["I'll take it.", "Not today."]);
+
+    // With enough respect the haggling option opens up.
+    test.world_mut().resource_mut::<FactionRespect>().respect[FactionId::StreetOracles.index()] =
+        10;
+    test.send(ChooseResponse(1));
+    test.tick(1);
+    assert!(!test.world().resource::<Conversation>().is_active());
+    test.send(StartConversation { giver });
+    test.tick(1);
+    test.send(ChooseResponse(0));
+    test.tick(1);
+    assert_eq!(
+        choices(&test),
+        ["I'll take it.", "Make it worth my while.", "Not today."]
+    );
+
+    // The intro mission is still running, so she won't hand out another.
+    test.send(ChooseResponse(0));
+    test.tick(1);
+    let log = test.world().resource::<MissionLog>();
+    assert_eq!(log.active.as_ref().map(|m| m.id), Some("street_scramble"));
+    assert!(!test.world().resource::<Conversation>().is_active());
+
+    // Wrapping up the intro frees her up; then back to Sable.
+    walk_into(&mut test, "choir_wall");
+    let log = test.world().resource::<MissionLog>();
+    assert!(log.active.is_none());
+    assert_eq!(log.completed, ["street_scramble"]);
+    let sable = test.world().get::<Transform>(giver).unwrap().translation;
+    let player = test.player();
+    test.teleport(player, sable + Vec3::new(2.0, 0.2, 0.0));
+    test.tick(1);
+
+    test.send(StartConversation { giver });
+    test.tick(1);
+    test.send(ChooseResponse(0));
+    test.tick(1);
+    test.send(ChooseResponse(0));
+    test.tick(1);
+    let log = test.world().resource::<MissionLog>();
+    assert_eq!(log.active.as_ref().map(|m| m.id), Some("oracle_errand"));
+
+    walk_into(&mut test, "oracle_dropoff");
+    let log = test.world().resource::<MissionLog>();
+    assert!(log.active.is_none());
+    assert_eq!(log.completed, ["street_scramble", "oracle_errand"]);
+}
+
+#[test]
+fn cops_shout_when_they_spot_a_wanted_player() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+    test.world_mut().resource_mut::<WantedLevel>().set(2);
+
+    run_console_command(test.world_mut(), "spawn cop").unwrap();
+    test.tick(2);
+
+    assert!(subtitles(&test).contains(&("Cop".to_string(), "Freeze!".to_string())));
+}
+
+#[test]
+fn cops_shout_again_when_the_wanted_level_rises_or_they_lose_sight() {
+    let mut test = TestApp::new();
+    let player = test.spawn_player();
+    run_console_command(test.world_mut(), "spawn cop").unwrap();
+    test.tick(2);
+    // Spotted with a clean record, so nothing worth shouting about yet.
+    assert!(subtitles(&test).is_empty());
+
+    test.world_mut().resource_mut::<WantedLevel>().set(1);
+    test.tick(1);
+    assert!(subtitles(&test).contains(&("Cop".to_string(), "Freeze!".to_string())));
+
+    // Out of sight, then back in view once the bark cooldown has passed.
+    let home = test.position(player);
+    test.teleport(player, home + Vec3::new(45.0, 0.0, 0.0));
+    test.tick(60 * 7);
+    test.teleport(player, home);
+    test.tick(1);
+    let said = subtitles(&test);
+    assert!(said.iter().any(|(speaker, _)| speaker == "Cop"), "{said:?}");
+}
+
+#[test]
+fn pedestrians_scream_when_shot_at() {
+    let mut test = TestApp::new();
+    let player = test.spawn_player();
+    let position = test.position(player);
+    let pedestrian = test
+        .world_mut()
+        .spawn((AiRole::Pedestrian, Transform::from_translation(position)))
+        .id();
+
+    test.send(DamageEvent {
+        entity: pedestrian,
+        amount: 5.0,
+        source: Some(player),
+    });
+    test.tick(1);
+
+    let said = subtitles(&test);
+    assert_eq!(said.len(), 1, "{said:?}");
+    assert_eq!(said[0].0, "Pedestrian");
+}
+
+#[test]
+fn gang_members_taunt_rivals() {
+    let mut test = TestApp::new();
+    let player = test.spawn_player();
+    // Downtown places rival crews within earshot of each other.
+    let members: Vec<(FactionId, Vec3)> = test
+        .world_mut()
+        .query::<(&AiRole, &FactionId, &Transform)>()
+        .iter(test.world())
+        .filter(|(role, _, _)| matches!(role, AiRole::GangSoldier | AiRole::Boss))
+        .map(|(_, faction, transform)| (*faction, transform.translation))
+        .collect();
+    let factions: Vec<FactionId> = members.iter().map(|(faction, _)| *faction).collect();
+    assert!(factions.contains(&FactionId::NeonJackals), "{factions:?}");
+    assert!(factions.contains(&FactionId::TideSyndicate), "{factions:?}");
+    assert!(subtitles(&test).is_empty(), "taunts carry across town");
+
+    test.teleport(player, members[0].1 + Vec3::new(0.0, 0.2, -4.0));
+    test.tick(1);
+
+    let said = subtitles(&test);
+    assert_eq!(said.len(), 1, "{said:?}");
+    let (speaker, text) = &said[0];
+    assert!(["Neon Jackals", "Tide Syndicate"].contains(&speaker.as_str()));
+    assert!(text.contains(speaker.as_str()), "{text}");
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use asphalt_saints::game::economy::{CashChanged, Ledger, Overdraft, TransactionKind, Wallet};
+use asphalt_saints::game::faction::{FactionId, FactionRespect};
+use asphalt_saints::game::progression::Progression;
+use bevy::ecs::system::RunSystemOnce;
+use common::TestApp;
+
+fn set_cash(test: &mut TestApp, cash: u32) {
+    test.world_mut().resource_mut::<Progression>().cash = cash;
+}
+
+fn set_respect(test: &mut TestApp, faction: FactionId, respect: i32) {
+    test.world_mut().resource_mut::<FactionRespect>().respect[faction.index()] = respect;
+}
+
+fn reward(test: &mut TestApp, base: u32, faction: Option<FactionId>) -> u32 {
+    test.world_mut()
+        .run_system_once(move |mut wallet: Wallet| {
+            wallet.reward(TransactionKind::Mission, base, faction, "test job")
+        })
+        .unwrap()
+}
+
+#[test]
+fn overdrafts_leave_the_balance_and_ledger_alone() {
+    let mut test = TestApp::new();
+    set_cash(&mut test, 40);
+
+    let result = test
+        .world_mut()
+        .run_system_once(|mut wallet: Wallet| {
+            wallet.spend(TransactionKind::Purchase, 100, "too much")
+        })
+        .unwrap();
+
+    assert_eq!(
+        result,
+        Err(Overdraft {
+            balance: 40,
+            requested: 100
+        })
+    );
+    assert_eq!(test.world().resource::<Progression>().cash, 40);
+    assert!(test.world().resource::<Ledger>().entries.is_empty());
+    assert!(test.messages::<CashChanged>().is_empty());
+
+    let balance = test
+        .world_mut()
+        .run_system_once(|mut wallet: Wallet| wallet.spend(TransactionKind::Purchase, 40, "exact"))
+        .unwrap();
+    assert_eq!(balance, Ok(0));
+    let ledger = test.world().resource::<Ledger>();
+    assert_eq!(ledger.entries.len(), 1);
+    assert_eq!(ledger.entries[0].amount, -40);
+}
+
+#[test]
+fn payouts_scale_with_respect_up_to_the_cap() {
+    let mut test = TestApp::new();
+    set_cash(&mut test, 0);
+
+    assert_eq!(reward(&mut test, 1000, Some(FactionId::NeonJackals)), 1000);
+
+    set_respect(&mut test, FactionId::NeonJackals, 40);
+    assert_eq!(reward(&mut test, 1000, Some(FactionId::NeonJackals)), 1200);
+
+    // The bonus and the penalty are both capped at half the base.
+    set_respect(&mut test, FactionId::NeonJackals, 500);
+    assert_eq!(reward(&mut test, 1000, Some(FactionId::NeonJackals)), 1500);
+    set_respect(&mut test, FactionId::NeonJackals, -500);
+    assert_eq!(reward(&mut test, 1000, Some(FactionId::NeonJackals)), 500);
+
+    // Jobs without a faction pay the base rate regardless.
+    assert_eq!(reward(&mut test, 1000, None), 1000);
+
+    assert_eq!(test.world().resource::<Progression>().cash, 5200);
+    let ledger = test.world().resource::<Ledger>();
+    assert_eq!(ledger.entries.len(), 5);
+    assert_eq!(ledger.entries.back().unwrap().balance, 5200);
+}
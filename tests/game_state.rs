@@ -0,0 +1,91 @@
+mod common;
+
+use asphalt_saints::game::core::{GameState, InSession};
+use asphalt_saints::game::map::{MapPlugin, MapView};
+use asphalt_saints::game::player::Player;
+use asphalt_saints::game::world::District;
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use common::TestApp;
+
+#[test]
+fn boots_into_the_main_menu() {
+    let mut test = TestApp::new();
+    assert_eq!(test.state(), GameState::MainMenu);
+    assert_eq!(test.count::<Player>(), 0);
+}
+
+#[test]
+fn starting_a_game_spawns_the_session() {
+    let mut test = TestApp::new();
+    test.set_state(GameState::InGame);
+
+    assert!(test.world().contains_resource::<State<InSession>>());
+    assert_eq!(test.count::<Player>(), 1);
+    assert_eq!(test.count::<District>(), 1);
+}
+
+#[test]
+fn pausing_freezes_physics_without_ending_the_session() {
+    let mut test = TestApp::new();
+    let player = test.spawn_player();
+    test.press(KeyCode::KeyW);
+    test.tick(10);
+
+    test.set_state(GameState::Paused);
+    assert!(test.world().resource::<Time<Physics>>().is_paused());
+    let paused_at = test.position(player);
+    test.tick(20);
+    test.assert_position(player, paused_at, 1e-4);
+
+    test.set_state(GameState::InGame);
+    assert!(!test.world().resource::<Time<Physics>>().is_paused());
+    test.tick(10);
+    assert!(test.position(player).distance(paused_at) > 0.1);
+}
+
+#[test]
+fn returning_to_the_menu_despawns_the_session() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+
+    test.set_state(GameState::MainMenu);
+
+    assert_eq!(test.count::<Player>(), 0);
+    assert_eq!(test.count::<District>(), 0);
+    // A second session starts fresh rather than stacking on the first.
+    test.set_state(GameState::InGame);
+    assert_eq!(test.count::<Player>(), 1);
+}
+
+#[test]
+fn the_full_map_never_leaves_the_clocks_stopped() {
+    let mut test = TestApp::with(|app| {
+        app.add_plugins(MapPlugin);
+    });
+    test.spawn_player();
+    test.press(KeyCode::KeyM);
+    test.tick(1);
+    test.release(KeyCode::KeyM);
+    test.tick(1);
+    assert!(test.world().resource::<MapView>().fullscreen);
+    assert!(test.world().resource::<Time<Virtual>>().is_paused());
+
+    // Pausing closes the map, so resuming comes back to a running world.
+    test.set_state(GameState::Paused);
+    assert!(!test.world().resource::<MapView>().fullscreen);
+    test.set_state(GameState::InGame);
+    assert!(!test.world().resource::<Time<Virtual>>().is_paused());
+    assert!(!test.world().resource::<Time<Physics>>().is_paused());
+
+    // Quitting with the map open doesn't carry it into the next session.
+    test.press(KeyCode::KeyM);
+    test.tick(1);
+    test.release(KeyCode::KeyM);
+    test.tick(1);
+    test.set_state(GameState::MainMenu);
+    test.set_state(GameState::InGame);
+    assert!(!test.world().resource::<MapView>().fullscreen);
+    assert!(!test.world().resource::<Time<Virtual>>().is_paused());
+    assert!(!test.world().resource::<Time<Physics>>().is_paused());
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use asphalt_saints::game::faction::FactionId;
+use asphalt_saints::game::level::{LevelConfig, Levels, Placement, parse_level};
+use asphalt_saints::game::pickup::PickupEffect;
+use asphalt_saints::game::prefab::PrefabRegistry;
+use asphalt_saints::game::trigger::TriggerVolume;
+use asphalt_saints::game::world::PropKind;
+use common::TestApp;
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("asphalt_levels_{}_{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn triggers(test: &mut TestApp) -> Vec<&'static str> {
+    test.world_mut()
+        .query::<&TriggerVolume>()
+        .iter(test.world())
+        .map(|volume| volume.id)
+        // Shop entrances are triggers too.
+        .filter(|id| *id != "service")
+        .collect()
+}
+
+#[test]
+fn parses_entity_lists() {
+    let text = r#"
+// comment
+[
+    Trigger(id: "dropoff", position: (1, 0, 2), radius: 4), // trailing comment
+    Crate(position: (0, 2, 0), loot: Cash(50)),
+    Fence(position: (3, 0.5, 0)),
+    Gang(faction: NeonJackals, position: (5, 1, 5), boss: true),
+]
+"#;
+    let placed = parse_level(Path::new("test.level"), text, &PrefabRegistry::default()).unwrap();
+    assert_eq!(placed.len(), 4);
+    assert_eq!(placed[1].index, 1);
+    assert!(matches!(
+        &placed[0].placement,
+        Placement::Trigger { volume, radius } if volume.id == "dropoff" && *radius == 4.0
+    ));
+    assert!(matches!(
+        &placed[1].placement,
+        Placement::Prop(PropKind::Crate {
+            loot: Some(PickupEffect::Cash(50))
+        })
+    ));
+    assert!(matches!(
+        placed[3].placement,
+        Placement::Gang {
+            faction: FactionId::NeonJackals,
+            boss: true
+        }
+    ));
+}
+
+#[test]
+fn reports_problems_with_file_line_and_column() {
+    let error = |entity: &str| {
+        let text = format!("[\n    Fence(position: (0, 0, 0)),\n    {entity},\n]\n");
+        parse_level(Path::new("broken.level"), &text, &PrefabRegistry::default())
+            .unwrap_err()
+            .to_string()
+    };
+    assert_eq!(
+        error("Crate(position: (1, 2))"),
+        "broken.level:3:26: Expected a tuple of size 3 but found 2 elements instead"
+    );
+    assert_eq!(
+        error(r#"Hydrant(position: (0, 0, 0), colour: "red")"#),
+        "broken.level:3:40: Unexpected field named `colour` in `Hydrant`, expected `position` instead"
+    );
+    assert_eq!(
+        error("Shop(kind: Garage, position: (0, 0, 0))"),
+        "broken.level:3:43: Unexpected missing field named `name` in `Shop`"
+    );
+    // Checked against the registry once the entry has been read.
+    assert_eq!(
+        error(r#"Prefab(name: "tank", position: (0, 0, 0))"#),
+        "broken.level:4:1: unknown prefab `tank`"
+    );
+}
+
+#[test]
+fn edits_hot_reload_and_broken_edits_keep_the_old_entities() {
+    let dir = scratch_dir("reload");
+    let path = dir.join("test.level");
+    let trigger = |id: &str| format!(r#"[Trigger(id: "{id}", position: (60, 0, 60))]"#);
+    fs::write(&path, trigger("first")).unwrap();
+
+    let mut test = TestApp::new();
+    *test.world_mut().resource_mut::<LevelConfig>() = LevelConfig {
+        directory: dir.clone(),
+        poll_secs: 0.0,
+    };
+    test.spawn_player();
+    assert_eq!(triggers(&mut test), ["first"]);
+
+    // Same length and written straight away, so only the contents differ.
+    fs::write(&path, trigger("other")).unwrap();
+    test.tick(2);
+    assert_eq!(triggers(&mut test), ["other"]);
+
+    fs::write(&path, "[Trigger(position: (60, 0, 60))]").unwrap();
+    test.tick(2);
+    assert_eq!(triggers(&mut test), ["other"]);
+    let levels = test.world().resource::<Levels>();
+    let error = levels.files[0].error.as_ref().unwrap();
+    assert_eq!((error.line, error.column), (1, 31));
+
+    fs::remove_dir_all(dir).unwrap();
+}
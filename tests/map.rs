@@ -0,0 +1,58 @@
+mod common;
+
+use asphalt_saints::game::console::run_console_command;
+use asphalt_saints::game::map::{Blip, BlipKind, MapFeature, MapFeatureKind};
+use asphalt_saints::game::mission::CompleteMission;
+use asphalt_saints::game::trigger::TriggerVolume;
+use bevy::prelude::*;
+use common::TestApp;
+
+/// Ids of the triggers carrying an objective blip.
+fn objectives(test: &mut TestApp) -> Vec<&'static str> {
+    test.world_mut()
+        .query::<(&TriggerVolume, &Blip)>()
+        .iter(test.world())
+        .filter(|(_, blip)| blip.kind == BlipKind::Objective)
+        .map(|(trigger, _)| trigger.id)
+        .collect()
+}
+
+#[test]
+fn the_objective_blip_follows_the_active_mission() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+    assert_eq!(objectives(&mut test), ["choir_wall"]);
+
+    run_console_command(test.world_mut(), "mission start jackal_bait").unwrap();
+    test.tick(1);
+    assert_eq!(objectives(&mut test), ["jackal_slip"]);
+
+    test.send(CompleteMission);
+    test.tick(2);
+    assert!(objectives(&mut test).is_empty());
+}
+
+#[test]
+fn roads_cover_the_avenue_and_street_grid() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+    let roads: Vec<(Vec3, Vec2)> = test
+        .world_mut()
+        .query::<(&Transform, &MapFeature)>()
+        .iter(test.world())
+        .filter(|(_, feature)| feature.kind == MapFeatureKind::Road)
+        .map(|(transform, feature)| (transform.translation, feature.size))
+        .collect();
+
+    let on_road = |point: Vec2| {
+        roads.iter().any(|(center, size)| {
+            let local = (point - center.xz()).abs();
+            local.x <= size.x * 0.5 && local.y <= size.y * 0.5
+        })
+    };
+    // Down the avenue, along a chunk edge, and not in the middle of a block.
+    assert!(on_road(Vec2::new(0.0, 200.0)));
+    assert!(on_road(Vec2::new(128.0, -90.0)));
+    assert!(on_road(Vec2::new(-150.0, 64.0)));
+    assert!(!on_road(Vec2::new(96.0, 96.0)));
+}
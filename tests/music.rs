@@ -0,0 +1,57 @@
+mod common;
+
+use asphalt_saints::game::audio::{AudioPlugin, SoundCategory, SoundVoice};
+use asphalt_saints::game::console::run_console_command;
+use asphalt_saints::game::music::{MusicPlugin, Radio};
+use asphalt_saints::game::ui::Toast;
+use bevy::prelude::*;
+use common::TestApp;
+
+/// Paths of the music voices that aren't fading out yet, newest last.
+fn music(test: &mut TestApp) -> Vec<String> {
+    let mut voices: Vec<(u64, String)> = test
+        .world_mut()
+        .query::<(&AudioPlayer, &SoundVoice)>()
+        .iter(test.world())
+        .filter(|(_, voice)| voice.category == SoundCategory::Music)
+        .filter_map(|(player, voice)| Some((voice.serial, player.0.path()?.to_string())))
+        .collect();
+    voices.sort();
+    voices.into_iter().map(|(_, path)| path).collect()
+}
+
+#[test]
+fn driving_tunes_into_the_radio_on_a_shared_clock() {
+    let mut test = TestApp::with(|app| {
+        app.init_asset::<AudioSource>()
+            .add_plugins((AudioPlugin, MusicPlugin));
+    });
+    test.spawn_player();
+    assert!(music(&mut test).last().unwrap().starts_with("music/"));
+
+    run_console_command(test.world_mut(), "spawn vehicle sedan").unwrap();
+    test.tick(1);
+    test.tap(KeyCode::KeyF);
+    let playing = music(&mut test);
+    assert!(
+        playing.last().unwrap().starts_with("radio/rust_fm/"),
+        "{playing:?}"
+    );
+
+    // Every station keeps broadcasting, so Neon is into its DJ link by now.
+    test.world_mut().resource_mut::<Radio>().clock = 245.0;
+    test.tap(KeyCode::KeyR);
+    let toasts: Vec<String> = test
+        .messages::<Toast>()
+        .into_iter()
+        .map(|toast| toast.text)
+        .collect();
+    assert!(toasts.contains(&"Neon 88.1".to_string()), "{toasts:?}");
+    assert_eq!(
+        music(&mut test).last().map(String::as_str),
+        Some("radio/neon/dj_midnight.ogg")
+    );
+
+    test.tap(KeyCode::KeyF);
+    assert!(music(&mut test).last().unwrap().starts_with("music/"));
+}
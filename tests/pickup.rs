@@ -0,0 +1,155 @@
+mod common;
+
+use asphalt_saints::game::pickup::{
+    Collectibles, DropPickup, Pickup, PickupConfig, PickupEffect, PickupSpawner,
+};
+use asphalt_saints::game::progression::Progression;
+use bevy::pbr::{MeshMaterial3d, StandardMaterial};
+use bevy::prelude::*;
+use common::TestApp;
+
+fn cash(test: &TestApp) -> u32 {
+    test.world().resource::<Progression>().cash
+}
+
+fn pickups(test: &mut TestApp) -> Vec<Entity> {
+    test.world_mut()
+        .query_filtered::<Entity, With<Pickup>>()
+        .iter(test.world())
+        .collect()
+}
+
+#[test]
+fn pickups_near_the_player_drift_in_and_are_collected() {
+    let mut test = TestApp::new();
+    let player = test.spawn_player();
+    let position = test.position(player);
+    let magnet = test.world().resource::<PickupConfig>().magnet_radius;
+    let before = cash(&test);
+
+    test.send(DropPickup {
+        effect: PickupEffect::Cash(25),
+        position: position + Vec3::X * (magnet - 0.5),
+    });
+    test.send(DropPickup {
+        effect: PickupEffect::Cash(40),
+        position: position + Vec3::X * (magnet + 5.0),
+    });
+    test.tick(60);
+
+    assert_eq!(cash(&test), before + 25);
+    assert_eq!(pickups(&mut test).len(), 1, "the far pickup stays put");
+}
+
+#[test]
+fn pickups_of_one_colour_share_a_material() {
+    let mut test = TestApp::new();
+    let player = test.spawn_player();
+    let far = test.position(player) + Vec3::X * 20.0;
+    for effect in [
+        PickupEffect::Cash(10),
+        PickupEffect::Cash(20),
+        PickupEffect::Health(5.0),
+    ] {
+        test.send(DropPickup {
+            effect,
+            position: far,
+        });
+    }
+    test.tick(2);
+
+    let materials: Vec<(bool, AssetId<StandardMaterial>)> = test
+        .world_mut()
+        .query::<(&Pickup, &MeshMaterial3d<StandardMaterial>)>()
+        .iter(test.world())
+        .map(|(pickup, material)| {
+            (
+                matches!(pickup.effect, PickupEffect::Cash(_)),
+                material.0.id(),
+            )
+        })
+        .collect();
+    let cash: Vec<_> = materials.iter().filter(|(cash, _)| *cash).collect();
+    let health: Vec<_> = materials.iter().filter(|(cash, _)| !*cash).collect();
+    assert_eq!(cash.len(), 2);
+    assert_eq!(cash[0].1, cash[1].1);
+    assert_ne!(cash[0].1, health[0].1);
+}
+
+#[test]
+fn spawners_restock_after_their_timer() {
+    let mut test = TestApp::new();
+    let player = test.spawn_player();
+    let home = test.position(player);
+    let spot = home + Vec3::X * 10.0;
+    test.world_mut().spawn((
+        PickupSpawner::new(PickupEffect::Cash(50), Some(2.0)),
+        Transform::from_translation(spot),
+    ));
+    test.tick(2);
+    let stocked = |test: &mut TestApp| {
+        let spots: Vec<Vec3> = test
+            .world_mut()
+            .query_filtered::<&Transform, With<Pickup>>()
+            .iter(test.world())
+            .map(|transform| transform.translation)
+            .collect();
+        spots.iter().any(|position| position.distance(spot) < 0.5)
+    };
+    assert!(stocked(&mut test));
+
+    let before = cash(&test);
+    test.teleport(player, spot);
+    test.tick(2);
+    test.teleport(player, home);
+    assert_eq!(cash(&test), before + 50);
+    assert!(!stocked(&mut test));
+
+    // Two seconds at 60 frames a second.
+    test.tick(100);
+    assert!(!stocked(&mut test), "restocked early");
+    test.tick(30);
+    assert!(stocked(&mut test), "never restocked");
+}
+
+#[test]
+fn the_last_token_pays_the_completion_bonus() {
+    let mut test = TestApp::new();
+    // Everything but the pier, which sits out past the old ground edge.
+    {
+        let mut collectibles = test.world_mut().resource_mut::<Collectibles>();
+        let found = collectibles
+            .tokens
+            .iter()
+            .map(|(id, _)| *id)
+            .filter(|id| *id != "token_pier")
+            .collect();
+        collectibles.found = found;
+    }
+    let player = test.spawn_player();
+    let (reward, bonus, pier) = {
+        let collectibles = test.world().resource::<Collectibles>();
+        let pier = collectibles
+            .tokens
+            .iter()
+            .find(|(id, _)| *id == "token_pier")
+            .unwrap()
+            .1;
+        (
+            collectibles.reward_each,
+            collectibles.completion_bonus,
+            pier,
+        )
+    };
+
+    // There's ground to stand on next to it.
+    test.teleport(player, pier + Vec3::new(4.0, 0.4, 0.0));
+    test.tick(60);
+    assert!(test.position(player).y > 0.0, "fell through the world");
+
+    let before = cash(&test);
+    test.teleport(player, pier);
+    test.tick(2);
+    assert_eq!(cash(&test), before + reward + bonus);
+    assert!(test.world().resource::<Collectibles>().is_complete());
+}
@@ -0,0 +1,184 @@
+mod common;
+
+use asphalt_saints::game::physics::GameLayer;
+use asphalt_saints::game::player::{GroundContact, Player, PlayerFacing};
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use common::TestApp;
+
+const LEDGE_SIZE: Vec3 = Vec3::new(6.0, 1.0, 6.0);
+
+fn speed(test: &mut TestApp) -> f32 {
+    let player = test.player();
+    let velocity = test.world().get::<LinearVelocity>(player).unwrap();
+    Vec2::new(velocity.x, velocity.z).length()
+}
+
+fn vertical_speed(test: &mut TestApp) -> f32 {
+    let player = test.player();
+    test.world().get::<LinearVelocity>(player).unwrap().y
+}
+
+fn grounded(test: &mut TestApp) -> bool {
+    let player = test.player();
+    test.world().get::<GroundContact>(player).unwrap().grounded
+}
+
+/// Builds a box beside the open lot to step off of.
+fn build_ledge(test: &mut TestApp) -> Vec3 {
+    let player = test.player();
+    let floor = test.position(player);
+    let ledge = floor.with_y(0.0) + Vec3::new(20.0, LEDGE_SIZE.y * 0.5, 0.0);
+    test.world_mut().spawn((
+        RigidBody::Static,
+        Collider::cuboid(LEDGE_SIZE.x, LEDGE_SIZE.y, LEDGE_SIZE.z),
+        GameLayer::Static.layers(),
+        Transform::from_translation(ledge),
+    ));
+    ledge.with_y(floor.y + LEDGE_SIZE.y)
+}
+
+/// Stands the player on the ledge and returns where they stand.
+fn stand_on(test: &mut TestApp, ledge: Vec3) -> Vec3 {
+    let player = test.player();
+    test.teleport(player, ledge + Vec3::Y * 0.1);
+    test.tick(1);
+    for _ in 0..60 {
+        test.tick(1);
+        if grounded(test) {
+            break;
+        }
+    }
+    assert!(grounded(test), "never landed on the ledge");
+    test.position(player)
+}
+
+/// Puts the player in the air just past the ledge, as if they'd walked off.
+fn step_off(test: &mut TestApp, on_ledge: Vec3) {
+    let player = test.player();
+    test.teleport(player, on_ledge + Vec3::X * (LEDGE_SIZE.x * 0.5 + 1.0));
+    test.tick(1);
+    assert!(!grounded(test));
+}
+
+#[test]
+fn jumping_just_after_walking_off_a_ledge_still_counts() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+    let ledge = build_ledge(&mut test);
+
+    let on_ledge = stand_on(&mut test, ledge);
+    step_off(&mut test, on_ledge);
+    test.tap(KeyCode::Enter);
+    assert!(vertical_speed(&mut test) > 4.0, "no coyote jump");
+
+    // Too long after leaving the ledge and the press does nothing.
+    test.tick(120);
+    let on_ledge = stand_on(&mut test, ledge);
+    step_off(&mut test, on_ledge);
+    test.tick(12);
+    test.tap(KeyCode::Enter);
+    assert!(vertical_speed(&mut test) < 0.0, "jumped in mid-air");
+}
+
+#[test]
+fn jump_pressed_just_before_landing_fires_on_touchdown() {
+    let mut test = TestApp::new();
+    let player = test.spawn_player();
+    let floor = test.position(player);
+    let drop = |test: &mut TestApp| {
+        test.teleport(player, floor + Vec3::Y * 2.0);
+        test.tick(1);
+    };
+
+    // How many frames a drop takes to land.
+    drop(&mut test);
+    let mut fall = 0;
+    while !grounded(&mut test) && fall < 120 {
+        test.tick(1);
+        fall += 1;
+    }
+    assert!(fall > 10, "landed after {fall} frames");
+
+    // Pressed a few frames early: the player bounces straight back up.
+    drop(&mut test);
+    test.tick(fall - 4);
+    test.tap(KeyCode::Enter);
+    test.tick(4);
+    assert!(vertical_speed(&mut test) > 4.0, "buffered jump was dropped");
+
+    // Pressed long before landing: it's forgotten by then.
+    test.tick(120);
+    drop(&mut test);
+    test.tick(fall - 20);
+    test.tap(KeyCode::Enter);
+    test.tick(20);
+    assert!(
+        vertical_speed(&mut test).abs() < 1.0,
+        "stale press still jumped"
+    );
+}
+
+#[test]
+fn forward_moves_along_facing() {
+    let mut test = TestApp::new();
+    let player = test.spawn_player();
+    let start = test.position(player);
+
+    test.press(KeyCode::KeyW);
+    test.tick(15);
+
+    let end = test.position(player);
+    // Facing starts at yaw 0, which is -Z.
+    assert!(end.z < start.z - 0.5, "moved from {start} to {end}");
+    test.assert_position(player, Vec3::new(start.x, end.y, end.z), 0.2);
+}
+
+#[test]
+fn walk_speed_is_capped_and_sprint_is_faster() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+
+    test.press(KeyCode::KeyW);
+    test.tick(40);
+    let walking = speed(&mut test);
+    assert!((walking - 7.5).abs() < 0.3, "walking at {walking}");
+
+    test.press(KeyCode::ShiftLeft);
+    test.tick(40);
+    let sprinting = speed(&mut test);
+    assert!(sprinting > walking + 2.0, "sprinting at {sprinting}");
+}
+
+#[test]
+fn letting_go_brakes_to_a_stop() {
+    let mut test = TestApp::new();
+    let player = test.spawn_player();
+
+    test.press(KeyCode::KeyW);
+    test.tick(30);
+    test.release(KeyCode::KeyW);
+    test.tick(30);
+
+    assert!(speed(&mut test) < 0.05);
+    let stopped = test.position(player);
+    test.tick(10);
+    test.assert_position(player, stopped, 0.05);
+}
+
+#[test]
+fn steering_turns_the_pawn() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+
+    test.press(KeyCode::KeyD);
+    test.tick(10);
+
+    let yaw = test
+        .world_mut()
+        .query_filtered::<&PlayerFacing, With<Player>>()
+        .single(test.world())
+        .unwrap()
+        .yaw;
+    assert!(yaw > 0.5, "yaw {yaw}");
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use std::path::Path;
+
+use asphalt_saints::game::ai::AiRole;
+use asphalt_saints::game::console::run_console_command;
+use asphalt_saints::game::destructible::Destructible;
+use asphalt_saints::game::level::{Placement, parse_level};
+use asphalt_saints::game::prefab::PrefabRegistry;
+use asphalt_saints::game::vehicle::{Vehicle, VehiclePaint};
+use bevy::pbr::{MeshMaterial3d, StandardMaterial};
+use bevy::prelude::*;
+use common::TestApp;
+
+fn cops(test: &mut TestApp) -> Vec<(Handle<Mesh>, Handle<StandardMaterial>)> {
+    test.world_mut()
+        .query::<(&AiRole, &Mesh3d, &MeshMaterial3d<StandardMaterial>)>()
+        .iter(test.world())
+        .filter(|(role, _, _)| matches!(role, AiRole::Cop))
+        .map(|(_, mesh, material)| (mesh.0.clone(), material.0.clone()))
+        .collect()
+}
+
+#[test]
+fn console_spawns_prefabs_with_shared_visuals() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+
+    run_console_command(test.world_mut(), "spawn cop").unwrap();
+    run_console_command(test.world_mut(), "spawn cop").unwrap();
+    test.tick(1);
+
+    let cops = cops(&mut test);
+    assert_eq!(cops.len(), 2);
+    assert_eq!(cops[0], cops[1]);
+}
+
+#[test]
+fn recoloured_instances_share_a_material_per_colour() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+    run_console_command(test.world_mut(), "spawn vehicle sedan").unwrap();
+    test.tick(1);
+    run_console_command(test.world_mut(), "spawn vehicle sedan").unwrap();
+    test.tick(1);
+
+    let materials = |test: &mut TestApp| -> Vec<(Entity, Handle<StandardMaterial>)> {
+        test.world_mut()
+            .query_filtered::<(Entity, &MeshMaterial3d<StandardMaterial>), With<Vehicle>>()
+            .iter(test.world())
+            .map(|(entity, material)| (entity, material.0.clone()))
+            .collect()
+    };
+    let cars = materials(&mut test);
+    assert_eq!(cars.len(), 2);
+    assert_eq!(cars[0].1, cars[1].1);
+
+    // Repainting one leaves the other alone.
+    let red = Color::srgb(0.8, 0.1, 0.1);
+    test.world_mut()
+        .get_mut::<VehiclePaint>(cars[0].0)
+        .unwrap()
+        .0 = red;
+    test.tick(1);
+    let repainted = materials(&mut test);
+    let (first, second) = (&repainted[0].1, &repainted[1].1);
+    assert_ne!(first, second);
+    let assets = test.world().resource::<Assets<StandardMaterial>>();
+    let color_of = |handle| assets.get(handle).unwrap().base_color;
+    let (painted, untouched) = if color_of(first) == red {
+        (first, second)
+    } else {
+        (second, first)
+    };
+    assert_eq!(color_of(painted), red);
+    assert_ne!(color_of(untouched), red);
+}
+
+#[test]
+fn crates_are_as_big_as_they_look_and_break() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+    let crate_ = test
+        .world()
+        .resource::<PrefabRegistry>()
+        .get("crate")
+        .unwrap();
+    let collider = crate_.collider.unwrap().size();
+    assert_eq!(crate_.visual.unwrap().shape.size(), collider);
+
+    run_console_command(test.world_mut(), "spawn crate").unwrap();
+    test.tick(1);
+    // Streamed crates are renamed after their chunk prop; this one isn't.
+    let destructible = test
+        .world_mut()
+        .query::<(&Destructible, &Name)>()
+        .iter(test.world())
+        .find(|(_, name)| name.as_str() == "crate")
+        .map(|(destructible, _)| destructible.size);
+    assert_eq!(destructible, Some(collider));
+}
+
+#[test]
+fn unknown_prefabs_are_rejected() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+
+    let err = run_console_command(test.world_mut(), "spawn tank").unwrap_err();
+    assert!(err.to_string().contains("unknown prefab `tank`"));
+}
+
+#[test]
+fn levels_place_any_registered_prefab() {
+    let test = TestApp::new();
+    let registry = test.world().resource::<PrefabRegistry>();
+    for name in ["player", "cop", "sedan", "crate", "street_lamp"] {
+        assert!(registry.contains(name), "`{name}` isn't registered");
+    }
+
+    let placed = parse_level(
+        Path::new("test.level"),
+        r#"[Prefab(name: "cop", position: (1, 1, 1))]"#,
+        registry,
+    )
+    .unwrap();
+    assert!(matches!(placed[0].placement, Placement::Prefab("cop")));
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use asphalt_saints::game::core::{GameState, InSession};
+use asphalt_saints::game::mission::{CompleteMission, MissionLog};
+use asphalt_saints::game::progression::Progression;
+use asphalt_saints::game::save::{LoadGame, SaveConfig, SaveGame};
+use bevy::prelude::*;
+use common::TestApp;
+
+/// Points saves at a directory of their own so tests don't share slots.
+fn use_scratch_saves(test: &mut TestApp, name: &str) {
+    let directory = std::env::temp_dir().join(format!("asphalt_saints_{name}"));
+    let _ = std::fs::remove_dir_all(&directory);
+    test.world_mut().resource_mut::<SaveConfig>().directory = directory;
+}
+
+#[test]
+fn loading_from_pause_rebuilds_the_session() {
+    let mut test = TestApp::new();
+    use_scratch_saves(&mut test, "load_from_pause");
+    test.spawn_player();
+    test.world_mut().resource_mut::<Progression>().cash = 500;
+    test.send(SaveGame { slot: 0 });
+    test.tick(1);
+
+    test.world_mut().resource_mut::<Progression>().cash = 10;
+    test.world_mut()
+        .resource_mut::<MissionLog>()
+        .completed
+        .push("street_scramble");
+    let leftover = test.world_mut().spawn(DespawnOnExit(InSession)).id();
+
+    test.set_state(GameState::Paused);
+    test.send(LoadGame { slot: Some(0) });
+    test.tick(4);
+
+    assert_eq!(test.state(), GameState::InGame);
+    assert!(test.world().get_entity(leftover).is_err());
+    assert_eq!(test.world().resource::<Progression>().cash, 500);
+    let log = test.world().resource::<MissionLog>();
+    assert!(log.completed.is_empty());
+    assert_eq!(log.active.as_ref().map(|m| m.id), Some("street_scramble"));
+}
+
+#[test]
+fn finished_missions_stay_finished_after_loading() {
+    let mut test = TestApp::new();
+    use_scratch_saves(&mut test, "finished_missions");
+    test.spawn_player();
+    test.send(CompleteMission);
+    test.tick(1);
+    test.send(SaveGame { slot: 0 });
+    test.tick(1);
+
+    test.set_state(GameState::Paused);
+    test.send(LoadGame { slot: Some(0) });
+    test.tick(4);
+
+    assert_eq!(test.state(), GameState::InGame);
+    let log = test.world().resource::<MissionLog>();
+    assert_eq!(log.completed, ["street_scramble"]);
+    // The intro isn't handed out again.
+    assert!(log.active.is_none());
+}
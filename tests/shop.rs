@@ -0,0 +1,71 @@
+mod common;
+
+use asphalt_saints::game::console::run_console_command;
+use asphalt_saints::game::progression::Progression;
+use asphalt_saints::game::shop::{Garage, ServicePanel};
+use asphalt_saints::game::ui::Toast;
+use asphalt_saints::game::vehicle::{Driving, Vehicle};
+use bevy::prelude::*;
+use common::TestApp;
+
+const WEAPON_STORE: Vec3 = Vec3::new(30.0, 1.2, -20.0);
+const GARAGE: Vec3 = Vec3::new(-30.0, 0.7, 34.0);
+
+fn vehicles(test: &mut TestApp) -> Vec<Entity> {
+    test.world_mut()
+        .query_filtered::<Entity, With<Vehicle>>()
+        .iter(test.world())
+        .collect()
+}
+
+#[test]
+fn garage_stores_the_vehicle_being_driven_and_hands_it_back() {
+    let mut test = TestApp::new();
+    let player = test.spawn_player();
+    run_console_command(test.world_mut(), "spawn vehicle sedan").unwrap();
+    test.tick(1);
+    test.tap(KeyCode::KeyF);
+    let car = test.world().get::<Driving>(player).expect("not driving").0;
+
+    test.teleport(car, GARAGE);
+    test.tick(5);
+    test.tap(KeyCode::KeyE);
+    assert!(test.world().resource::<ServicePanel>().open);
+    test.tap(KeyCode::Digit1);
+    test.tick(1);
+
+    assert_eq!(test.world().resource::<Garage>().stored.len(), 1);
+    assert!(vehicles(&mut test).is_empty());
+    assert!(test.world().get::<Driving>(player).is_none());
+
+    test.world_mut().resource_mut::<ServicePanel>().open = true;
+    test.tap(KeyCode::Digit2);
+    test.tick(1);
+    assert!(test.world().resource::<Garage>().stored.is_empty());
+    assert_eq!(vehicles(&mut test).len(), 1);
+}
+
+#[test]
+fn failed_purchases_explain_why() {
+    let mut test = TestApp::new();
+    let player = test.spawn_player();
+    test.world_mut().resource_mut::<Progression>().cash = 100;
+    test.teleport(player, WEAPON_STORE);
+    test.tick(5);
+    test.tap(KeyCode::KeyE);
+    assert!(test.world().resource::<ServicePanel>().open);
+
+    test.press(KeyCode::Digit2);
+    test.tick(1);
+
+    let toasts: Vec<String> = test
+        .messages::<Toast>()
+        .into_iter()
+        .map(|toast| toast.text)
+        .collect();
+    assert!(
+        toasts.contains(&"Can't buy SMG: cannot spend $1500 with a balance of $100".to_string()),
+        "{toasts:?}"
+    );
+    assert_eq!(test.world().resource::<Progression>().cash, 100);
+}
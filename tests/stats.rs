@@ -0,0 +1,46 @@
+mod common;
+
+use asphalt_saints::game::combat::Health;
+use asphalt_saints::game::core::GameState;
+use asphalt_saints::game::stats::{ModifierSource, Stat, StatModifier, Stats};
+use common::TestApp;
+
+const ADRENALINE: ModifierSource = ModifierSource::Drug("adrenaline");
+const TOUGHNESS: ModifierSource = ModifierSource::Skill("toughness");
+
+#[test]
+fn timed_modifiers_expire_and_the_stat_is_recomputed() {
+    let mut test = TestApp::new();
+    test.set_state(GameState::InGame);
+    let mut stats = Stats::default().with_base(Stat::MaxHealth, 100.0);
+    stats.add_modifier(StatModifier::add(Stat::MaxHealth, ADRENALINE, 50.0).for_secs(0.5));
+    let entity = test.world_mut().spawn((stats, Health::new(100.0))).id();
+    test.tick(1);
+
+    assert_eq!(test.world().get::<Health>(entity).unwrap().max, 150.0);
+
+    // About two thirds of a second at the test frame rate.
+    test.tick(40);
+    let stats = test.world().get::<Stats>(entity).unwrap();
+    assert_eq!(stats.get(Stat::MaxHealth), 100.0);
+    let health = test.world().get::<Health>(entity).unwrap();
+    assert_eq!(health.max, 100.0);
+    assert_eq!(health.current, 100.0);
+}
+
+#[test]
+fn modifiers_stack_across_sources_and_refresh_within_one() {
+    let mut stats = Stats::default().with_base(Stat::WalkSpeed, 5.0);
+    stats.add_modifier(StatModifier::add(Stat::WalkSpeed, TOUGHNESS, 1.0));
+    stats.add_modifier(StatModifier::add(Stat::WalkSpeed, ADRENALINE, 2.0));
+    stats.add_modifier(StatModifier::mul(Stat::WalkSpeed, ADRENALINE, 1.5));
+    assert_eq!(stats.get(Stat::WalkSpeed), (5.0 + 1.0 + 2.0) * 1.5);
+
+    // The same source on the same stat replaces rather than stacks.
+    stats.replace_modifier(StatModifier::add(Stat::WalkSpeed, TOUGHNESS, 3.0));
+    assert_eq!(stats.get(Stat::WalkSpeed), (5.0 + 3.0 + 2.0) * 1.5);
+
+    stats.remove_where(|source| source == ADRENALINE);
+    assert_eq!(stats.get(Stat::WalkSpeed), 8.0);
+    assert_eq!(stats.base(Stat::WalkSpeed), 5.0);
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use asphalt_saints::game::combat::DamageEvent;
+use asphalt_saints::game::destructible::Destructible;
+use asphalt_saints::game::streaming::WorldChunks;
+use bevy::prelude::*;
+use common::{OPEN_LOT, TestApp};
+
+fn wait_until_loaded(test: &mut TestApp, coord: IVec2) {
+    for _ in 0..60 {
+        if test.world().resource::<WorldChunks>().is_loaded(coord) {
+            // Let the spawn commands apply.
+            test.tick(1);
+            return;
+        }
+        test.tick(1);
+    }
+    panic!("chunk {coord} never loaded");
+}
+
+fn destructibles_in(test: &mut TestApp, coord: IVec2) -> Vec<Entity> {
+    let entities: Vec<Entity> = test
+        .world_mut()
+        .query_filtered::<Entity, With<Destructible>>()
+        .iter(test.world())
+        .collect();
+    let chunks = test.world().resource::<WorldChunks>();
+    entities
+        .into_iter()
+        .filter(|entity| {
+            chunks
+                .prop_of(*entity)
+                .is_some_and(|(chunk, _)| chunk == coord)
+        })
+        .collect()
+}
+
+#[test]
+fn chunks_unload_only_past_the_hysteresis_band() {
+    let mut test = TestApp::new();
+    let player = test.spawn_player();
+    let near = IVec2::new(0, 0);
+    wait_until_loaded(&mut test, near);
+
+    // Past the load distance but inside the unload distance: stays.
+    test.teleport(player, OPEN_LOT.with_z(150.0));
+    test.tick(5);
+    assert!(test.world().resource::<WorldChunks>().is_loaded(near));
+
+    test.teleport(player, OPEN_LOT.with_z(200.0));
+    test.tick(5);
+    assert!(!test.world().resource::<WorldChunks>().is_loaded(near));
+}
+
+#[test]
+fn destroyed_props_stay_destroyed_after_a_reload() {
+    let mut test = TestApp::new();
+    let player = test.spawn_player();
+    let origin = IVec2::new(0, -1);
+    test.teleport(player, OPEN_LOT.with_z(30.0));
+    wait_until_loaded(&mut test, origin);
+    let before = destructibles_in(&mut test, origin);
+    assert!(!before.is_empty());
+
+    test.send(DamageEvent {
+        entity: before[0],
+        amount: 10_000.0,
+        source: None,
+    });
+    test.tick(2);
+    assert_eq!(destructibles_in(&mut test, origin).len(), before.len() - 1);
+
+    test.teleport(player, OPEN_LOT.with_z(230.0));
+    test.tick(5);
+    assert!(!test.world().resource::<WorldChunks>().is_loaded(origin));
+
+    test.teleport(player, OPEN_LOT.with_z(30.0));
+    wait_until_loaded(&mut test, origin);
+    assert_eq!(destructibles_in(&mut test, origin).len(), before.len() - 1);
+}
+
+#[test]
+fn the_ground_reaches_the_edge_of_the_streamed_world() {
+    let mut test = TestApp::new();
+    let player = test.spawn_player();
+    // Outside the old half-sized collider but still inside the world.
+    let edge = Vec3::new(-230.0, 1.2, 230.0);
+    test.teleport(player, edge);
+    test.tick(120);
+    assert!(test.position(player).y > 0.0, "fell through the world");
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use asphalt_saints::game::console::run_console_command;
+use asphalt_saints::game::core::GameState;
+use asphalt_saints::game::time_of_day::WorldClock;
+use common::TestApp;
+
+fn hour(test: &TestApp) -> f32 {
+    test.world().resource::<WorldClock>().hour
+}
+
+#[test]
+fn clock_advances_in_game_and_stops_while_paused() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+    let start = hour(&test);
+    test.tick(60);
+    let running = hour(&test);
+    assert!(running > start);
+
+    test.set_state(GameState::Paused);
+    test.tick(60);
+    assert_eq!(hour(&test), running);
+}
+
+#[test]
+fn clock_wraps_into_the_next_day() {
+    let mut clock = WorldClock {
+        hour: 23.5,
+        ..Default::default()
+    };
+    clock.advance(clock.seconds_per_hour);
+    assert_eq!(clock.day, 2);
+    assert!((clock.hour - 0.5).abs() < 1e-4);
+    assert!(clock.is_night());
+    assert!(clock.is_between(22.0, 2.0));
+    assert!(!clock.is_between(8.0, 22.0));
+}
+
+#[test]
+fn time_command_sets_the_hour() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+    let output = run_console_command(test.world_mut(), "time 21.5").unwrap();
+    assert_eq!(output, "day 1 21:30");
+    assert!(test.world().resource::<WorldClock>().is_night());
+}
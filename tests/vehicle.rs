@@ -0,0 +1,102 @@
+mod common;
+
+use asphalt_saints::game::console::run_console_command;
+use asphalt_saints::game::vehicle::{Driving, Vehicle};
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use common::TestApp;
+
+fn vehicle(test: &mut TestApp) -> Entity {
+    test.world_mut()
+        .query_filtered::<Entity, With<Vehicle>>()
+        .single(test.world())
+        .expect("expected exactly one vehicle")
+}
+
+/// Flat direction the vehicle's nose points.
+fn heading(test: &TestApp, vehicle: Entity) -> Vec3 {
+    let rotation = test.world().get::<Transform>(vehicle).unwrap().rotation;
+    (rotation * Vec3::NEG_Z).with_y(0.0).normalize()
+}
+
+#[test]
+fn player_drives_a_vehicle_and_gets_out_beside_it() {
+    let mut test = TestApp::new();
+    let player = test.spawn_player();
+    run_console_command(test.world_mut(), "spawn vehicle sedan").unwrap();
+    test.tick(1);
+    let car = vehicle(&mut test);
+
+    test.tap(KeyCode::KeyF);
+    assert_eq!(
+        test.world().get::<Driving>(player).map(|driving| driving.0),
+        Some(car)
+    );
+    assert!(test.world().get::<RigidBodyDisabled>(player).is_some());
+
+    let start = test.position(car);
+    let facing = heading(&test, car);
+    test.press(KeyCode::KeyW);
+    test.tick(60);
+    test.release(KeyCode::KeyW);
+    test.tick(1);
+    let parked = test.position(car);
+    let travel = (parked - start).with_y(0.0);
+    assert!(travel.length() > 2.0, "{start} -> {parked}");
+    assert!(
+        travel.normalize().dot(facing) > 0.95,
+        "drove {travel} while facing {facing}"
+    );
+    test.assert_position(player, parked, 0.5);
+
+    test.tap(KeyCode::KeyF);
+    test.tick(1);
+    assert!(test.world().get::<Driving>(player).is_none());
+    assert!(test.world().get::<RigidBodyDisabled>(player).is_none());
+    let beside = test.position(player).distance(test.position(car));
+    assert!((1.5..4.0).contains(&beside), "{beside}");
+}
+
+#[test]
+fn steering_turns_the_vehicle_and_it_drives_where_it_points() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+    run_console_command(test.world_mut(), "spawn vehicle sedan").unwrap();
+    test.tick(1);
+    let car = vehicle(&mut test);
+    test.tap(KeyCode::KeyF);
+
+    let facing = heading(&test, car);
+    test.press(KeyCode::KeyW);
+    test.press(KeyCode::KeyD);
+    test.tick(60);
+    test.release(KeyCode::KeyD);
+    test.tick(1);
+    let turned = heading(&test, car);
+    // Turning right from above is clockwise, i.e. a negative cross product.
+    assert!(facing.cross(turned).y < -0.1, "{facing} -> {turned}");
+
+    let start = test.position(car);
+    test.tick(30);
+    let travel = (test.position(car) - start).with_y(0.0);
+    let now = heading(&test, car);
+    assert!(
+        travel.normalize().dot(now) > 0.95,
+        "drove {travel} while facing {now}"
+    );
+}
+
+#[test]
+fn vehicles_out_of_reach_stay_parked() {
+    let mut test = TestApp::new();
+    let player = test.spawn_player();
+    run_console_command(test.world_mut(), "spawn vehicle truck").unwrap();
+    test.tick(1);
+    let truck = vehicle(&mut test);
+    let position = test.position(player);
+    test.teleport(truck, position + Vec3::new(12.0, 0.0, 0.0));
+    test.tick(1);
+
+    test.tap(KeyCode::KeyF);
+    assert!(test.world().get::<Driving>(player).is_none());
+}
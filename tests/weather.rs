@@ -0,0 +1,88 @@
+mod common;
+
+use asphalt_saints::game::ai::AiRole;
+use asphalt_saints::game::console::run_console_command;
+use asphalt_saints::game::weather::{Weather, WeatherKind, WeatherSchedule, WetSurface};
+use avian3d::prelude::*;
+use common::TestApp;
+
+fn pedestrians(test: &mut TestApp) -> usize {
+    test.world_mut()
+        .query::<&AiRole>()
+        .iter(test.world())
+        .filter(|role| matches!(role, AiRole::Pedestrian))
+        .count()
+}
+
+#[test]
+fn schedule_wraps_past_midnight() {
+    let schedule = WeatherSchedule::default();
+    assert_eq!(schedule.at(2.0), WeatherKind::Clear);
+    assert_eq!(schedule.at(5.0), WeatherKind::Fog);
+    assert_eq!(schedule.at(18.5), WeatherKind::Storm);
+}
+
+#[test]
+fn rain_blends_in_and_wets_the_ground() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+    run_console_command(test.world_mut(), "weather rain").unwrap();
+    test.tick(1);
+    let partway = test.world().resource::<Weather>().effects.grip;
+    assert!(partway < 1.0 && partway > WeatherKind::Rain.effects().grip);
+
+    test.tick(60 * 60);
+    let weather = test.world().resource::<Weather>().clone();
+    // The blend settles on the target exactly rather than creeping forever.
+    assert_eq!(weather.effects, WeatherKind::Rain.effects());
+    let (surface, friction) = test
+        .world_mut()
+        .query::<(&WetSurface, &Friction)>()
+        .single(test.world())
+        .unwrap();
+    assert!(friction.dynamic_coefficient < surface.dry);
+}
+
+#[test]
+fn storms_thin_out_the_crowd() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+    test.tick(30);
+    let clear = pedestrians(&mut test);
+
+    test.world_mut()
+        .resource_mut::<Weather>()
+        .set_immediate(WeatherKind::Storm);
+    test.world_mut().resource_mut::<Weather>().console_forced = Some(WeatherKind::Storm);
+    test.tick(30);
+    assert!(pedestrians(&mut test) < clear);
+}
+
+#[test]
+fn missions_force_their_weather() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+    run_console_command(test.world_mut(), "mission start jackal_bait").unwrap();
+    test.tick(1);
+    assert_eq!(
+        test.world().resource::<Weather>().forced(),
+        Some(WeatherKind::Storm)
+    );
+}
+
+#[test]
+fn the_console_outranks_mission_weather() {
+    let mut test = TestApp::new();
+    test.spawn_player();
+    run_console_command(test.world_mut(), "weather fog").unwrap();
+    run_console_command(test.world_mut(), "mission start jackal_bait").unwrap();
+    test.tick(1);
+    let weather = test.world().resource::<Weather>();
+    assert_eq!(weather.mission_forced, Some(WeatherKind::Storm));
+    assert_eq!(weather.kind, WeatherKind::Fog);
+
+    // Handing control back lets the mission's storm roll in.
+    run_console_command(test.world_mut(), "weather auto").unwrap();
+    test.tick(1);
+    assert_eq!(test.world().resource::<Weather>().kind, WeatherKind::Storm);
+}